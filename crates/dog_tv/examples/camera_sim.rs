@@ -1,5 +1,6 @@
 #![cfg(feature = "std")]
 
+use dog_tv::renderer::background::Background;
 use dog_tv::renderer::camera::properties::RenderCameraProperties;
 use dog_tv::renderer::renderables::color::Color;
 use dog_tv::renderer::renderables::scene_renderable::make_line3;
@@ -42,6 +43,10 @@ pub async fn run_offscreen() {
     ));
 
     sim.update_3d_renderables(renderables3d);
+    sim.set_background(Background::VerticalGradient {
+        top: Color::new(0.55, 0.7, 0.9, 1.0),
+        bottom: Color::white(1.0),
+    });
 
     let result = sim.render(Isometry3::trans_z(-5.0));
 
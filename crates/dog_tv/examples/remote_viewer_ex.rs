@@ -0,0 +1,89 @@
+#![cfg(feature = "remote")]
+
+//! Run the viewer with `cargo run --example remote_viewer_ex --features remote`,
+//! then stream packets to it from another process with
+//! `cargo run --example remote_viewer_ex --features remote -- client`.
+
+use core::f64::consts::TAU;
+use dog_tv::renderer::camera::RenderCamera;
+use dog_tv::renderer::renderables::color::Color;
+use dog_tv::renderer::renderables::scene_renderable::axes::make_axis3;
+use dog_tv::renderer::renderables::scene_renderable::named_line3_at;
+use dog_tv::renderer::RenderContext;
+use dog_tv::sophus::lie::Isometry3;
+use dog_tv::viewer::packets::append_to_scene_packet;
+use dog_tv::viewer::packets::create_scene_packet;
+use dog_tv::viewer::packets::plot_view_packet::scalar_curve::ScalarCurveStyle;
+use dog_tv::viewer::packets::plot_view_packet::ClearCondition;
+use dog_tv::viewer::packets::plot_view_packet::LineType;
+use dog_tv::viewer::packets::plot_view_packet::PlotViewPacket;
+use dog_tv::viewer::packets::Packet;
+use dog_tv::viewer::remote::PacketAddress;
+use dog_tv::viewer::remote::PacketClient;
+use dog_tv::viewer::simple_viewer::SimpleViewer;
+use dog_tv::viewer::viewer_base::ViewerBaseConfig;
+
+fn run_client() {
+    let mut client = PacketClient::connect(&PacketAddress::default()).unwrap();
+
+    client
+        .send(&[create_scene_packet(
+            "remote scene",
+            RenderCamera::default(),
+            false,
+        )])
+        .unwrap();
+
+    let mut x: f64 = 0.0;
+    loop {
+        std::thread::sleep(std::time::Duration::from_millis(10));
+
+        let packets = vec![
+            append_to_scene_packet(
+                "remote scene",
+                vec![named_line3_at(
+                    "axes",
+                    make_axis3(Isometry3::identity()),
+                    Isometry3::rot_z(x),
+                )],
+            ),
+            Packet::Plot(vec![PlotViewPacket::append_to_curve(
+                ("remote curve", "sin"),
+                vec![(x, x.sin())].into(),
+                ScalarCurveStyle {
+                    color: Color::orange(),
+                    line_type: LineType::default(),
+                },
+                ClearCondition { max_x_range: TAU },
+                None,
+            )]),
+        ];
+        client.send(&packets).unwrap();
+
+        x += 0.01;
+    }
+}
+
+fn run_viewer() {
+    let (config, server) = ViewerBaseConfig::listen(PacketAddress::default()).unwrap();
+    println!("Listening on {:?}", server.address());
+
+    eframe::run_native(
+        "Remote Viewer Example",
+        dog_tv_viewer::recommened_eframe_native_options(),
+        Box::new(|cc| {
+            Ok(SimpleViewer::from_config(
+                RenderContext::from_egui_cc(cc),
+                config,
+            ))
+        }),
+    )
+    .unwrap();
+}
+
+fn main() {
+    match std::env::args().nth(1).as_deref() {
+        Some("client") => run_client(),
+        _ => run_viewer(),
+    }
+}
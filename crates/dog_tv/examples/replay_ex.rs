@@ -0,0 +1,98 @@
+#![cfg(feature = "recording")]
+
+//! Record packets with `cargo run --example replay_ex --features recording -- record log.dogl`,
+//! then replay them with `cargo run --example replay_ex --features recording -- replay log.dogl`.
+
+use core::f64::consts::TAU;
+use dog_tv::renderer::camera::RenderCamera;
+use dog_tv::renderer::renderables::color::Color;
+use dog_tv::renderer::renderables::scene_renderable::axes::make_axis3;
+use dog_tv::renderer::renderables::scene_renderable::named_line3_at;
+use dog_tv::renderer::RenderContext;
+use dog_tv::sophus::lie::Isometry3;
+use dog_tv::viewer::packets::append_to_scene_packet;
+use dog_tv::viewer::packets::create_scene_packet;
+use dog_tv::viewer::packets::plot_view_packet::scalar_curve::ScalarCurveStyle;
+use dog_tv::viewer::packets::plot_view_packet::ClearCondition;
+use dog_tv::viewer::packets::plot_view_packet::LineType;
+use dog_tv::viewer::packets::plot_view_packet::PlotViewPacket;
+use dog_tv::viewer::packets::Packet;
+use dog_tv::viewer::recording::PacketReplay;
+use dog_tv::viewer::simple_viewer::SimpleViewer;
+use thingbuf::mpsc::blocking::channel;
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let (mode, path) = match args.as_slice() {
+        [_, mode, path] => (mode.as_str(), path.clone()),
+        _ => {
+            eprintln!("usage: replay_ex record|replay <log-file>");
+            return;
+        }
+    };
+
+    let (message_tx, message_rx) = channel(50);
+
+    let replay = match mode {
+        "record" => {
+            std::thread::spawn(move || {
+                message_tx
+                    .send(vec![create_scene_packet(
+                        "scene",
+                        RenderCamera::default(),
+                        false,
+                    )])
+                    .unwrap();
+
+                let mut x: f64 = 0.0;
+                loop {
+                    std::thread::sleep(std::time::Duration::from_millis(10));
+                    let packets = vec![
+                        append_to_scene_packet(
+                            "scene",
+                            vec![named_line3_at(
+                                "axes",
+                                make_axis3(Isometry3::identity()),
+                                Isometry3::rot_z(x),
+                            )],
+                        ),
+                        Packet::Plot(vec![PlotViewPacket::append_to_curve(
+                            ("curve", "sin"),
+                            vec![(x, x.sin())].into(),
+                            ScalarCurveStyle {
+                                color: Color::orange(),
+                                line_type: LineType::default(),
+                            },
+                            ClearCondition { max_x_range: TAU },
+                            None,
+                        )]),
+                    ];
+                    if message_tx.send(packets).is_err() {
+                        return;
+                    }
+                    x += 0.01;
+                }
+            });
+            None
+        }
+        "replay" => Some(PacketReplay::open(&path).unwrap()),
+        _ => {
+            eprintln!("unknown mode {}, expected record or replay", mode);
+            return;
+        }
+    };
+
+    eframe::run_native(
+        "Replay Example",
+        dog_tv_viewer::recommened_eframe_native_options(),
+        Box::new(move |cc| {
+            let mut viewer = SimpleViewer::new(RenderContext::from_egui_cc(cc), message_rx);
+            match replay {
+                Some(replay) => viewer.start_replay(replay),
+                None => viewer.start_recording(&path).unwrap(),
+            }
+            Ok(viewer)
+        }),
+    )
+    .unwrap();
+}
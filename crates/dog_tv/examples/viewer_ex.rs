@@ -2,17 +2,36 @@
 
 use core::f64::consts::TAU;
 use dog_tv::examples::viewer_example::make_distorted_frame;
+use dog_tv::examples::viewer_example::make_example_image;
 use dog_tv::renderer::camera::clipping_planes::ClippingPlanes;
 use dog_tv::renderer::camera::properties::RenderCameraProperties;
 use dog_tv::renderer::camera::RenderCamera;
 use dog_tv::renderer::renderables::color::Color;
 use dog_tv::renderer::renderables::frame::ImageFrame;
+use dog_tv::renderer::renderables::pixel_renderable::make_arrow2;
+use dog_tv::renderer::renderables::pixel_renderable::make_box2;
+use dog_tv::renderer::renderables::pixel_renderable::make_circle2;
 use dog_tv::renderer::renderables::pixel_renderable::make_line2;
 use dog_tv::renderer::renderables::pixel_renderable::make_point2;
+use dog_tv::renderer::renderables::scene_renderable::axes::axes3;
 use dog_tv::renderer::renderables::scene_renderable::make_line3;
 use dog_tv::renderer::renderables::scene_renderable::make_mesh3_at;
 use dog_tv::renderer::renderables::scene_renderable::make_point3;
+use dog_tv::renderer::renderables::scene_renderable::make_textured_mesh3_at;
+use dog_tv::renderer::renderables::scene_renderable::named_camera_frustum3;
+use dog_tv::renderer::renderables::scene_renderable::named_gaussian3;
+use dog_tv::renderer::renderables::scene_renderable::named_point_stream3;
+use dog_tv::renderer::renderables::scene_renderable::named_polyline3;
+use dog_tv::renderer::renderables::scene_renderable::named_scalar_point3_at;
+use dog_tv::renderer::renderables::scene_renderable::uncertainty::Gaussian3;
+use dog_tv::renderer::renderables::scene_renderable::uncertainty::UncertaintyStyle;
+use dog_tv::renderer::renderables::scene_renderable::Point3;
+use dog_tv::renderer::renderables::scene_renderable::PointBudget;
+use dog_tv::renderer::renderables::scene_renderable::PolylineVertex3;
+use dog_tv::renderer::renderables::scene_renderable::ScalarField3;
 use dog_tv::renderer::RenderContext;
+use dog_tv::sophus::core::linalg::SMat;
+use dog_tv::sophus::core::linalg::SVec;
 use dog_tv::sophus::core::linalg::VecF64;
 use dog_tv::sophus::image::intensity_image::intensity_arc_image::IsIntensityArcImage;
 use dog_tv::sophus::image::mut_image::MutImageF32;
@@ -32,6 +51,8 @@ use dog_tv::viewer::packets::plot_view_packet::ClearCondition;
 use dog_tv::viewer::packets::plot_view_packet::LineType;
 use dog_tv::viewer::packets::plot_view_packet::PlotViewPacket;
 use dog_tv::viewer::packets::Packet;
+use dog_tv::viewer::picking::PickEvent;
+use dog_tv::viewer::picking::PickTrigger;
 use dog_tv::viewer::simple_viewer::SimpleViewer;
 use std::thread::spawn;
 
@@ -41,6 +62,7 @@ fn create_distorted_image_packet() -> Packet {
         scene_renderables: vec![],
         pixel_renderables: vec![],
         frame: Some(make_distorted_frame()),
+        ..Default::default()
     };
 
     image_packet.pixel_renderables.push(make_point2(
@@ -55,6 +77,25 @@ fn create_distorted_image_packet() -> Packet {
         &Color::blue(),
         5.0,
     ));
+    image_packet.pixel_renderables.push(make_box2(
+        "boxes2",
+        &[[[10.0, 8.0], [40.0, 30.0]]],
+        &Color::green(),
+        2.0,
+    ));
+    image_packet.pixel_renderables.push(make_circle2(
+        "circles2",
+        &[[16.0, 12.0], [32.0, 24.0]],
+        6.0,
+        &Color::red(),
+        1.0,
+    ));
+    image_packet.pixel_renderables.push(make_arrow2(
+        "arrows2",
+        &[[[16.0, 12.0], [32.0, 24.0]]],
+        &Color::blue(),
+        2.0,
+    ));
 
     image_packet.scene_renderables.push(make_line3(
         "lines3",
@@ -87,6 +128,7 @@ fn create_tiny_image_view_packet() -> Packet {
         scene_renderables: vec![],
         pixel_renderables: vec![],
         frame: Some(ImageFrame::from_image(&img.to_shared().to_rgba())),
+        ..Default::default()
     };
 
     image_packet.pixel_renderables.push(make_line2(
@@ -142,6 +184,59 @@ fn create_scene(pinhole: bool) -> Vec<Packet> {
         5.0,
     ));
 
+    scene_renderables.push(named_gaussian3(
+        "landmark uncertainty",
+        vec![Gaussian3 {
+            mean: [1.0, 0.0, 0.0].into(),
+            covariance: SMat::<f32, 3, 3>::from_diagonal(&[0.01, 0.002, 0.005].into()),
+            color: Color {
+                r: 1.0,
+                g: 0.0,
+                b: 1.0,
+                a: 0.5,
+            },
+        }],
+        3.0,
+        UncertaintyStyle::Ellipsoid,
+    ));
+
+    let trajectory: Vec<_> = (0..100)
+        .map(|i| Isometry3::rot_z(TAU * i as f64 / 100.0).group_mul(&Isometry3::trans_x(2.0)))
+        .collect();
+    scene_renderables.push(
+        axes3(&trajectory)
+            .scale(0.1)
+            .line_width(2.0)
+            .build_instanced("trajectory"),
+    );
+
+    // a wavy surface, colored by height or by distance from its center
+    let mut surface: Vec<SVec<f32, 3>> = vec![];
+    for i in 0..50 {
+        for j in 0..50 {
+            let x = 0.04 * i as f32 - 1.0;
+            let y = 0.04 * j as f32 - 1.0;
+            surface.push([x, y, 0.1 * (5.0 * x).sin() * (5.0 * y).cos()].into());
+        }
+    }
+    let fields = vec![
+        ScalarField3 {
+            name: "height".to_string(),
+            values: surface.iter().map(|p| p[2]).collect(),
+        },
+        ScalarField3 {
+            name: "range".to_string(),
+            values: surface.iter().map(|p| p.norm()).collect(),
+        },
+    ];
+    scene_renderables.push(named_scalar_point3_at(
+        "surface",
+        surface,
+        fields,
+        3.0,
+        Isometry3::trans_z(2.0),
+    ));
+
     let blue = Color::blue();
     scene_renderables.push(make_mesh3_at(
         "mesh",
@@ -149,6 +244,36 @@ fn create_scene(pinhole: bool) -> Vec<Packet> {
         Isometry3::trans_z(3.0),
     ));
 
+    scene_renderables.push(make_textured_mesh3_at(
+        "textured quad",
+        &[
+            [
+                ([-1.0, -1.0, 0.0], [0.0, 0.0]),
+                ([1.0, -1.0, 0.0], [1.0, 0.0]),
+                ([1.0, 1.0, 0.0], [1.0, 1.0]),
+            ],
+            [
+                ([-1.0, -1.0, 0.0], [0.0, 0.0]),
+                ([1.0, 1.0, 0.0], [1.0, 1.0]),
+                ([-1.0, 1.0, 0.0], [0.0, 1.0]),
+            ],
+        ],
+        &make_example_image(ImageSize::new(64, 48)),
+        Isometry3::trans_z(4.0),
+    ));
+
+    let image_size = ImageSize::new(64, 48);
+    scene_renderables.push(named_camera_frustum3(
+        "camera",
+        &ImageFrame::try_from(
+            &make_example_image(image_size),
+            &RenderCameraProperties::default_from(image_size),
+        )
+        .unwrap(),
+        0.5,
+        Isometry3::trans_x(-2.0),
+    ));
+
     let label = match pinhole {
         false => "scene - distorted",
         true => "scene - bird's eye",
@@ -213,21 +338,83 @@ pub fn run_viewer_example() {
                 ),
             ];
 
-            let packets = vec![Packet::Plot(plot_packets)];
+            // a growing cylinder, decimated once it exceeds the budget
+            let ring = (0..100)
+                .map(|i| {
+                    let phi = TAU * i as f64 / 100.0;
+                    Point3 {
+                        p: [
+                            (1.5 * phi.cos()) as f32,
+                            (1.5 * phi.sin()) as f32,
+                            (0.1 * x) as f32,
+                        ]
+                        .into(),
+                        color: Color::orange(),
+                        point_size: 2.0,
+                    }
+                })
+                .collect();
+
+            // a trajectory circling above the cylinder, fading from red to blue over the window
+            let phase = (0.5 + 0.5 * (3.0 * x).sin()) as f32;
+            let trajectory = vec![PolylineVertex3 {
+                p: [
+                    (2.0 * (3.0 * x).cos()) as f32,
+                    (2.0 * (3.0 * x).sin()) as f32,
+                    (0.1 * x + 0.5) as f32,
+                ]
+                .into(),
+                color: Color {
+                    r: phase,
+                    g: 0.2,
+                    b: 1.0 - phase,
+                    a: 1.0,
+                },
+                t: x,
+            }];
+
+            let packets = vec![
+                Packet::Plot(plot_packets),
+                append_to_scene_packet(
+                    "scene - bird's eye",
+                    vec![
+                        named_point_stream3(
+                            "streamed points",
+                            ring,
+                            PointBudget {
+                                max_points: 100_000,
+                                voxel_size: 0.005,
+                            },
+                        ),
+                        named_polyline3("trajectory", trajectory, 4.0, Some(TAU)),
+                    ],
+                ),
+            ];
             message_tx.send(packets).unwrap();
 
             x += 0.01;
         }
     });
 
+    let (pick_tx, pick_rx) = channel::<PickEvent>(10);
+    spawn(move || {
+        while let Some(event) = pick_rx.recv() {
+            if event.trigger == PickTrigger::Click {
+                println!(
+                    "clicked {:?} `{}` #{} in {}",
+                    event.pick.kind, event.pick.name, event.pick.primitive_index, event.view_label
+                );
+            }
+        }
+    });
+
     eframe::run_native(
         "Viewer Example",
         dog_tv_viewer::recommened_eframe_native_options(),
         Box::new(|cc| {
-            Ok(SimpleViewer::new(
-                RenderContext::from_egui_cc(cc),
-                message_rx,
-            ))
+            let mut viewer = SimpleViewer::new(RenderContext::from_egui_cc(cc), message_rx);
+            viewer.set_pick_sender(pick_tx);
+            Ok(viewer)
         }),
     )
     .unwrap();
@@ -0,0 +1,92 @@
+use dog_tv::renderer::camera::properties::RenderCameraProperties;
+use dog_tv::renderer::offscreen_renderer::OffscreenRenderer;
+use dog_tv::renderer::renderables::color::Color;
+use dog_tv::renderer::renderables::scene_renderable::make_mesh3_at;
+use dog_tv::renderer::renderables::scene_renderable::named_instances3;
+use dog_tv::renderer::renderables::scene_renderable::Instance3;
+use dog_tv::renderer::renderables::scene_renderable::InstanceTemplate3;
+use dog_tv::renderer::renderables::scene_renderable::Triangle3;
+use dog_tv::renderer::types::ScenePickKind;
+use dog_tv::renderer::RenderContext;
+use dog_tv::sophus::core::linalg::SVec;
+use dog_tv::sophus::core::linalg::VecF64;
+use dog_tv::sophus::image::ImageSize;
+use dog_tv::sophus::lie::Isometry3;
+
+fn has_adapter() -> bool {
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+        backends: wgpu::Backends::all(),
+        ..Default::default()
+    });
+    block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default())).is_some()
+}
+
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+fn square(color: Color) -> Vec<Triangle3> {
+    let p = |x: f32, y: f32| SVec::<f32, 3>::new(x, y, 0.0);
+    let triangle = |p0, p1, p2| Triangle3 {
+        p0,
+        p1,
+        p2,
+        color0: color,
+        color1: color,
+        color2: color,
+        normals: None,
+    };
+    vec![
+        triangle(p(-1.0, -1.0), p(1.0, -1.0), p(1.0, 1.0)),
+        triangle(p(-1.0, -1.0), p(1.0, 1.0), p(-1.0, 1.0)),
+    ]
+}
+
+#[test]
+fn pick_instanced_entity() {
+    if !has_adapter() {
+        eprintln!("no wgpu adapter, skipping");
+        return;
+    }
+    let render_context = block_on(RenderContext::new());
+    let image_size = ImageSize::new(64, 48);
+    let mut renderer = OffscreenRenderer::new(
+        &render_context,
+        &RenderCameraProperties::default_from(image_size),
+    );
+
+    // a mesh behind the camera takes entity id 1, so that the instanced entity has id 2
+    renderer.update_scene(vec![
+        make_mesh3_at(
+            "behind",
+            &[(
+                [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+                Color::red(),
+            )],
+            Isometry3::trans_z(-5.0),
+        ),
+        named_instances3(
+            "squares",
+            InstanceTemplate3::Mesh(square(Color::blue())),
+            vec![
+                Instance3::new(Isometry3::trans_x(-100.0)),
+                Instance3::new(Isometry3::trans_z(5.0)),
+            ],
+        ),
+    ]);
+
+    let result = renderer
+        .render_params(&image_size, &Isometry3::identity())
+        .pick(Some(VecF64::<2>::new(32.0, 24.0)))
+        .render();
+
+    let pick = result
+        .scene_pick
+        .expect("the center pixel shows an instance");
+    assert_eq!(pick.kind, ScenePickKind::Instanced);
+    assert_eq!(pick.name, "squares");
+    assert_eq!(pick.primitive_index, 1);
+}
@@ -0,0 +1,27 @@
+use crate::renderables::color::Color;
+use sophus::image::arc_image::ArcImage4U8;
+
+/// Background of the scene, i.e. what is seen where no renderable is hit
+///
+/// The background is drawn before the scene and distorted along with it. It does not write the
+/// depth buffer, hence the depth image still reports the background as infinitely far away.
+#[derive(Clone, Debug, Default)]
+pub enum Background {
+    /// fully transparent, e.g. to show the background image of an image view
+    #[default]
+    Transparent,
+    /// solid color
+    Color(Color),
+    /// vertical gradient, from the top to the bottom of the viewport
+    VerticalGradient {
+        /// color at the top
+        top: Color,
+        /// color at the bottom
+        bottom: Color,
+    },
+    /// equirectangular environment image, with the world z axis pointing up
+    ///
+    /// The left and right image borders are in the direction of the world -x axis, the image
+    /// center in +x direction.
+    Environment(ArcImage4U8),
+}
@@ -1,6 +1,8 @@
 use sophus::core::linalg::VecF64;
 use sophus::image::ImageSize;
 use sophus::lie::prelude::IsVector;
+use sophus::sensor::camera_enum::perspective_camera::BrownConradyCameraF64;
+use sophus::sensor::camera_enum::perspective_camera::KannalaBrandtCameraF64;
 use sophus::sensor::camera_enum::perspective_camera::PinholeCameraF64;
 use sophus::sensor::camera_enum::perspective_camera::UnifiedCameraF64;
 use sophus::sensor::camera_enum::PerspectiveCameraEnum;
@@ -13,6 +15,10 @@ pub enum RenderIntrinsics {
     Pinhole(PinholeCameraF64),
     /// Unified camera model
     UnifiedExtended(UnifiedCameraF64),
+    /// Kannala-Brandt fisheye camera model
+    KannalaBrandt(KannalaBrandtCameraF64),
+    /// Brown-Conrady (radial-tangential) camera model
+    BrownConrady(BrownConradyCameraF64),
 }
 
 impl RenderIntrinsics {
@@ -20,8 +26,10 @@ impl RenderIntrinsics {
     pub fn new(camera: &DynCameraF64) -> RenderIntrinsics {
         match camera.model_enum() {
             PerspectiveCameraEnum::Pinhole(pinhole) => RenderIntrinsics::Pinhole(*pinhole),
-            PerspectiveCameraEnum::KannalaBrandt(_camera) => todo!(),
-            PerspectiveCameraEnum::BrownConrady(_camera) => todo!(),
+            PerspectiveCameraEnum::KannalaBrandt(camera) => {
+                RenderIntrinsics::KannalaBrandt(*camera)
+            }
+            PerspectiveCameraEnum::BrownConrady(camera) => RenderIntrinsics::BrownConrady(*camera),
             PerspectiveCameraEnum::UnifiedExtended(camera) => {
                 RenderIntrinsics::UnifiedExtended(*camera)
             }
@@ -33,6 +41,8 @@ impl RenderIntrinsics {
         match self {
             RenderIntrinsics::Pinhole(pinhole) => pinhole.image_size(),
             RenderIntrinsics::UnifiedExtended(unified) => unified.image_size(),
+            RenderIntrinsics::KannalaBrandt(kb) => kb.image_size(),
+            RenderIntrinsics::BrownConrady(bc) => bc.image_size(),
         }
     }
 
@@ -41,22 +51,47 @@ impl RenderIntrinsics {
         match self {
             RenderIntrinsics::Pinhole(pinhole) => pinhole.cam_unproj_with_z(uv, z),
             RenderIntrinsics::UnifiedExtended(unified) => unified.cam_unproj_with_z(uv, z),
+            RenderIntrinsics::KannalaBrandt(kb) => kb.cam_unproj_with_z(uv, z),
+            RenderIntrinsics::BrownConrady(bc) => bc.cam_unproj_with_z(uv, z),
         }
     }
 
     /// Return pinhole model
+    ///
+    /// For distorted camera models, this is a pinhole camera with half the focal length, so that
+    /// the field of view of the distorted camera is (mostly) covered.
     pub fn pinhole_model(&self) -> PinholeCameraF64 {
         match self {
             RenderIntrinsics::Pinhole(camera) => *camera,
-            RenderIntrinsics::UnifiedExtended(camera) => PinholeCameraF64::new(
-                &VecF64::<4>::from_array([
-                    0.5 * camera.params()[0],
-                    0.5 * camera.params()[1],
-                    camera.params()[2],
-                    camera.params()[3],
-                ]),
-                camera.image_size(),
-            ),
+            RenderIntrinsics::UnifiedExtended(camera) => {
+                Self::wide_pinhole(camera.params(), camera.image_size())
+            }
+            RenderIntrinsics::KannalaBrandt(camera) => {
+                Self::wide_pinhole(camera.params(), camera.image_size())
+            }
+            RenderIntrinsics::BrownConrady(camera) => {
+                Self::wide_pinhole(camera.params(), camera.image_size())
+            }
+        }
+    }
+
+    /// Map a pixel of the distorted image to the corresponding pixel of the pinhole model
+    ///
+    /// This is the mapping the scene renderer uses to warp the pinhole rendering into the
+    /// distorted image. For pinhole intrinsics, it is the identity.
+    pub fn undistort(&self, uv_distorted: &VecF64<2>) -> VecF64<2> {
+        match self {
+            RenderIntrinsics::Pinhole(_) => *uv_distorted,
+            _ => self
+                .pinhole_model()
+                .cam_proj(&self.cam_unproj_with_z(uv_distorted, 1.0)),
         }
     }
+
+    fn wide_pinhole<const N: usize>(params: &VecF64<N>, image_size: ImageSize) -> PinholeCameraF64 {
+        PinholeCameraF64::new(
+            &VecF64::<4>::from_array([0.5 * params[0], 0.5 * params[1], params[2], params[3]]),
+            image_size,
+        )
+    }
 }
@@ -4,7 +4,10 @@ use sophus::sensor::dyn_camera::DynCameraF64;
 use crate::camera::clipping_planes::ClippingPlanes;
 use crate::camera::clipping_planes::ClippingPlanesF64;
 use crate::camera::intrinsics::RenderIntrinsics;
+#[cfg(feature = "render")]
 use crate::uniform_buffers::CameraPropertiesUniform;
+#[cfg(feature = "render")]
+use crate::uniform_buffers::DistortionModel;
 
 /// Camera properties
 #[derive(Clone, Debug)]
@@ -50,32 +53,42 @@ impl RenderCameraProperties {
         }
     }
 
+    #[cfg(feature = "render")]
     pub(crate) fn to_uniform(&self) -> CameraPropertiesUniform {
-        match self.intrinsics {
-            RenderIntrinsics::Pinhole(pinhole) => CameraPropertiesUniform {
-                camera_image_width: pinhole.image_size().width as f32,
-                camera_image_height: pinhole.image_size().height as f32,
-                near: self.clipping_planes.near as f32,
-                far: self.clipping_planes.far as f32,
-                fx: pinhole.params()[0] as f32,
-                fy: pinhole.params()[1] as f32,
-                px: pinhole.params()[2] as f32,
-                py: pinhole.params()[3] as f32,
-                alpha: 0.0,
-                beta: 0.0,
-            },
-            RenderIntrinsics::UnifiedExtended(unified) => CameraPropertiesUniform {
-                camera_image_width: unified.image_size().width as f32,
-                camera_image_height: unified.image_size().height as f32,
-                near: self.clipping_planes.near as f32,
-                far: self.clipping_planes.far as f32,
-                fx: unified.params()[0] as f32,
-                fy: unified.params()[1] as f32,
-                px: unified.params()[2] as f32,
-                py: unified.params()[3] as f32,
-                alpha: unified.params()[4] as f32,
-                beta: unified.params()[5] as f32,
-            },
+        let (model, params): (DistortionModel, &[f64]) = match &self.intrinsics {
+            RenderIntrinsics::Pinhole(camera) => {
+                (DistortionModel::Pinhole, camera.params().as_slice())
+            }
+            RenderIntrinsics::UnifiedExtended(camera) => {
+                (DistortionModel::UnifiedExtended, camera.params().as_slice())
+            }
+            RenderIntrinsics::KannalaBrandt(camera) => {
+                (DistortionModel::KannalaBrandt, camera.params().as_slice())
+            }
+            RenderIntrinsics::BrownConrady(camera) => {
+                (DistortionModel::BrownConrady, camera.params().as_slice())
+            }
+        };
+
+        // The first four parameters are always fx, fy, px, py - followed by the distortion
+        // parameters, if any.
+        let mut distortion = [0.0; 8];
+        for (i, d) in params.iter().skip(4).enumerate() {
+            distortion[i] = *d as f32;
+        }
+
+        CameraPropertiesUniform {
+            camera_image_width: self.intrinsics.image_size().width as f32,
+            camera_image_height: self.intrinsics.image_size().height as f32,
+            near: self.clipping_planes.near as f32,
+            far: self.clipping_planes.far as f32,
+            fx: params[0] as f32,
+            fy: params[1] as f32,
+            px: params[2] as f32,
+            py: params[3] as f32,
+            model: model as u32,
+            _padding: [0; 3],
+            distortion,
         }
     }
 }
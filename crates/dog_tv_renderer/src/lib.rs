@@ -6,35 +6,55 @@
 
 /// Aspect ratio
 pub mod aspect_ratio;
+/// Scene background
+pub mod background;
 /// Render camera
 pub mod camera;
+/// Scene lighting
+pub mod lighting;
 /// The rendering implementation
+#[cfg(feature = "render")]
 pub mod offscreen_renderer;
 /// render uniforms
+#[cfg(feature = "render")]
 pub mod pipeline_builder;
 /// The pixel renderer for 2D rendering.
+#[cfg(feature = "render")]
 pub mod pixel_renderer;
 /// The render context
+#[cfg(feature = "render")]
 pub mod render_context;
 /// The renderable structs.
 pub mod renderables;
+/// Ground grid and other scene helpers
+pub mod scene_helpers;
 /// The scene renderer for 3D rendering.
+#[cfg(feature = "render")]
 pub mod scene_renderer;
 /// offscreen texture for rendering
+#[cfg(feature = "render")]
 pub mod textures;
 /// Types used in the renderer API
+#[cfg(feature = "render")]
 pub mod types;
 /// pipeline builder
+#[cfg(feature = "render")]
 pub mod uniform_buffers;
 
+#[cfg(feature = "render")]
 pub use crate::render_context::RenderContext;
 
 /// preludes
 pub mod preludes {
+    #[cfg(feature = "render")]
     pub(crate) use alloc::collections::btree_map::BTreeMap;
+    #[cfg(feature = "render")]
+    pub(crate) use alloc::collections::btree_set::BTreeSet;
+    #[cfg(feature = "render")]
     pub(crate) use alloc::format;
     pub(crate) use alloc::string::String;
     pub(crate) use alloc::string::ToString;
+    #[cfg(feature = "render")]
     pub(crate) use alloc::sync::Arc;
     pub(crate) use alloc::vec;
     pub(crate) use alloc::vec::Vec;
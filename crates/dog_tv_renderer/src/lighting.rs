@@ -0,0 +1,102 @@
+use sophus::core::linalg::VecF64;
+#[cfg(feature = "render")]
+use sophus::lie::Isometry3F64;
+
+/// Shading model of triangle meshes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ShadingModel {
+    /// vertex colors only, no lighting
+    Unlit,
+    /// ambient and diffuse lighting
+    #[default]
+    Lambertian,
+    /// ambient, diffuse and specular lighting
+    Phong,
+}
+
+/// Light source
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LightSource {
+    /// light attached to the camera, pointing along the viewing direction
+    Headlight,
+    /// distant light, direction the light travels in, expressed in the world frame
+    Directional(VecF64<3>),
+}
+
+/// Scene lighting, applied to triangle meshes
+///
+/// Meshes are lit from both sides, i.e. normals facing away from the camera are flipped.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SceneLighting {
+    /// shading model
+    pub shading: ShadingModel,
+    /// light source
+    pub light: LightSource,
+    /// ambient intensity in [0, 1], the remainder is diffuse
+    pub ambient: f32,
+    /// specular intensity, only used for Phong shading
+    pub specular: f32,
+    /// specular exponent, only used for Phong shading
+    pub shininess: f32,
+}
+
+impl Default for SceneLighting {
+    fn default() -> Self {
+        SceneLighting {
+            shading: ShadingModel::default(),
+            light: LightSource::Headlight,
+            ambient: 0.3,
+            specular: 0.3,
+            shininess: 32.0,
+        }
+    }
+}
+
+impl SceneLighting {
+    /// vertex colors only, as without lighting
+    pub fn unlit() -> Self {
+        SceneLighting {
+            shading: ShadingModel::Unlit,
+            ..Default::default()
+        }
+    }
+
+    #[cfg(feature = "render")]
+    pub(crate) fn to_uniform(self, world_from_camera: &Isometry3F64) -> LightingUniform {
+        let direction_in_camera = match self.light {
+            LightSource::Headlight => VecF64::<3>::new(0.0, 0.0, 1.0),
+            LightSource::Directional(direction_in_world) => {
+                world_from_camera.inverse().rotation().matrix() * direction_in_world
+            }
+        };
+        LightingUniform {
+            direction: [
+                direction_in_camera[0] as f32,
+                direction_in_camera[1] as f32,
+                direction_in_camera[2] as f32,
+                0.0,
+            ],
+            shading: match self.shading {
+                ShadingModel::Unlit => 0,
+                ShadingModel::Lambertian => 1,
+                ShadingModel::Phong => 2,
+            },
+            ambient: self.ambient,
+            specular: self.specular,
+            shininess: self.shininess,
+        }
+    }
+}
+
+#[repr(C)]
+// This is so we can store this in a buffer
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct LightingUniform {
+    // direction the light travels in, in the camera frame
+    pub(crate) direction: [f32; 4],
+    // 0: unlit, 1: lambertian, 2: phong
+    pub(crate) shading: u32,
+    pub(crate) ambient: f32,
+    pub(crate) specular: f32,
+    pub(crate) shininess: f32,
+}
@@ -1,25 +1,43 @@
+use crate::background::Background;
 use crate::camera::intrinsics::RenderIntrinsics;
 use crate::camera::properties::RenderCameraProperties;
+use crate::pixel_renderer::pixel_arrow::Arrow2dEntity;
 use crate::pixel_renderer::pixel_line::Line2dEntity;
 use crate::pixel_renderer::pixel_point::Point2dEntity;
+use crate::pixel_renderer::pixel_polygon::Polygon2dEntity;
+use crate::pixel_renderer::pixel_shape::Shape2dEntity;
+use crate::pixel_renderer::pixel_text::Text2dEntity;
 use crate::pixel_renderer::PixelRenderer;
 use crate::preludes::*;
 use crate::renderables::pixel_renderable::PixelRenderable;
+use crate::renderables::scene_renderable::LineSegments3;
 use crate::renderables::scene_renderable::SceneRenderable;
+use crate::renderables::scene_renderable::TriangleMesh3;
+use crate::scene_helpers::SceneHelpers;
 use crate::scene_renderer::distortion::DistortionRenderer;
+use crate::scene_renderer::instanced::InstancedEntity;
 use crate::scene_renderer::line::Line3dEntity;
 use crate::scene_renderer::mesh::Mesh3dEntity;
 use crate::scene_renderer::point::Point3dEntity;
+use crate::scene_renderer::scalar_point::ScalarPoint3dEntity;
+use crate::scene_renderer::text::Text3dEntity;
+use crate::scene_renderer::textured_mesh::TexturedMeshEntity;
 use crate::scene_renderer::SceneRenderer;
 use crate::textures::Textures;
+use crate::types::EntityIdImage;
 use crate::types::RenderResult;
 use crate::types::SceneFocusMarker;
+use crate::types::ScenePick;
 use crate::types::TranslationAndScaling;
 use crate::uniform_buffers::VertexShaderUniformBuffers;
 use crate::RenderContext;
+use sophus::core::linalg::VecF64;
 use sophus::core::IsTensorLike;
+use sophus::image::arc_image::ArcImage4F32;
 use sophus::image::arc_image::ArcImage4U8;
 use sophus::image::image_view::IsImageView;
+use sophus::image::mut_image::MutImage4F32;
+use sophus::image::prelude::IsMutImageView;
 use sophus::image::ImageSize;
 use sophus::lie::Isometry3F64;
 
@@ -43,13 +61,42 @@ struct RenderParams {
     maybe_marker: Option<SceneFocusMarker>,
     compute_depth_texture: bool,
     backface_culling: bool,
+    helpers: SceneHelpers,
+    background: Background,
     download_rgba: bool,
+    maybe_pick_pixel: Option<VecF64<2>>,
+    download_ground_truth: bool,
+}
+
+/// Camera sharing the scene of an offscreen renderer
+///
+/// Holds the camera properties and render targets of the camera only, hence many cameras can
+/// render a scene which is uploaded once, see `OffscreenRenderer::camera_render_params`.
+pub struct OffscreenCamera {
+    camera_properties: RenderCameraProperties,
+    textures: Textures,
+}
+
+impl OffscreenCamera {
+    /// create new camera from its intrinsics
+    pub fn new(render_context: &RenderContext, camera_properties: &RenderCameraProperties) -> Self {
+        Self {
+            camera_properties: camera_properties.clone(),
+            textures: Textures::new(render_context, &camera_properties.intrinsics.image_size()),
+        }
+    }
+
+    /// get camera properties
+    pub fn camera_properties(&self) -> &RenderCameraProperties {
+        &self.camera_properties
+    }
 }
 
 /// Render builder
 pub struct RenderBuilder<'a> {
     params: RenderParams,
     offscreen_renderer: &'a mut OffscreenRenderer,
+    maybe_camera: Option<&'a mut OffscreenCamera>,
 }
 
 impl<'a> RenderBuilder<'a> {
@@ -67,9 +114,14 @@ impl<'a> RenderBuilder<'a> {
                 maybe_marker: None,
                 compute_depth_texture: false,
                 backface_culling: false,
+                helpers: SceneHelpers::default(),
+                background: Background::default(),
                 download_rgba: false,
+                maybe_pick_pixel: None,
+                download_ground_truth: false,
             },
             offscreen_renderer,
+            maybe_camera: None,
         }
     }
 
@@ -97,15 +149,47 @@ impl<'a> RenderBuilder<'a> {
         self
     }
 
+    /// set scene helpers, none are drawn by default
+    pub fn helpers(mut self, helpers: SceneHelpers) -> Self {
+        self.params.helpers = helpers;
+        self
+    }
+
+    /// set background, transparent by default
+    pub fn background(mut self, background: Background) -> Self {
+        self.params.background = background;
+        self
+    }
+
     /// set download rgba
     pub fn download_rgba(mut self, download_rgba: bool) -> Self {
         self.params.download_rgba = download_rgba;
         self
     }
 
+    /// set pick pixel
+    ///
+    /// If set, the scene renderable and primitive visible at the given viewport pixel are
+    /// reported in `RenderResult::scene_pick`. Pixel renderables are not pickable.
+    pub fn pick(mut self, maybe_pixel: Option<VecF64<2>>) -> Self {
+        self.params.maybe_pick_pixel = maybe_pixel;
+        self
+    }
+
+    /// set download ground truth, i.e. the normal and entity id images
+    pub fn download_ground_truth(mut self, download_ground_truth: bool) -> Self {
+        self.params.download_ground_truth = download_ground_truth;
+        self
+    }
+
     /// render
     pub fn render(self) -> RenderResult {
-        self.offscreen_renderer.render_impl(&self.params)
+        match self.maybe_camera {
+            Some(camera) => self
+                .offscreen_renderer
+                .render_camera_impl(camera, &self.params),
+            None => self.offscreen_renderer.render_impl(&self.params),
+        }
     }
 }
 
@@ -220,10 +304,51 @@ impl OffscreenRenderer {
                         Point2dEntity::new(&self.render_context, &points),
                     );
                 }
+                PixelRenderable::Text(texts) => {
+                    let text_renderer = &mut self.pixel.text_renderer;
+                    let entity =
+                        Text2dEntity::new(&self.render_context, &texts, &text_renderer.glyph_atlas);
+                    text_renderer.glyph_atlas.upload(&self.render_context);
+                    text_renderer.texts_table.insert(texts.name.clone(), entity);
+                }
+                PixelRenderable::Ellipse(ellipses) => {
+                    self.pixel.shape_renderer.ellipses_table.insert(
+                        ellipses.name.clone(),
+                        Shape2dEntity::from_ellipses(&self.render_context, &ellipses),
+                    );
+                }
+                PixelRenderable::Box(boxes) => {
+                    self.pixel.shape_renderer.boxes_table.insert(
+                        boxes.name.clone(),
+                        Shape2dEntity::from_boxes(&self.render_context, &boxes),
+                    );
+                }
+                PixelRenderable::Polygon(polygons) => {
+                    self.pixel.polygon_renderer.polygons_table.insert(
+                        polygons.name.clone(),
+                        Polygon2dEntity::new(&self.render_context, &polygons),
+                    );
+                }
+                PixelRenderable::Arrow(arrows) => {
+                    self.pixel.arrow_renderer.arrows_table.insert(
+                        arrows.name.clone(),
+                        Arrow2dEntity::new(&self.render_context, &arrows),
+                    );
+                }
             }
         }
     }
 
+    /// delete 2d renderables by name
+    pub fn delete_pixels(&mut self, names: &[String]) {
+        self.pixel.remove(names);
+    }
+
+    /// clear all 2d renderables
+    pub fn clear_pixels(&mut self) {
+        self.pixel.clear();
+    }
+
     /// update 3d renerables
     pub fn update_scene(&mut self, renderables: Vec<SceneRenderable>) {
         for m in renderables {
@@ -246,10 +371,131 @@ impl OffscreenRenderer {
                         Mesh3dEntity::new(&self.render_context, &mesh),
                     );
                 }
+                SceneRenderable::TexturedMesh3(mesh) => {
+                    self.scene.textured_mesh_renderer.mesh_table.insert(
+                        mesh.name.clone(),
+                        TexturedMeshEntity::new(
+                            &self.render_context,
+                            &mesh,
+                            &self.scene.textured_mesh_renderer.texture_bind_group_layout,
+                        ),
+                    );
+                }
+                SceneRenderable::Text(texts) => {
+                    let text_renderer = &mut self.scene.text_renderer;
+                    let entity =
+                        Text3dEntity::new(&self.render_context, &texts, &text_renderer.glyph_atlas);
+                    text_renderer.glyph_atlas.upload(&self.render_context);
+                    text_renderer.text_table.insert(texts.name.clone(), entity);
+                }
+                SceneRenderable::Instanced(instances) => {
+                    self.scene.instanced_renderer.instanced_table.insert(
+                        instances.name.clone(),
+                        InstancedEntity::new(
+                            &self.render_context,
+                            instances,
+                            &self.scene.instanced_renderer.pick_id_bind_group_layout,
+                        ),
+                    );
+                }
+                SceneRenderable::PointStream(stream) => {
+                    self.scene
+                        .point_stream_renderer
+                        .append(&self.render_context, stream);
+                }
+                SceneRenderable::Polyline(polyline) => {
+                    self.scene
+                        .line_renderer
+                        .append_polyline(&self.render_context, polyline);
+                }
+                SceneRenderable::ScalarPoint(points) => {
+                    let scalar_point_renderer = &mut self.scene.scalar_point_renderer;
+                    let entity = ScalarPoint3dEntity::new(
+                        &self.render_context,
+                        &points,
+                        &scalar_point_renderer.coloring_bind_group_layout,
+                    );
+                    scalar_point_renderer
+                        .scalar_point_table
+                        .insert(points.name.clone(), entity);
+                }
+                SceneRenderable::CameraFrustum(frustum) => {
+                    let (lines, mesh) = frustum.tessellate();
+                    self.scene.line_renderer.line_table.insert(
+                        frustum.name.clone(),
+                        Line3dEntity::new(&self.render_context, &lines),
+                    );
+                    let textured_mesh_renderer = &mut self.scene.textured_mesh_renderer;
+                    match mesh {
+                        Some(mesh) => {
+                            textured_mesh_renderer.mesh_table.insert(
+                                frustum.name.clone(),
+                                TexturedMeshEntity::new(
+                                    &self.render_context,
+                                    &mesh,
+                                    &textured_mesh_renderer.texture_bind_group_layout,
+                                ),
+                            );
+                        }
+                        None => {
+                            textured_mesh_renderer.mesh_table.remove(&frustum.name);
+                        }
+                    }
+                }
+                SceneRenderable::Gaussian(gaussians) => {
+                    let (mesh, lines) = gaussians.tessellate();
+                    self.update_mesh_and_lines(&gaussians.name, mesh, lines);
+                }
+                SceneRenderable::PoseCovariance(poses) => {
+                    let (mesh, lines) = poses.tessellate();
+                    self.update_mesh_and_lines(&poses.name, mesh, lines);
+                }
             }
         }
     }
 
+    /// Replaces the named mesh and line segments of a tessellated renderable, a missing part
+    /// removes the previous one
+    fn update_mesh_and_lines(
+        &mut self,
+        name: &str,
+        mesh: Option<TriangleMesh3>,
+        lines: Option<LineSegments3>,
+    ) {
+        match mesh {
+            Some(mesh) => {
+                self.scene.mesh_renderer.mesh_table.insert(
+                    name.to_string(),
+                    Mesh3dEntity::new(&self.render_context, &mesh),
+                );
+            }
+            None => {
+                self.scene.mesh_renderer.mesh_table.remove(name);
+            }
+        }
+        match lines {
+            Some(lines) => {
+                self.scene.line_renderer.line_table.insert(
+                    name.to_string(),
+                    Line3dEntity::new(&self.render_context, &lines),
+                );
+            }
+            None => {
+                self.scene.line_renderer.line_table.remove(name);
+            }
+        }
+    }
+
+    /// delete 3d renderables by name
+    pub fn delete_scene(&mut self, names: &[String]) {
+        self.scene.remove(names);
+    }
+
+    /// clear all 3d renderables
+    pub fn clear_scene(&mut self) {
+        self.scene.clear();
+    }
+
     /// render
     pub fn render_params(
         &mut self,
@@ -259,6 +505,35 @@ impl OffscreenRenderer {
         RenderBuilder::new(*view_port_size, *world_from_camera, self)
     }
 
+    /// render the scene as seen by another camera, into the render targets of that camera
+    ///
+    /// The pixel renderables and background image of this renderer are drawn as well.
+    pub fn camera_render_params<'a>(
+        &'a mut self,
+        camera: &'a mut OffscreenCamera,
+        view_port_size: &ImageSize,
+        world_from_camera: &Isometry3F64,
+    ) -> RenderBuilder<'a> {
+        let mut builder = RenderBuilder::new(*view_port_size, *world_from_camera, self);
+        builder.maybe_camera = Some(camera);
+        builder
+    }
+
+    fn render_camera_impl(
+        &mut self,
+        camera: &mut OffscreenCamera,
+        params: &RenderParams,
+    ) -> RenderResult {
+        // The uniforms are rewritten by each render, hence only the camera properties and render
+        // targets need to be swapped in.
+        core::mem::swap(&mut self.camera_properties, &mut camera.camera_properties);
+        core::mem::swap(&mut self.textures, &mut camera.textures);
+        let result = self.render_impl(params);
+        core::mem::swap(&mut self.camera_properties, &mut camera.camera_properties);
+        core::mem::swap(&mut self.textures, &mut camera.textures);
+        result
+    }
+
     fn render_impl(&mut self, params: &RenderParams) -> RenderResult {
         if self.textures.view_port_size != params.view_port_size {
             self.textures = Textures::new(&self.render_context, &params.view_port_size);
@@ -271,6 +546,12 @@ impl OffscreenRenderer {
             params.view_port_size,
         );
 
+        self.scene
+            .background_renderer
+            .update(&self.render_context, &params.background);
+        self.scene.text_renderer.begin_frame(&self.render_context);
+        self.pixel.text_renderer.begin_frame(&self.render_context);
+
         let mut command_encoder = self
             .render_context
             .wgpu_device
@@ -283,12 +564,24 @@ impl OffscreenRenderer {
             &self.textures.rgbd,
             &self.textures.depth,
             params.backface_culling,
+            &params.helpers,
+            &params.background,
         );
 
         self.render_context
             .wgpu_queue
             .submit(Some(command_encoder.finish()));
 
+        let scene_pick = params
+            .maybe_pick_pixel
+            .and_then(|pixel| self.pick(params, &pixel));
+        let (normal_image, entity_id_image) = if params.download_ground_truth {
+            let (normal_image, entity_id_image) = self.ground_truth(params);
+            (Some(normal_image), Some(entity_id_image))
+        } else {
+            (None, None)
+        };
+
         let command_encoder = self
             .render_context
             .wgpu_device
@@ -345,6 +638,110 @@ impl OffscreenRenderer {
             rgba_egui_tex_id: self.textures.rgbd.egui_tex_id,
             depth_image,
             depth_egui_tex_id: self.textures.depth.visual_depth_texture.egui_tex_id,
+            scene_pick,
+            normal_image,
+            entity_id_image,
+        }
+    }
+
+    /// Maps a viewport pixel to the corresponding pixel of the undistorted pick textures
+    fn undistorted_pick_pixel(
+        &self,
+        params: &RenderParams,
+        view_port_pixel: &VecF64<2>,
+    ) -> Option<(usize, usize)> {
+        // The scene is rendered undistorted, hence the pixel is mapped just like in the
+        // distortion pass.
+        let image_size = self.camera_properties.intrinsics.image_size();
+        let scale = VecF64::<2>::new(
+            image_size.width as f64 / params.view_port_size.width as f64,
+            image_size.height as f64 / params.view_port_size.height as f64,
+        );
+        let uv_undistorted = self
+            .camera_properties
+            .intrinsics
+            .undistort(&view_port_pixel.component_mul(&scale));
+        let x = uv_undistorted[0] / scale[0];
+        let y = uv_undistorted[1] / scale[1];
+        if !(x >= 0.0
+            && y >= 0.0
+            && x < params.view_port_size.width as f64
+            && y < params.view_port_size.height as f64)
+        {
+            return None;
+        }
+        Some((x as usize, y as usize))
+    }
+
+    fn pick(&self, params: &RenderParams, view_port_pixel: &VecF64<2>) -> Option<ScenePick> {
+        let (x, y) = self.undistorted_pick_pixel(params, view_port_pixel)?;
+
+        let mut command_encoder = self
+            .render_context
+            .wgpu_device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        let entities = self.scene.paint_pick(
+            &self.render_context,
+            &params.scene_from_camera,
+            &mut command_encoder,
+            &self.textures.pick,
+        );
+        let [entity_id, primitive_index] = self.textures.pick.download_texel(
+            &self.render_context,
+            command_encoder,
+            x as u32,
+            y as u32,
+        );
+
+        let (kind, name) = entities.get((entity_id as usize).checked_sub(1)?)?;
+        Some(ScenePick {
+            name: name.clone(),
+            kind: *kind,
+            primitive_index: primitive_index as usize,
+        })
+    }
+
+    /// Renders the pick pass and maps its ids and normals to the (distorted) viewport
+    fn ground_truth(&self, params: &RenderParams) -> (ArcImage4F32, EntityIdImage) {
+        let mut command_encoder = self
+            .render_context
+            .wgpu_device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        let entities = self.scene.paint_pick(
+            &self.render_context,
+            &params.scene_from_camera,
+            &mut command_encoder,
+            &self.textures.pick,
+        );
+        let (ids, normals) = self.textures.pick.download_images(
+            &self.render_context,
+            command_encoder,
+            &params.view_port_size,
+        );
+
+        let size = params.view_port_size;
+        let mut normal_image = MutImage4F32::from_image_size(size);
+        let mut entity_ids = vec![0; size.width * size.height];
+        for v in 0..size.height {
+            for u in 0..size.width {
+                let Some((x, y)) =
+                    self.undistorted_pick_pixel(params, &VecF64::<2>::new(u as f64, v as f64))
+                else {
+                    continue;
+                };
+                let i = y * size.width + x;
+                entity_ids[v * size.width + u] = ids[i];
+                *normal_image.mut_pixel(u, v) = normals[i].into();
+            }
         }
+
+        (
+            ArcImage4F32::from(normal_image),
+            EntityIdImage {
+                image_size: size,
+                ids: entity_ids,
+                entities,
+            },
+        )
     }
 }
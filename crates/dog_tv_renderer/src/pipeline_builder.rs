@@ -1,4 +1,5 @@
 use crate::preludes::*;
+use crate::textures::pick::PickTextures;
 use crate::types::DOG_MULTISAMPLE_COUNT;
 use crate::uniform_buffers::VertexShaderUniformBuffers;
 use crate::RenderContext;
@@ -19,6 +20,13 @@ pub enum PipelineType {
     Scene,
 }
 
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PipelineVariant {
+    Opaque,
+    Transparent,
+    Pick,
+}
+
 /// builder
 pub struct PipelineBuilder {
     context: RenderContext,
@@ -78,11 +86,114 @@ impl IsVertex for PointVertex2 {
     }
 }
 
+/// 2d text vertex
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct TextVertex2 {
+    pub(crate) _pos: [f32; 2],
+    pub(crate) _offset: [f32; 2],
+    pub(crate) _tex: [f32; 2],
+    pub(crate) _color: [f32; 4],
+}
+
+impl IsVertex for TextVertex2 {
+    fn array_stride() -> wgpu::BufferAddress {
+        core::mem::size_of::<TextVertex2>() as wgpu::BufferAddress
+    }
+
+    fn attr() -> Vec<wgpu::VertexAttribute> {
+        wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2, 2 => Float32x2, 3 => Float32x4]
+            .to_vec()
+    }
+}
+
+/// 2d ellipse or box vertex, all six vertices of a shape share the same attributes
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct ShapeVertex2 {
+    pub(crate) _center: [f32; 2],
+    pub(crate) _half_size: [f32; 2],
+    pub(crate) _angle: f32,
+    pub(crate) _line_width: f32,
+    pub(crate) _color: [f32; 4],
+    pub(crate) _fill_color: [f32; 4],
+    // 0: ellipse, 1: box
+    pub(crate) _kind: u32,
+}
+
+impl IsVertex for ShapeVertex2 {
+    fn array_stride() -> wgpu::BufferAddress {
+        core::mem::size_of::<ShapeVertex2>() as wgpu::BufferAddress
+    }
+
+    fn attr() -> Vec<wgpu::VertexAttribute> {
+        wgpu::vertex_attr_array![
+            0 => Float32x2,
+            1 => Float32x2,
+            2 => Float32,
+            3 => Float32,
+            4 => Float32x4,
+            5 => Float32x4,
+            6 => Uint32
+        ]
+        .to_vec()
+    }
+}
+
+/// 2d polygon vertex
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct PolygonVertex2 {
+    pub(crate) _pos: [f32; 2],
+    // outline offset in viewport pixels, zero for fill triangles
+    pub(crate) _offset: [f32; 2],
+    pub(crate) _color: [f32; 4],
+}
+
+impl IsVertex for PolygonVertex2 {
+    fn array_stride() -> wgpu::BufferAddress {
+        core::mem::size_of::<PolygonVertex2>() as wgpu::BufferAddress
+    }
+
+    fn attr() -> Vec<wgpu::VertexAttribute> {
+        wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2, 2 => Float32x4].to_vec()
+    }
+}
+
+/// 2d arrow vertex, all nine vertices of an arrow share the same attributes
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct ArrowVertex2 {
+    pub(crate) _tail: [f32; 2],
+    pub(crate) _tip: [f32; 2],
+    pub(crate) _color: [f32; 4],
+    pub(crate) _line_width: f32,
+    pub(crate) _head_size: f32,
+}
+
+impl IsVertex for ArrowVertex2 {
+    fn array_stride() -> wgpu::BufferAddress {
+        core::mem::size_of::<ArrowVertex2>() as wgpu::BufferAddress
+    }
+
+    fn attr() -> Vec<wgpu::VertexAttribute> {
+        wgpu::vertex_attr_array![
+            0 => Float32x2,
+            1 => Float32x2,
+            2 => Float32x4,
+            3 => Float32,
+            4 => Float32
+        ]
+        .to_vec()
+    }
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Pod, Zeroable)]
 pub(crate) struct MeshVertex3 {
     pub(crate) _pos: [f32; 3],
     pub(crate) _color: [f32; 4],
+    pub(crate) _normal: [f32; 3],
 }
 
 impl IsVertex for MeshVertex3 {
@@ -91,7 +202,7 @@ impl IsVertex for MeshVertex3 {
     }
 
     fn attr() -> Vec<wgpu::VertexAttribute> {
-        wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x4].to_vec()
+        wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x4, 2 => Float32x3].to_vec()
     }
 }
 
@@ -101,8 +212,11 @@ impl IsVertex for MeshVertex3 {
 pub(crate) struct LineVertex3 {
     pub(crate) _p0: [f32; 3],
     pub(crate) _p1: [f32; 3],
-    pub(crate) _color: [f32; 4],
+    // color at p0
+    pub(crate) _color0: [f32; 4],
     pub(crate) _line_width: f32,
+    // color at p1
+    pub(crate) _color1: [f32; 4],
 }
 
 impl IsVertex for LineVertex3 {
@@ -111,8 +225,51 @@ impl IsVertex for LineVertex3 {
     }
 
     fn attr() -> Vec<wgpu::VertexAttribute> {
-        wgpu::vertex_attr_array![0 => Float32x3, 1=>Float32x3, 2 => Float32x4, 3 => Float32]
-            .to_vec()
+        wgpu::vertex_attr_array![
+            0 => Float32x3,
+            1 => Float32x3,
+            2 => Float32x4,
+            3 => Float32,
+            4 => Float32x4
+        ]
+        .to_vec()
+    }
+}
+
+/// Per-instance data of instanced scene renderables
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub(crate) struct InstanceVertex3 {
+    // columns of the scaled rotation and translation of entity_from_instance
+    pub(crate) _scaled_rotation_x: [f32; 3],
+    pub(crate) _scaled_rotation_y: [f32; 3],
+    pub(crate) _scaled_rotation_z: [f32; 3],
+    pub(crate) _translation: [f32; 3],
+    pub(crate) _color: [f32; 4],
+    // index of the instance within its entity, used for picking
+    pub(crate) _index: u32,
+}
+
+impl IsVertex for InstanceVertex3 {
+    fn array_stride() -> wgpu::BufferAddress {
+        core::mem::size_of::<InstanceVertex3>() as wgpu::BufferAddress
+    }
+
+    fn step_mode() -> wgpu::VertexStepMode {
+        wgpu::VertexStepMode::Instance
+    }
+
+    // locations start at 8, after those of the template vertices
+    fn attr() -> Vec<wgpu::VertexAttribute> {
+        wgpu::vertex_attr_array![
+            8 => Float32x3,
+            9 => Float32x3,
+            10 => Float32x3,
+            11 => Float32x3,
+            12 => Float32x4,
+            13 => Uint32
+        ]
+        .to_vec()
     }
 }
 
@@ -135,6 +292,97 @@ impl IsVertex for PointVertex3 {
     }
 }
 
+/// Per-point data of streamed point clouds
+///
+/// Each point is one instance of a six-vertex quad, hence there is no per-vertex buffer.
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub(crate) struct StreamPointVertex3 {
+    pub(crate) _pos: [f32; 3],
+    pub(crate) _point_size: f32,
+    pub(crate) _color: [f32; 4],
+    // index of the point within its entity, used for picking
+    pub(crate) _index: u32,
+}
+
+impl IsVertex for StreamPointVertex3 {
+    fn array_stride() -> wgpu::BufferAddress {
+        core::mem::size_of::<StreamPointVertex3>() as wgpu::BufferAddress
+    }
+
+    fn step_mode() -> wgpu::VertexStepMode {
+        wgpu::VertexStepMode::Instance
+    }
+
+    fn attr() -> Vec<wgpu::VertexAttribute> {
+        wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32, 2 => Float32x4, 3 => Uint32].to_vec()
+    }
+}
+
+/// Per-point position and size of point clouds with scalar fields
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub(crate) struct ScalarPointVertex3 {
+    pub(crate) _pos: [f32; 3],
+    pub(crate) _point_size: f32,
+}
+
+impl IsVertex for ScalarPointVertex3 {
+    fn array_stride() -> wgpu::BufferAddress {
+        core::mem::size_of::<ScalarPointVertex3>() as wgpu::BufferAddress
+    }
+
+    fn step_mode() -> wgpu::VertexStepMode {
+        wgpu::VertexStepMode::Instance
+    }
+
+    fn attr() -> Vec<wgpu::VertexAttribute> {
+        wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32].to_vec()
+    }
+}
+
+/// Per-point value of the scalar field a point cloud is colored by
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub(crate) struct ScalarVertex {
+    pub(crate) _value: f32,
+}
+
+impl IsVertex for ScalarVertex {
+    fn array_stride() -> wgpu::BufferAddress {
+        core::mem::size_of::<ScalarVertex>() as wgpu::BufferAddress
+    }
+
+    fn step_mode() -> wgpu::VertexStepMode {
+        wgpu::VertexStepMode::Instance
+    }
+
+    fn attr() -> Vec<wgpu::VertexAttribute> {
+        wgpu::vertex_attr_array![2 => Float32].to_vec()
+    }
+}
+
+/// 3d text vertex
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub(crate) struct TextVertex3 {
+    pub(crate) _pos: [f32; 3],
+    pub(crate) _offset: [f32; 2],
+    pub(crate) _tex: [f32; 2],
+    pub(crate) _color: [f32; 4],
+}
+
+impl IsVertex for TextVertex3 {
+    fn array_stride() -> wgpu::BufferAddress {
+        core::mem::size_of::<TextVertex3>() as wgpu::BufferAddress
+    }
+
+    fn attr() -> Vec<wgpu::VertexAttribute> {
+        wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x2, 2 => Float32x2, 3 => Float32x4]
+            .to_vec()
+    }
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Pod, Zeroable)]
 pub(crate) struct TexturedMeshVertex3 {
@@ -187,35 +435,197 @@ impl PipelineBuilder {
         name: String,
         shader: &wgpu::ShaderModule,
         cull_mode: Option<wgpu::Face>,
+    ) -> wgpu::RenderPipeline {
+        self.create_with_bind_group_layouts::<Vertex>(name, shader, cull_mode, &[])
+    }
+
+    /// Creates a pipeline with additional bind group layouts, which are bound after the uniforms
+    /// starting at group 1.
+    pub(crate) fn create_with_bind_group_layouts<Vertex: IsVertex>(
+        &self,
+        name: String,
+        shader: &wgpu::ShaderModule,
+        cull_mode: Option<wgpu::Face>,
+        extra_bind_group_layouts: &[&wgpu::BindGroupLayout],
+    ) -> wgpu::RenderPipeline {
+        self.create_impl::<Vertex>(
+            name,
+            shader,
+            cull_mode,
+            extra_bind_group_layouts,
+            PipelineVariant::Opaque,
+            None,
+        )
+    }
+
+    /// Creates a pipeline for transparent geometry: colors are alpha blended and the depth buffer
+    /// is tested but not written.
+    pub(crate) fn create_transparent<Vertex: IsVertex>(
+        &self,
+        name: String,
+        shader: &wgpu::ShaderModule,
+        cull_mode: Option<wgpu::Face>,
+        extra_bind_group_layouts: &[&wgpu::BindGroupLayout],
+    ) -> wgpu::RenderPipeline {
+        self.create_impl::<Vertex>(
+            name,
+            shader,
+            cull_mode,
+            extra_bind_group_layouts,
+            PipelineVariant::Transparent,
+            None,
+        )
+    }
+
+    /// Creates a pipeline for the pick pass: the `fs_pick` entry point writes entity id and
+    /// primitive index into the (not multisampled) pick id texture, and the surface normal into
+    /// the pick normal texture. Nothing is culled, so that everything visible can be picked.
+    pub(crate) fn create_pick<Vertex: IsVertex>(
+        &self,
+        name: String,
+        shader: &wgpu::ShaderModule,
+        extra_bind_group_layouts: &[&wgpu::BindGroupLayout],
+    ) -> wgpu::RenderPipeline {
+        self.create_impl::<Vertex>(
+            name,
+            shader,
+            None,
+            extra_bind_group_layouts,
+            PipelineVariant::Pick,
+            None,
+        )
+    }
+
+    /// Creates a pipeline with a second, per-instance vertex buffer at slot 1
+    pub(crate) fn create_instanced<Vertex: IsVertex, Instance: IsVertex>(
+        &self,
+        name: String,
+        shader: &wgpu::ShaderModule,
+        cull_mode: Option<wgpu::Face>,
+        extra_bind_group_layouts: &[&wgpu::BindGroupLayout],
+        variant: PipelineVariant,
+    ) -> wgpu::RenderPipeline {
+        let instance_attributes = Instance::attr();
+        self.create_impl::<Vertex>(
+            name,
+            shader,
+            // the pick pass never culls, see `create_pick`
+            cull_mode.filter(|_| variant != PipelineVariant::Pick),
+            extra_bind_group_layouts,
+            variant,
+            Some(wgpu::VertexBufferLayout {
+                array_stride: Instance::array_stride(),
+                step_mode: Instance::step_mode(),
+                attributes: &instance_attributes,
+            }),
+        )
+    }
+
+    /// Creates a pipeline without vertex buffers, for full-screen passes which derive their
+    /// geometry from the vertex index. Colors are alpha blended and the depth buffer is tested
+    /// but not written.
+    pub(crate) fn create_fullscreen(
+        &self,
+        name: String,
+        shader: &wgpu::ShaderModule,
+        extra_bind_group_layouts: &[&wgpu::BindGroupLayout],
+    ) -> wgpu::RenderPipeline {
+        self.create_with_buffers(
+            name,
+            shader,
+            None,
+            extra_bind_group_layouts,
+            PipelineVariant::Transparent,
+            &[],
+        )
+    }
+
+    fn create_impl<Vertex: IsVertex>(
+        &self,
+        name: String,
+        shader: &wgpu::ShaderModule,
+        cull_mode: Option<wgpu::Face>,
+        extra_bind_group_layouts: &[&wgpu::BindGroupLayout],
+        variant: PipelineVariant,
+        instance_buffer_layout: Option<wgpu::VertexBufferLayout>,
+    ) -> wgpu::RenderPipeline {
+        let vertex_attributes = Vertex::attr();
+        let mut buffers = vec![wgpu::VertexBufferLayout {
+            array_stride: Vertex::array_stride(),
+            step_mode: Vertex::step_mode(),
+            attributes: &vertex_attributes,
+        }];
+        buffers.extend(instance_buffer_layout);
+        self.create_with_buffers(
+            name,
+            shader,
+            cull_mode,
+            extra_bind_group_layouts,
+            variant,
+            &buffers,
+        )
+    }
+
+    fn create_with_buffers(
+        &self,
+        name: String,
+        shader: &wgpu::ShaderModule,
+        cull_mode: Option<wgpu::Face>,
+        extra_bind_group_layouts: &[&wgpu::BindGroupLayout],
+        variant: PipelineVariant,
+        buffers: &[wgpu::VertexBufferLayout],
     ) -> wgpu::RenderPipeline {
         let device = self.context.wgpu_device.clone();
 
+        let mut bind_group_layouts = vec![&self.uniforms.render_bind_group_layout];
+        bind_group_layouts.extend_from_slice(extra_bind_group_layouts);
+
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some(&format!(
                 "`{}` `{:?}` pipeline layout",
                 name, self.pipeline_type
             )),
-            bind_group_layouts: &[&self.uniforms.render_bind_group_layout],
+            bind_group_layouts: &bind_group_layouts,
             push_constant_ranges: &[],
         });
 
+        let targets = match variant {
+            PipelineVariant::Pick => vec![
+                Some(wgpu::ColorTargetState {
+                    format: PickTextures::ID_FORMAT,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                }),
+                Some(wgpu::ColorTargetState {
+                    format: PickTextures::NORMAL_FORMAT,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                }),
+            ],
+            _ => vec![Some(wgpu::ColorTargetState {
+                format: self.rgba_target.rgba_output_format,
+                blend: (variant == PipelineVariant::Transparent)
+                    .then_some(wgpu::BlendState::ALPHA_BLENDING),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        };
+
         device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: Some(&format!("`{}` `{:?}` pipeline", name, self.pipeline_type)),
             layout: Some(&pipeline_layout),
             vertex: wgpu::VertexState {
                 module: shader,
                 entry_point: "vs_main",
-                buffers: &[wgpu::VertexBufferLayout {
-                    array_stride: Vertex::array_stride(),
-                    step_mode: Vertex::step_mode(),
-                    attributes: &Vertex::attr(),
-                }],
+                buffers,
                 compilation_options: Default::default(),
             },
             fragment: Some(wgpu::FragmentState {
                 module: shader,
-                entry_point: "fs_main",
-                targets: &[Some(self.rgba_target.rgba_output_format.into())],
+                entry_point: match variant {
+                    PipelineVariant::Pick => "fs_pick",
+                    _ => "fs_main",
+                },
+                targets: &targets,
                 compilation_options: Default::default(),
             }),
             primitive: wgpu::PrimitiveState {
@@ -224,8 +634,16 @@ impl PipelineBuilder {
                 cull_mode,
                 ..Default::default()
             },
-            depth_stencil: self.depth_stencil.clone(),
+            depth_stencil: self
+                .depth_stencil
+                .clone()
+                .map(|depth_stencil| DepthStencilState {
+                    depth_write_enabled: depth_stencil.depth_write_enabled
+                        && variant != PipelineVariant::Transparent,
+                    ..depth_stencil
+                }),
             multisample: match self.pipeline_type {
+                PipelineType::Scene if variant == PipelineVariant::Pick => Default::default(),
                 PipelineType::Scene => wgpu::MultisampleState {
                     count: DOG_MULTISAMPLE_COUNT,
                     mask: !0,
@@ -1,13 +1,25 @@
+/// Pixel arrow renderer
+pub mod pixel_arrow;
 /// Line renderer
 pub mod pixel_line;
 /// Pixel point renderer
 pub mod pixel_point;
+/// Pixel polygon renderer
+pub mod pixel_polygon;
+/// Pixel ellipse and box renderer
+pub mod pixel_shape;
+/// Pixel text renderer
+pub mod pixel_text;
 
 use crate::pipeline_builder::PipelineBuilder;
 use crate::pipeline_builder::PointVertex2;
 use crate::pipeline_builder::TargetTexture;
+use crate::pixel_renderer::pixel_arrow::PixelArrowRenderer;
 use crate::pixel_renderer::pixel_line::PixelLineRenderer;
 use crate::pixel_renderer::pixel_point::PixelPointRenderer;
+use crate::pixel_renderer::pixel_polygon::PixelPolygonRenderer;
+use crate::pixel_renderer::pixel_shape::PixelShapeRenderer;
+use crate::pixel_renderer::pixel_text::PixelTextRenderer;
 use crate::preludes::*;
 use crate::types::SceneFocusMarker;
 use crate::uniform_buffers::VertexShaderUniformBuffers;
@@ -17,6 +29,10 @@ use crate::RenderContext;
 pub struct PixelRenderer {
     pub(crate) line_renderer: PixelLineRenderer,
     pub(crate) point_renderer: PixelPointRenderer,
+    pub(crate) text_renderer: PixelTextRenderer,
+    pub(crate) shape_renderer: PixelShapeRenderer,
+    pub(crate) polygon_renderer: PixelPolygonRenderer,
+    pub(crate) arrow_renderer: PixelArrowRenderer,
     pub(crate) pixel_pipeline_builder: PipelineBuilder,
 }
 
@@ -34,10 +50,38 @@ impl PixelRenderer {
         Self {
             line_renderer: PixelLineRenderer::new(render_context, &pixel_pipeline_builder),
             point_renderer: PixelPointRenderer::new(render_context, &pixel_pipeline_builder),
+            text_renderer: PixelTextRenderer::new(render_context, &pixel_pipeline_builder),
+            shape_renderer: PixelShapeRenderer::new(render_context, &pixel_pipeline_builder),
+            polygon_renderer: PixelPolygonRenderer::new(render_context, &pixel_pipeline_builder),
+            arrow_renderer: PixelArrowRenderer::new(render_context, &pixel_pipeline_builder),
             pixel_pipeline_builder,
         }
     }
 
+    /// Remove the named renderables, names which are not present are ignored
+    pub fn remove(&mut self, names: &[String]) {
+        for name in names {
+            self.line_renderer.lines_table.remove(name);
+            self.point_renderer.points_table.remove(name);
+            self.text_renderer.texts_table.remove(name);
+            self.shape_renderer.ellipses_table.remove(name);
+            self.shape_renderer.boxes_table.remove(name);
+            self.polygon_renderer.polygons_table.remove(name);
+            self.arrow_renderer.arrows_table.remove(name);
+        }
+    }
+
+    /// Remove all renderables
+    pub fn clear(&mut self) {
+        self.line_renderer.lines_table.clear();
+        self.point_renderer.points_table.clear();
+        self.text_renderer.texts_table.clear();
+        self.shape_renderer.ellipses_table.clear();
+        self.shape_renderer.boxes_table.clear();
+        self.polygon_renderer.polygons_table.clear();
+        self.arrow_renderer.arrows_table.clear();
+    }
+
     pub(crate) fn show_interaction_marker(
         &self,
         state: &RenderContext,
@@ -93,7 +137,12 @@ impl PixelRenderer {
             &[],
         );
 
+        // filled shapes first, so that lines, points and labels stay visible on top
+        self.polygon_renderer.paint(&mut render_pass);
+        self.shape_renderer.paint(&mut render_pass);
         self.line_renderer.paint(&mut render_pass);
+        self.arrow_renderer.paint(&mut render_pass);
         self.point_renderer.paint(&mut render_pass);
+        self.text_renderer.paint(&mut render_pass);
     }
 }
@@ -0,0 +1,84 @@
+use crate::pipeline_builder::ArrowVertex2;
+use crate::pipeline_builder::PipelineBuilder;
+use crate::preludes::*;
+use crate::renderables::pixel_renderable::Arrows2;
+use crate::RenderContext;
+use wgpu::util::DeviceExt;
+
+pub(crate) struct Arrow2dEntity {
+    pub(crate) vertex_data: Vec<ArrowVertex2>,
+    pub(crate) vertex_buffer: wgpu::Buffer,
+}
+
+impl Arrow2dEntity {
+    pub(crate) fn new(render_context: &RenderContext, arrows: &Arrows2) -> Self {
+        let mut vertex_data = vec![];
+        for arrow in arrows.arrows.iter() {
+            // shaft quad and head triangle are expanded in the vertex shader
+            let v = ArrowVertex2 {
+                _tail: [arrow.tail[0], arrow.tail[1]],
+                _tip: [arrow.tip[0], arrow.tip[1]],
+                _color: [arrow.color.r, arrow.color.g, arrow.color.b, arrow.color.a],
+                _line_width: arrow.line_width,
+                _head_size: arrow.head_size,
+            };
+            vertex_data.extend_from_slice(&[v; 9]);
+        }
+
+        let vertex_buffer =
+            render_context
+                .wgpu_device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some(&format!("Pixel arrow vertex buffer: {}", arrows.name)),
+                    contents: bytemuck::cast_slice(&vertex_data),
+                    usage: wgpu::BufferUsages::VERTEX,
+                });
+
+        Self {
+            vertex_data,
+            vertex_buffer,
+        }
+    }
+}
+
+/// Pixel arrow renderer
+pub struct PixelArrowRenderer {
+    pub(crate) pipeline: wgpu::RenderPipeline,
+    pub(crate) arrows_table: BTreeMap<String, Arrow2dEntity>,
+}
+
+impl PixelArrowRenderer {
+    /// Create a new pixel arrow renderer
+    pub fn new(render_context: &RenderContext, pixel_pipelines: &PipelineBuilder) -> Self {
+        let device = &render_context.wgpu_device;
+
+        let arrow_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("pixel arrow shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                format!(
+                    "{} {}",
+                    include_str!("./../shaders/utils.wgsl"),
+                    include_str!("./../shaders/pixel_arrow.wgsl")
+                )
+                .into(),
+            ),
+        });
+
+        Self {
+            pipeline: pixel_pipelines.create::<ArrowVertex2>(
+                "arrow".to_string(),
+                &arrow_shader,
+                None,
+            ),
+            arrows_table: BTreeMap::new(),
+        }
+    }
+
+    pub(crate) fn paint<'rp>(&'rp self, render_pass: &mut wgpu::RenderPass<'rp>) {
+        render_pass.set_pipeline(&self.pipeline);
+        for (_name, arrows) in self.arrows_table.iter() {
+            render_pass.set_vertex_buffer(0, arrows.vertex_buffer.slice(..));
+            render_pass.draw(0..arrows.vertex_data.len() as u32, 0..1);
+        }
+    }
+}
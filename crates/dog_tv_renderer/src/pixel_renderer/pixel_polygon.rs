@@ -0,0 +1,304 @@
+use crate::pipeline_builder::PipelineBuilder;
+use crate::pipeline_builder::PolygonVertex2;
+use crate::preludes::*;
+use crate::renderables::pixel_renderable::Polygons2;
+use crate::RenderContext;
+use sophus::core::linalg::SVec;
+use wgpu::util::DeviceExt;
+
+pub(crate) struct Polygon2dEntity {
+    pub(crate) vertex_data: Vec<PolygonVertex2>,
+    pub(crate) vertex_buffer: wgpu::Buffer,
+}
+
+impl Polygon2dEntity {
+    /// Longest miter of the outline, relative to the line width, before sharp corners are cut
+    const MITER_LIMIT: f32 = 4.0;
+
+    pub(crate) fn new(render_context: &RenderContext, polygons: &Polygons2) -> Self {
+        let mut vertex_data = vec![];
+        for polygon in polygons.polygons.iter() {
+            let mut points = polygon.points.clone();
+            points.dedup();
+            while points.len() > 1 && points.first() == points.last() {
+                points.pop();
+            }
+            if points.len() < 3 {
+                continue;
+            }
+
+            if let Some(fill_color) = polygon.fill_color {
+                let color = [fill_color.r, fill_color.g, fill_color.b, fill_color.a];
+                for i in triangulate(&points) {
+                    vertex_data.push(PolygonVertex2 {
+                        _pos: [points[i][0], points[i][1]],
+                        _offset: [0.0, 0.0],
+                        _color: color,
+                    });
+                }
+            }
+
+            if polygon.line_width > 0.0 {
+                let color = [
+                    polygon.color.r,
+                    polygon.color.g,
+                    polygon.color.b,
+                    polygon.color.a,
+                ];
+                let offsets = miter_offsets(&points, 0.5 * polygon.line_width);
+                let n = points.len();
+                for i in 0..n {
+                    let j = (i + 1) % n;
+                    let vertex = |k: usize, sign: f32| PolygonVertex2 {
+                        _pos: [points[k][0], points[k][1]],
+                        _offset: [sign * offsets[k][0], sign * offsets[k][1]],
+                        _color: color,
+                    };
+                    vertex_data.push(vertex(i, 1.0));
+                    vertex_data.push(vertex(i, -1.0));
+                    vertex_data.push(vertex(j, 1.0));
+                    vertex_data.push(vertex(i, -1.0));
+                    vertex_data.push(vertex(j, 1.0));
+                    vertex_data.push(vertex(j, -1.0));
+                }
+            }
+        }
+
+        let vertex_buffer =
+            render_context
+                .wgpu_device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some(&format!("Pixel polygon vertex buffer: {}", polygons.name)),
+                    contents: bytemuck::cast_slice(&vertex_data),
+                    usage: wgpu::BufferUsages::VERTEX,
+                });
+
+        Self {
+            vertex_data,
+            vertex_buffer,
+        }
+    }
+}
+
+fn cross(a: SVec<f32, 2>, b: SVec<f32, 2>) -> f32 {
+    a[0] * b[1] - a[1] * b[0]
+}
+
+/// Offsets of the outline corners, along the miter of the two adjacent edges
+fn miter_offsets(points: &[SVec<f32, 2>], half_width: f32) -> Vec<SVec<f32, 2>> {
+    let n = points.len();
+    (0..n)
+        .map(|i| {
+            let prev = points[(i + n - 1) % n];
+            let next = points[(i + 1) % n];
+            let d0 = (points[i] - prev).normalize();
+            let d1 = (next - points[i]).normalize();
+            let n0 = SVec::<f32, 2>::new(d0[1], -d0[0]);
+            let n1 = SVec::<f32, 2>::new(d1[1], -d1[0]);
+            let sum = n0 + n1;
+            if sum.norm() < 1e-6 {
+                // the outline turns back on itself
+                return n1 * half_width;
+            }
+            let miter = sum.normalize();
+            let scale = (1.0 / miter.dot(&n1)).min(Polygon2dEntity::MITER_LIMIT);
+            miter * scale * half_width
+        })
+        .collect()
+}
+
+/// Triangulates a simple polygon by ear clipping, returns three point indices per triangle
+fn triangulate(points: &[SVec<f32, 2>]) -> Vec<usize> {
+    let n = points.len();
+    let twice_area: f32 = (0..n).map(|i| cross(points[i], points[(i + 1) % n])).sum();
+    let orientation = twice_area.signum();
+
+    let mut remaining: Vec<usize> = (0..n).collect();
+    let mut triangles = vec![];
+    let mut i = 0;
+    let mut attempts = 0;
+    while remaining.len() > 3 && attempts < remaining.len() {
+        let m = remaining.len();
+        let (ia, ib, ic) = (
+            remaining[(i + m - 1) % m],
+            remaining[i % m],
+            remaining[(i + 1) % m],
+        );
+        let (a, b, c) = (points[ia], points[ib], points[ic]);
+        let is_convex = orientation * cross(b - a, c - b) > 0.0;
+        let is_ear = is_convex
+            && remaining.iter().all(|&k| {
+                k == ia
+                    || k == ib
+                    || k == ic
+                    // points on the boundary of the ear block it as well
+                    || !(orientation * cross(b - a, points[k] - a) >= 0.0
+                        && orientation * cross(c - b, points[k] - b) >= 0.0
+                        && orientation * cross(a - c, points[k] - c) >= 0.0)
+            });
+        if is_ear {
+            triangles.extend_from_slice(&[ia, ib, ic]);
+            remaining.remove(i % m);
+            attempts = 0;
+        } else {
+            i += 1;
+            attempts += 1;
+        }
+        i %= remaining.len();
+    }
+    // self-intersecting polygons may have no ears left, the rest is not filled
+    if remaining.len() == 3 {
+        triangles.extend_from_slice(&remaining);
+    }
+    triangles
+}
+
+/// Pixel polygon renderer
+///
+/// Fills are triangulated once on upload, outlines are drawn as mitered quads per edge.
+pub struct PixelPolygonRenderer {
+    pub(crate) pipeline: wgpu::RenderPipeline,
+    pub(crate) polygons_table: BTreeMap<String, Polygon2dEntity>,
+}
+
+impl PixelPolygonRenderer {
+    /// Create a new pixel polygon renderer
+    pub fn new(render_context: &RenderContext, pixel_pipelines: &PipelineBuilder) -> Self {
+        let device = &render_context.wgpu_device;
+
+        let polygon_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("pixel polygon shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                format!(
+                    "{} {}",
+                    include_str!("./../shaders/utils.wgsl"),
+                    include_str!("./../shaders/pixel_polygon.wgsl")
+                )
+                .into(),
+            ),
+        });
+
+        Self {
+            pipeline: pixel_pipelines.create_transparent::<PolygonVertex2>(
+                "polygon".to_string(),
+                &polygon_shader,
+                None,
+                &[],
+            ),
+            polygons_table: BTreeMap::new(),
+        }
+    }
+
+    pub(crate) fn paint<'rp>(&'rp self, render_pass: &mut wgpu::RenderPass<'rp>) {
+        render_pass.set_pipeline(&self.pipeline);
+        for (_name, polygons) in self.polygons_table.iter() {
+            render_pass.set_vertex_buffer(0, polygons.vertex_buffer.slice(..));
+            render_pass.draw(0..polygons.vertex_data.len() as u32, 0..1);
+        }
+    }
+}
+
+#[test]
+fn triangulate_concave_collinear_and_both_windings() {
+    let signed_area = |points: &[SVec<f32, 2>]| {
+        let n = points.len();
+        0.5 * (0..n)
+            .map(|i| cross(points[i], points[(i + 1) % n]))
+            .sum::<f32>()
+    };
+    let check = |coordinates: &[[f32; 2]], expected_area: f32| {
+        for reversed in [false, true] {
+            let mut points: Vec<_> = coordinates
+                .iter()
+                .map(|p| SVec::<f32, 2>::new(p[0], p[1]))
+                .collect();
+            if reversed {
+                points.reverse();
+            }
+            let area = signed_area(&points);
+            assert!((area.abs() - expected_area).abs() < 1e-5);
+
+            let indices = triangulate(&points);
+            assert_eq!(indices.len(), 3 * (points.len() - 2));
+            let mut triangle_area_sum = 0.0;
+            for triangle in indices.chunks(3) {
+                let triangle_area = signed_area(&[
+                    points[triangle[0]],
+                    points[triangle[1]],
+                    points[triangle[2]],
+                ]);
+                // same winding as the polygon, i.e. no triangle outside of it
+                assert!(triangle_area * area.signum() >= 0.0);
+                triangle_area_sum += triangle_area;
+            }
+            assert!((triangle_area_sum - area).abs() < 1e-5);
+        }
+    };
+
+    // L shape, concave at (1, 1)
+    check(
+        &[
+            [0.0, 0.0],
+            [2.0, 0.0],
+            [2.0, 1.0],
+            [1.0, 1.0],
+            [1.0, 2.0],
+            [0.0, 2.0],
+        ],
+        3.0,
+    );
+    // arrow head, concave at (1, 1)
+    check(&[[0.0, 0.0], [4.0, 1.0], [0.0, 2.0], [1.0, 1.0]], 3.0);
+    // square with collinear vertices along two edges
+    check(
+        &[
+            [0.0, 0.0],
+            [1.0, 0.0],
+            [2.0, 0.0],
+            [2.0, 2.0],
+            [1.0, 2.0],
+            [0.0, 2.0],
+        ],
+        4.0,
+    );
+}
+
+#[test]
+fn miter_offsets_of_corners() {
+    let square: Vec<_> = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]]
+        .iter()
+        .map(|p| SVec::<f32, 2>::new(p[0], p[1]))
+        .collect();
+    for points in [square.clone(), square.iter().rev().copied().collect()] {
+        let offsets = miter_offsets(&points, 0.5);
+        for (point, offset) in points.iter().zip(offsets.iter()) {
+            // along the diagonal through the corner, sqrt(2) times the half width
+            let diagonal = point - SVec::<f32, 2>::new(0.5, 0.5);
+            assert!((offset.norm() - 0.5 * 2.0_f32.sqrt()).abs() < 1e-5);
+            assert!(cross(*offset, diagonal).abs() < 1e-5);
+        }
+    }
+
+    // collinear vertex: plain normal of the edges
+    let offsets = miter_offsets(
+        &[
+            SVec::<f32, 2>::new(0.0, 0.0),
+            SVec::<f32, 2>::new(1.0, 0.0),
+            SVec::<f32, 2>::new(2.0, 0.0),
+            SVec::<f32, 2>::new(1.0, 1.0),
+        ],
+        0.5,
+    );
+    assert!((offsets[1] - SVec::<f32, 2>::new(0.0, -0.5)).norm() < 1e-5);
+
+    // sharp corner: miter is cut at the limit
+    let offsets = miter_offsets(
+        &[
+            SVec::<f32, 2>::new(0.0, 0.0),
+            SVec::<f32, 2>::new(100.0, 1.0),
+            SVec::<f32, 2>::new(0.0, 2.0),
+        ],
+        0.5,
+    );
+    assert!((offsets[1].norm() - 0.5 * Polygon2dEntity::MITER_LIMIT).abs() < 1e-4);
+}
@@ -0,0 +1,122 @@
+use crate::pipeline_builder::PipelineBuilder;
+use crate::pipeline_builder::ShapeVertex2;
+use crate::preludes::*;
+use crate::renderables::color::Color;
+use crate::renderables::pixel_renderable::Boxes2;
+use crate::renderables::pixel_renderable::Ellipses2;
+use crate::RenderContext;
+use wgpu::util::DeviceExt;
+
+pub(crate) struct Shape2dEntity {
+    pub(crate) vertex_data: Vec<ShapeVertex2>,
+    pub(crate) vertex_buffer: wgpu::Buffer,
+}
+
+impl Shape2dEntity {
+    const ELLIPSE: u32 = 0;
+    const BOX: u32 = 1;
+
+    pub(crate) fn from_ellipses(render_context: &RenderContext, ellipses: &Ellipses2) -> Self {
+        let mut vertex_data = vec![];
+        for ellipse in ellipses.ellipses.iter() {
+            let v = ShapeVertex2 {
+                _center: [ellipse.center[0], ellipse.center[1]],
+                _half_size: [ellipse.radii[0], ellipse.radii[1]],
+                _angle: ellipse.angle,
+                _line_width: ellipse.line_width,
+                _color: to_array(&ellipse.color),
+                _fill_color: ellipse.fill_color.as_ref().map_or([0.0; 4], to_array),
+                _kind: Self::ELLIPSE,
+            };
+            vertex_data.extend_from_slice(&[v; 6]);
+        }
+        Self::new(render_context, &ellipses.name, vertex_data)
+    }
+
+    pub(crate) fn from_boxes(render_context: &RenderContext, boxes: &Boxes2) -> Self {
+        let mut vertex_data = vec![];
+        for b in boxes.boxes.iter() {
+            let center = 0.5 * (b.min + b.max);
+            let half_size = 0.5 * (b.max - b.min).abs();
+            let v = ShapeVertex2 {
+                _center: [center[0], center[1]],
+                _half_size: [half_size[0], half_size[1]],
+                _angle: 0.0,
+                _line_width: b.line_width,
+                _color: to_array(&b.color),
+                _fill_color: b.fill_color.as_ref().map_or([0.0; 4], to_array),
+                _kind: Self::BOX,
+            };
+            vertex_data.extend_from_slice(&[v; 6]);
+        }
+        Self::new(render_context, &boxes.name, vertex_data)
+    }
+
+    fn new(render_context: &RenderContext, name: &str, vertex_data: Vec<ShapeVertex2>) -> Self {
+        let vertex_buffer =
+            render_context
+                .wgpu_device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some(&format!("Pixel shape vertex buffer: {}", name)),
+                    contents: bytemuck::cast_slice(&vertex_data),
+                    usage: wgpu::BufferUsages::VERTEX,
+                });
+
+        Self {
+            vertex_data,
+            vertex_buffer,
+        }
+    }
+}
+
+fn to_array(color: &Color) -> [f32; 4] {
+    [color.r, color.g, color.b, color.a]
+}
+
+/// Pixel ellipse and box renderer
+///
+/// Each shape is a single quad, outline and fill are evaluated per fragment using the signed
+/// distance to the shape, and are hence anti-aliased at any zoom level.
+pub struct PixelShapeRenderer {
+    pub(crate) pipeline: wgpu::RenderPipeline,
+    pub(crate) ellipses_table: BTreeMap<String, Shape2dEntity>,
+    pub(crate) boxes_table: BTreeMap<String, Shape2dEntity>,
+}
+
+impl PixelShapeRenderer {
+    /// Create a new pixel shape renderer
+    pub fn new(render_context: &RenderContext, pixel_pipelines: &PipelineBuilder) -> Self {
+        let device = &render_context.wgpu_device;
+
+        let shape_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("pixel shape shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                format!(
+                    "{} {}",
+                    include_str!("./../shaders/utils.wgsl"),
+                    include_str!("./../shaders/pixel_shape.wgsl")
+                )
+                .into(),
+            ),
+        });
+
+        Self {
+            pipeline: pixel_pipelines.create_transparent::<ShapeVertex2>(
+                "shape".to_string(),
+                &shape_shader,
+                None,
+                &[],
+            ),
+            ellipses_table: BTreeMap::new(),
+            boxes_table: BTreeMap::new(),
+        }
+    }
+
+    pub(crate) fn paint<'rp>(&'rp self, render_pass: &mut wgpu::RenderPass<'rp>) {
+        render_pass.set_pipeline(&self.pipeline);
+        for (_name, shapes) in self.boxes_table.iter().chain(self.ellipses_table.iter()) {
+            render_pass.set_vertex_buffer(0, shapes.vertex_buffer.slice(..));
+            render_pass.draw(0..shapes.vertex_data.len() as u32, 0..1);
+        }
+    }
+}
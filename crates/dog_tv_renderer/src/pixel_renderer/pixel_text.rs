@@ -0,0 +1,105 @@
+use crate::pipeline_builder::PipelineBuilder;
+use crate::pipeline_builder::TextVertex2;
+use crate::preludes::*;
+use crate::renderables::pixel_renderable::Texts2;
+use crate::textures::glyph_atlas::GlyphAtlas;
+use crate::RenderContext;
+use wgpu::util::DeviceExt;
+
+pub(crate) struct Text2dEntity {
+    pub(crate) texts: Texts2,
+    pub(crate) vertex_data: Vec<TextVertex2>,
+    pub(crate) vertex_buffer: wgpu::Buffer,
+}
+
+impl Text2dEntity {
+    pub(crate) fn new(render_context: &RenderContext, texts: &Texts2, atlas: &GlyphAtlas) -> Self {
+        let mut vertex_data = vec![];
+        for text in texts.texts.iter() {
+            for quad in atlas.layout(&text.text, text.font_size, text.anchor) {
+                for (offset, tex) in quad.vertices() {
+                    vertex_data.push(TextVertex2 {
+                        _pos: [text.p[0], text.p[1]],
+                        _offset: offset,
+                        _tex: tex,
+                        _color: [text.color.r, text.color.g, text.color.b, text.color.a],
+                    });
+                }
+            }
+        }
+
+        let vertex_buffer =
+            render_context
+                .wgpu_device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some(&format!("Pixel text vertex buffer: {}", texts.name)),
+                    contents: bytemuck::cast_slice(&vertex_data),
+                    usage: wgpu::BufferUsages::VERTEX,
+                });
+
+        Self {
+            texts: texts.clone(),
+            vertex_data,
+            vertex_buffer,
+        }
+    }
+}
+
+/// Pixel text renderer
+pub struct PixelTextRenderer {
+    pub(crate) pipeline: wgpu::RenderPipeline,
+    pub(crate) glyph_atlas: GlyphAtlas,
+    pub(crate) texts_table: BTreeMap<String, Text2dEntity>,
+}
+
+impl PixelTextRenderer {
+    /// Create a new pixel text renderer
+    pub fn new(render_context: &RenderContext, pixel_pipelines: &PipelineBuilder) -> Self {
+        let device = &render_context.wgpu_device;
+
+        let text_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("pixel text shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                format!(
+                    "{} {}",
+                    include_str!("./../shaders/utils.wgsl"),
+                    include_str!("./../shaders/pixel_text.wgsl")
+                )
+                .into(),
+            ),
+        });
+
+        let glyph_atlas = GlyphAtlas::new(render_context);
+
+        Self {
+            pipeline: pixel_pipelines.create_transparent::<TextVertex2>(
+                "text".to_string(),
+                &text_shader,
+                None,
+                &[&glyph_atlas.bind_group_layout],
+            ),
+            glyph_atlas,
+            texts_table: BTreeMap::new(),
+        }
+    }
+
+    /// Start a new frame of the glyph atlas, and lay out all texts again if it was rebuilt
+    pub(crate) fn begin_frame(&mut self, render_context: &RenderContext) {
+        if !self.glyph_atlas.begin_frame() {
+            return;
+        }
+        for entity in self.texts_table.values_mut() {
+            *entity = Text2dEntity::new(render_context, &entity.texts, &self.glyph_atlas);
+        }
+        self.glyph_atlas.upload(render_context);
+    }
+
+    pub(crate) fn paint<'rp>(&'rp self, render_pass: &mut wgpu::RenderPass<'rp>) {
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(1, &self.glyph_atlas.bind_group, &[]);
+        for (_name, text) in self.texts_table.iter() {
+            render_pass.set_vertex_buffer(0, text.vertex_buffer.slice(..));
+            render_pass.draw(0..text.vertex_data.len() as u32, 0..1);
+        }
+    }
+}
@@ -339,3 +339,37 @@ impl Color {
         Self::new(0.25, 0.25, 0.25, a)
     }
 }
+
+/// Colormap of scalar fields, applied on the GPU
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Colormap {
+    /// blue to white to red to black, as sophus' `BlueWhiteRedBlackColorMap`
+    #[default]
+    BlueWhiteRedBlack,
+    /// black to white
+    Grayscale,
+    /// perceptually uniform dark blue to yellow
+    Viridis,
+    /// rainbow-like dark blue to dark red
+    Turbo,
+}
+
+impl Colormap {
+    /// All colormaps
+    pub const ALL: [Colormap; 4] = [
+        Colormap::BlueWhiteRedBlack,
+        Colormap::Grayscale,
+        Colormap::Viridis,
+        Colormap::Turbo,
+    ];
+
+    /// Name shown in the viewer
+    pub fn label(&self) -> &'static str {
+        match self {
+            Colormap::BlueWhiteRedBlack => "blue-white-red-black",
+            Colormap::Grayscale => "grayscale",
+            Colormap::Viridis => "viridis",
+            Colormap::Turbo => "turbo",
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use crate::preludes::*;
 use crate::renderables::color::Color;
+use sophus::core::linalg::SMat;
 use sophus::core::linalg::SVec;
 
 /// Pixel renderable
@@ -9,6 +10,16 @@ pub enum PixelRenderable {
     Line(LineSegments2),
     /// 2D point cloud
     Point(PointCloud2),
+    /// 2D text labels
+    Text(Texts2),
+    /// 2D ellipses and circles
+    Ellipse(Ellipses2),
+    /// 2D axis-aligned boxes
+    Box(Boxes2),
+    /// 2D polygons
+    Polygon(Polygons2),
+    /// 2D arrows
+    Arrow(Arrows2),
 }
 
 /// named line segments
@@ -27,6 +38,46 @@ pub fn named_point2(name: impl ToString, points: Vec<Point2>) -> PixelRenderable
     })
 }
 
+/// named text labels
+pub fn named_text2(name: impl ToString, texts: Vec<Text2>) -> PixelRenderable {
+    PixelRenderable::Text(Texts2 {
+        name: name.to_string(),
+        texts,
+    })
+}
+
+/// named ellipses
+pub fn named_ellipse2(name: impl ToString, ellipses: Vec<Ellipse2>) -> PixelRenderable {
+    PixelRenderable::Ellipse(Ellipses2 {
+        name: name.to_string(),
+        ellipses,
+    })
+}
+
+/// named boxes
+pub fn named_box2(name: impl ToString, boxes: Vec<Box2>) -> PixelRenderable {
+    PixelRenderable::Box(Boxes2 {
+        name: name.to_string(),
+        boxes,
+    })
+}
+
+/// named polygons
+pub fn named_polygon2(name: impl ToString, polygons: Vec<Polygon2>) -> PixelRenderable {
+    PixelRenderable::Polygon(Polygons2 {
+        name: name.to_string(),
+        polygons,
+    })
+}
+
+/// named arrows
+pub fn named_arrow2(name: impl ToString, arrows: Vec<Arrow2>) -> PixelRenderable {
+    PixelRenderable::Arrow(Arrows2 {
+        name: name.to_string(),
+        arrows,
+    })
+}
+
 /// make lines 2d
 pub fn make_line2(
     name: impl ToString,
@@ -73,6 +124,65 @@ pub fn make_point2(
     PixelRenderable::Point(cloud)
 }
 
+/// make 2d circle outlines
+pub fn make_circle2(
+    name: impl ToString,
+    centers: &[impl HasToVec2F32],
+    radius: f32,
+    color: &Color,
+    line_width: f32,
+) -> PixelRenderable {
+    named_ellipse2(
+        name,
+        centers
+            .iter()
+            .map(|center| Ellipse2::circle(center.to_vec2(), radius, color, line_width))
+            .collect(),
+    )
+}
+
+/// make 2d box outlines, given as pairs of min and max corners
+pub fn make_box2(
+    name: impl ToString,
+    arr: &[[impl HasToVec2F32; 2]],
+    color: &Color,
+    line_width: f32,
+) -> PixelRenderable {
+    named_box2(
+        name,
+        arr.iter()
+            .map(|tuple| Box2 {
+                min: tuple[0].to_vec2(),
+                max: tuple[1].to_vec2(),
+                color: *color,
+                line_width,
+                fill_color: None,
+            })
+            .collect(),
+    )
+}
+
+/// make 2d arrows, given as pairs of tail and tip
+pub fn make_arrow2(
+    name: impl ToString,
+    arr: &[[impl HasToVec2F32; 2]],
+    color: &Color,
+    line_width: f32,
+) -> PixelRenderable {
+    named_arrow2(
+        name,
+        arr.iter()
+            .map(|tuple| Arrow2 {
+                tail: tuple[0].to_vec2(),
+                tip: tuple[1].to_vec2(),
+                color: *color,
+                line_width,
+                head_size: 4.0 * line_width,
+            })
+            .collect(),
+    )
+}
+
 /// Can be converted to Vec2F32
 pub trait HasToVec2F32 {
     /// returns Vec2F32
@@ -121,6 +231,170 @@ pub struct Point2 {
     pub point_size: f32,
 }
 
+/// 2D ellipse
+///
+/// The outline is drawn with `line_width`, a line width of zero draws the fill only.
+#[derive(Clone, Debug)]
+pub struct Ellipse2 {
+    /// Center in pixels
+    pub center: SVec<f32, 2>,
+    /// Semi-axes in pixels, along the rotated x and y axes
+    pub radii: SVec<f32, 2>,
+    /// Rotation of the semi-axes in radians, from the image x axis towards the image y axis
+    pub angle: f32,
+    /// Outline color
+    pub color: Color,
+    /// Outline width in pixels
+    pub line_width: f32,
+    /// Fill color, if filled
+    pub fill_color: Option<Color>,
+}
+
+impl Ellipse2 {
+    /// Circle outline
+    pub fn circle(center: SVec<f32, 2>, radius: f32, color: &Color, line_width: f32) -> Self {
+        Ellipse2 {
+            center,
+            radii: SVec::<f32, 2>::new(radius, radius),
+            angle: 0.0,
+            color: *color,
+            line_width,
+            fill_color: None,
+        }
+    }
+
+    /// Outline of the `sigma` confidence ellipse of a 2d gaussian, e.g. of a keypoint
+    pub fn from_covariance(
+        mean: SVec<f32, 2>,
+        covariance: &SMat<f32, 2, 2>,
+        sigma: f32,
+        color: &Color,
+        line_width: f32,
+    ) -> Self {
+        // closed form eigen decomposition of the symmetric 2x2 matrix
+        let a = covariance[(0, 0)];
+        let b = 0.5 * (covariance[(0, 1)] + covariance[(1, 0)]);
+        let c = covariance[(1, 1)];
+        let mean_eigenvalue = 0.5 * (a + c);
+        let d = (0.25 * (a - c) * (a - c) + b * b).sqrt();
+        Ellipse2 {
+            center: mean,
+            radii: SVec::<f32, 2>::new(
+                sigma * (mean_eigenvalue + d).max(0.0).sqrt(),
+                sigma * (mean_eigenvalue - d).max(0.0).sqrt(),
+            ),
+            angle: 0.5 * (2.0 * b).atan2(a - c),
+            color: *color,
+            line_width,
+            fill_color: None,
+        }
+    }
+}
+
+/// 2D axis-aligned box, e.g. a bounding box
+///
+/// The outline is drawn with `line_width`, a line width of zero draws the fill only.
+#[derive(Clone, Debug)]
+pub struct Box2 {
+    /// Min corner in pixels
+    pub min: SVec<f32, 2>,
+    /// Max corner in pixels
+    pub max: SVec<f32, 2>,
+    /// Outline color
+    pub color: Color,
+    /// Outline width in pixels
+    pub line_width: f32,
+    /// Fill color, if filled
+    pub fill_color: Option<Color>,
+}
+
+/// 2D polygon, e.g. of a segmentation mask
+///
+/// The polygon is closed implicitly and must not self-intersect, but may be non-convex. The
+/// outline is drawn with `line_width`, a line width of zero draws the fill only.
+#[derive(Clone, Debug)]
+pub struct Polygon2 {
+    /// Corners in pixels
+    pub points: Vec<SVec<f32, 2>>,
+    /// Outline color
+    pub color: Color,
+    /// Outline width in pixels
+    pub line_width: f32,
+    /// Fill color, if filled
+    pub fill_color: Option<Color>,
+}
+
+/// 2D arrow, e.g. of optical flow
+#[derive(Clone, Debug)]
+pub struct Arrow2 {
+    /// Tail in pixels
+    pub tail: SVec<f32, 2>,
+    /// Tip in pixels
+    pub tip: SVec<f32, 2>,
+    /// Color
+    pub color: Color,
+    /// Shaft width in pixels
+    pub line_width: f32,
+    /// Length and width of the head in pixels
+    pub head_size: f32,
+}
+
+/// Point of a text label which is placed at the anchor position
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextAnchor {
+    /// top left corner
+    #[default]
+    TopLeft,
+    /// center of the top edge
+    TopCenter,
+    /// top right corner
+    TopRight,
+    /// center of the left edge
+    CenterLeft,
+    /// center
+    Center,
+    /// center of the right edge
+    CenterRight,
+    /// bottom left corner
+    BottomLeft,
+    /// center of the bottom edge
+    BottomCenter,
+    /// bottom right corner
+    BottomRight,
+}
+
+impl TextAnchor {
+    /// Anchor position within the text box, as fraction of its width and height
+    pub fn align(&self) -> [f32; 2] {
+        match self {
+            TextAnchor::TopLeft => [0.0, 0.0],
+            TextAnchor::TopCenter => [0.5, 0.0],
+            TextAnchor::TopRight => [1.0, 0.0],
+            TextAnchor::CenterLeft => [0.0, 0.5],
+            TextAnchor::Center => [0.5, 0.5],
+            TextAnchor::CenterRight => [1.0, 0.5],
+            TextAnchor::BottomLeft => [0.0, 1.0],
+            TextAnchor::BottomCenter => [0.5, 1.0],
+            TextAnchor::BottomRight => [1.0, 1.0],
+        }
+    }
+}
+
+/// 2D text label
+#[derive(Clone, Debug)]
+pub struct Text2 {
+    /// Text, may contain line breaks
+    pub text: String,
+    /// Anchor position in pixels
+    pub p: SVec<f32, 2>,
+    /// Which point of the text box is placed at the anchor position
+    pub anchor: TextAnchor,
+    /// Color
+    pub color: Color,
+    /// Font size in pixels
+    pub font_size: f32,
+}
+
 /// 2D line segments
 #[derive(Clone, Debug)]
 pub struct LineSegments2 {
@@ -138,3 +412,48 @@ pub struct PointCloud2 {
     /// List of points
     pub points: Vec<Point2>,
 }
+
+/// 2D text labels
+#[derive(Clone, Debug)]
+pub struct Texts2 {
+    /// Name of the entity
+    pub name: String,
+    /// List of text labels
+    pub texts: Vec<Text2>,
+}
+
+/// 2D ellipses
+#[derive(Clone, Debug)]
+pub struct Ellipses2 {
+    /// Name of the entity
+    pub name: String,
+    /// List of ellipses
+    pub ellipses: Vec<Ellipse2>,
+}
+
+/// 2D boxes
+#[derive(Clone, Debug)]
+pub struct Boxes2 {
+    /// Name of the entity
+    pub name: String,
+    /// List of boxes
+    pub boxes: Vec<Box2>,
+}
+
+/// 2D polygons
+#[derive(Clone, Debug)]
+pub struct Polygons2 {
+    /// Name of the entity
+    pub name: String,
+    /// List of polygons
+    pub polygons: Vec<Polygon2>,
+}
+
+/// 2D arrows
+#[derive(Clone, Debug)]
+pub struct Arrows2 {
+    /// Name of the entity
+    pub name: String,
+    /// List of arrows
+    pub arrows: Vec<Arrow2>,
+}
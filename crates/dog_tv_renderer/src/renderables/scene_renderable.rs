@@ -1,12 +1,26 @@
 use crate::preludes::*;
 use crate::renderables::color::Color;
+use crate::renderables::color::Colormap;
+use crate::renderables::frame::ImageFrame;
 use crate::renderables::pixel_renderable::HasToVec2F32;
+use crate::renderables::pixel_renderable::TextAnchor;
+use crate::renderables::scene_renderable::frustum::CameraFrustum3;
+use crate::renderables::scene_renderable::uncertainty::Gaussian3;
+use crate::renderables::scene_renderable::uncertainty::Gaussians3;
+use crate::renderables::scene_renderable::uncertainty::PoseCovariance3;
+use crate::renderables::scene_renderable::uncertainty::PoseCovariances3;
+use crate::renderables::scene_renderable::uncertainty::UncertaintyStyle;
 use sophus::core::linalg::SVec;
+use sophus::image::arc_image::ArcImage4U8;
 use sophus::lie::Isometry3;
 use sophus::lie::Isometry3F64;
 
 /// axes
 pub mod axes;
+/// camera frustums
+pub mod frustum;
+/// uncertainty ellipsoids
+pub mod uncertainty;
 
 /// scene renderable
 #[derive(Clone, Debug)]
@@ -17,6 +31,24 @@ pub enum SceneRenderable {
     Point(PointCloud3),
     /// 3D mesh
     Mesh3(TriangleMesh3),
+    /// 3D textured mesh
+    TexturedMesh3(TexturedTriangleMesh3),
+    /// 3D-anchored text labels
+    Text(Texts3),
+    /// 3D gaussian uncertainty
+    Gaussian(Gaussians3),
+    /// pose uncertainty
+    PoseCovariance(PoseCovariances3),
+    /// template geometry drawn once per instance
+    Instanced(Instances3),
+    /// points appended to a streamed point cloud
+    PointStream(PointStream3),
+    /// 3D points colored by a scalar field
+    ScalarPoint(ScalarPointCloud3),
+    /// camera frustum
+    CameraFrustum(CameraFrustum3),
+    /// vertices appended to a polyline
+    Polyline(Polyline3),
 }
 
 impl SceneRenderable {
@@ -26,6 +58,15 @@ impl SceneRenderable {
             SceneRenderable::Line(lines) => lines.world_from_entity,
             SceneRenderable::Point(points) => points.world_from_entity,
             SceneRenderable::Mesh3(mesh) => mesh.world_from_entity,
+            SceneRenderable::TexturedMesh3(mesh) => mesh.world_from_entity,
+            SceneRenderable::Text(texts) => texts.world_from_entity,
+            SceneRenderable::Gaussian(gaussians) => gaussians.world_from_entity,
+            SceneRenderable::PoseCovariance(poses) => poses.world_from_entity,
+            SceneRenderable::Instanced(instances) => instances.world_from_entity,
+            SceneRenderable::PointStream(points) => points.world_from_entity,
+            SceneRenderable::ScalarPoint(points) => points.world_from_entity,
+            SceneRenderable::CameraFrustum(frustum) => frustum.world_from_entity,
+            SceneRenderable::Polyline(polyline) => polyline.world_from_entity,
         }
     }
 }
@@ -90,6 +131,236 @@ pub fn named_mesh3(name: impl ToString, mesh: TriangleMesh3) -> SceneRenderable
     named_mesh3_at(name, mesh, Isometry3::identity())
 }
 
+/// creates a named textured mesh at a given pose
+pub fn named_textured_mesh3_at(
+    name: impl ToString,
+    mesh: TexturedTriangleMesh3,
+    world_from_entity: Isometry3F64,
+) -> SceneRenderable {
+    let mesh = TexturedTriangleMesh3 {
+        name: name.to_string(),
+        triangles: mesh.triangles,
+        texture: mesh.texture,
+        world_from_entity,
+    };
+
+    SceneRenderable::TexturedMesh3(mesh)
+}
+
+/// creates a named textured mesh
+pub fn named_textured_mesh3(name: impl ToString, mesh: TexturedTriangleMesh3) -> SceneRenderable {
+    named_textured_mesh3_at(name, mesh, Isometry3::identity())
+}
+
+/// creates named text labels at a given pose
+pub fn named_text3_at(
+    name: impl ToString,
+    texts: Vec<Text3>,
+    world_from_entity: Isometry3F64,
+) -> SceneRenderable {
+    SceneRenderable::Text(Texts3 {
+        name: name.to_string(),
+        texts,
+        world_from_entity,
+    })
+}
+
+/// creates named text labels
+pub fn named_text3(name: impl ToString, texts: Vec<Text3>) -> SceneRenderable {
+    named_text3_at(name, texts, Isometry3::identity())
+}
+
+/// creates named 3d gaussians at a given pose, scaled by `sigma` standard deviations
+pub fn named_gaussian3_at(
+    name: impl ToString,
+    gaussians: Vec<Gaussian3>,
+    sigma: f32,
+    style: UncertaintyStyle,
+    world_from_entity: Isometry3F64,
+) -> SceneRenderable {
+    SceneRenderable::Gaussian(Gaussians3 {
+        name: name.to_string(),
+        gaussians,
+        sigma,
+        style,
+        line_width: 2.0,
+        world_from_entity,
+    })
+}
+
+/// creates named 3d gaussians, scaled by `sigma` standard deviations
+pub fn named_gaussian3(
+    name: impl ToString,
+    gaussians: Vec<Gaussian3>,
+    sigma: f32,
+    style: UncertaintyStyle,
+) -> SceneRenderable {
+    named_gaussian3_at(name, gaussians, sigma, style, Isometry3::identity())
+}
+
+/// creates named poses with covariance at a given pose, scaled by `sigma` standard deviations
+///
+/// The translational ellipsoids are drawn semi-transparent.
+pub fn named_pose_covariance3_at(
+    name: impl ToString,
+    poses: Vec<PoseCovariance3>,
+    sigma: f32,
+    axis_length: f32,
+    world_from_entity: Isometry3F64,
+) -> SceneRenderable {
+    SceneRenderable::PoseCovariance(PoseCovariances3 {
+        name: name.to_string(),
+        poses,
+        sigma,
+        axis_length,
+        line_width: 2.0,
+        color: Color {
+            r: 1.0,
+            g: 1.0,
+            b: 0.0,
+            a: 0.5,
+        },
+        world_from_entity,
+    })
+}
+
+/// creates named poses with covariance, scaled by `sigma` standard deviations
+pub fn named_pose_covariance3(
+    name: impl ToString,
+    poses: Vec<PoseCovariance3>,
+    sigma: f32,
+    axis_length: f32,
+) -> SceneRenderable {
+    named_pose_covariance3_at(name, poses, sigma, axis_length, Isometry3::identity())
+}
+
+/// creates named instances of a template at a given pose
+pub fn named_instances3_at(
+    name: impl ToString,
+    template: InstanceTemplate3,
+    instances: Vec<Instance3>,
+    world_from_entity: Isometry3F64,
+) -> SceneRenderable {
+    SceneRenderable::Instanced(Instances3 {
+        name: name.to_string(),
+        template,
+        instances,
+        world_from_entity,
+    })
+}
+
+/// creates named instances of a template
+pub fn named_instances3(
+    name: impl ToString,
+    template: InstanceTemplate3,
+    instances: Vec<Instance3>,
+) -> SceneRenderable {
+    named_instances3_at(name, template, instances, Isometry3::identity())
+}
+
+/// appends vertices to the named polyline at a given pose, creating it if needed
+pub fn named_polyline3_at(
+    name: impl ToString,
+    vertices: Vec<PolylineVertex3>,
+    line_width: f32,
+    time_window: Option<f64>,
+    world_from_entity: Isometry3F64,
+) -> SceneRenderable {
+    SceneRenderable::Polyline(Polyline3 {
+        name: name.to_string(),
+        vertices,
+        line_width,
+        time_window,
+        world_from_entity,
+    })
+}
+
+/// appends vertices to the named polyline, creating it if needed
+pub fn named_polyline3(
+    name: impl ToString,
+    vertices: Vec<PolylineVertex3>,
+    line_width: f32,
+    time_window: Option<f64>,
+) -> SceneRenderable {
+    named_polyline3_at(
+        name,
+        vertices,
+        line_width,
+        time_window,
+        Isometry3::identity(),
+    )
+}
+
+/// creates a named camera frustum of the given depth, with the image of the frame if any
+pub fn named_camera_frustum3(
+    name: impl ToString,
+    frame: &ImageFrame,
+    depth: f64,
+    world_from_camera: Isometry3F64,
+) -> SceneRenderable {
+    SceneRenderable::CameraFrustum(CameraFrustum3 {
+        name: name.to_string(),
+        camera_properties: frame.camera_properties().clone(),
+        depth,
+        image: frame.maybe_image().cloned(),
+        color: Color::white(1.0),
+        line_width: 2.0,
+        world_from_entity: world_from_camera,
+    })
+}
+
+/// creates a named point cloud with scalar fields at a given pose, colored by the first field
+pub fn named_scalar_point3_at(
+    name: impl ToString,
+    positions: Vec<SVec<f32, 3>>,
+    fields: Vec<ScalarField3>,
+    point_size: f32,
+    world_from_entity: Isometry3F64,
+) -> SceneRenderable {
+    SceneRenderable::ScalarPoint(ScalarPointCloud3 {
+        name: name.to_string(),
+        positions,
+        fields,
+        point_size,
+        coloring: PointColoring::default(),
+        world_from_entity,
+    })
+}
+
+/// creates a named point cloud with scalar fields, colored by the first field
+pub fn named_scalar_point3(
+    name: impl ToString,
+    positions: Vec<SVec<f32, 3>>,
+    fields: Vec<ScalarField3>,
+    point_size: f32,
+) -> SceneRenderable {
+    named_scalar_point3_at(name, positions, fields, point_size, Isometry3::identity())
+}
+
+/// appends points to the named streamed point cloud at a given pose, creating it if needed
+pub fn named_point_stream3_at(
+    name: impl ToString,
+    points: Vec<Point3>,
+    budget: PointBudget,
+    world_from_entity: Isometry3F64,
+) -> SceneRenderable {
+    SceneRenderable::PointStream(PointStream3 {
+        name: name.to_string(),
+        points,
+        budget,
+        world_from_entity,
+    })
+}
+
+/// appends points to the named streamed point cloud, creating it if needed
+pub fn named_point_stream3(
+    name: impl ToString,
+    points: Vec<Point3>,
+    budget: PointBudget,
+) -> SceneRenderable {
+    named_point_stream3_at(name, points, budget, Isometry3::identity())
+}
+
 /// make 3d points at a given pose
 pub fn make_point3_at(
     name: impl ToString,
@@ -180,6 +451,7 @@ pub fn make_mesh3_at(
             color0: *color,
             color1: *color,
             color2: *color,
+            normals: None,
         });
     }
 
@@ -192,14 +464,19 @@ pub fn make_mesh3(name: impl ToString, arr: &[([impl HasToVec3F32; 3], Color)])
 }
 
 /// make 3d textured mesh at a given pose
+///
+/// Texture coordinates are normalized, i.e. (0, 0) is the top-left and (1, 1) the bottom-right
+/// corner of the texture image.
 pub fn make_textured_mesh3_at(
     name: impl ToString,
     arr: &[[(impl HasToVec3F32, impl HasToVec2F32); 3]],
+    texture: &ArcImage4U8,
     world_from_entity: Isometry3F64,
-) -> TexturedTriangleMesh3 {
+) -> SceneRenderable {
     let mut mesh = TexturedTriangleMesh3 {
         name: name.to_string(),
         triangles: vec![],
+        texture: texture.clone(),
         world_from_entity,
     };
 
@@ -214,15 +491,16 @@ pub fn make_textured_mesh3_at(
         });
     }
 
-    mesh
+    SceneRenderable::TexturedMesh3(mesh)
 }
 
 /// make 3d textured mesh
 pub fn make_textured_mesh3(
     name: impl ToString,
     arr: &[[(impl HasToVec3F32, impl HasToVec2F32); 3]],
-) -> TexturedTriangleMesh3 {
-    make_textured_mesh3_at(name, arr, Isometry3::identity())
+    texture: &ArcImage4U8,
+) -> SceneRenderable {
+    make_textured_mesh3_at(name, arr, texture, Isometry3::identity())
 }
 
 /// 3D line
@@ -249,6 +527,24 @@ pub struct Point3 {
     pub point_size: f32,
 }
 
+/// 3D-anchored text label
+///
+/// The label is a billboard: it always faces the camera and keeps its size in pixels, independent
+/// of the distance to the camera. It is occluded by geometry in front of the anchor point.
+#[derive(Clone, Debug)]
+pub struct Text3 {
+    /// Text, may contain line breaks
+    pub text: String,
+    /// Anchor point
+    pub p: SVec<f32, 3>,
+    /// Which point of the text box is placed at the projected anchor point
+    pub anchor: TextAnchor,
+    /// Color
+    pub color: Color,
+    /// Font size in pixels
+    pub font_size: f32,
+}
+
 /// 3D triangle
 #[derive(Clone, Debug)]
 pub struct Triangle3 {
@@ -264,6 +560,8 @@ pub struct Triangle3 {
     pub color1: Color,
     /// Triangle color vertex 2
     pub color2: Color,
+    /// Optional normals of vertex 0, 1 and 2, the face normal is used if not present
+    pub normals: Option<[SVec<f32, 3>; 3]>,
 }
 
 impl Triangle3 {
@@ -276,6 +574,24 @@ impl Triangle3 {
             color0: color,
             color1: color,
             color2: color,
+            normals: None,
+        }
+    }
+
+    /// Set per-vertex normals, e.g. for smooth shading
+    pub fn with_normals(mut self, normals: [SVec<f32, 3>; 3]) -> Self {
+        self.normals = Some(normals);
+        self
+    }
+
+    /// Unit face normal, following the right-hand rule for the vertex order p0, p1, p2
+    pub fn face_normal(&self) -> SVec<f32, 3> {
+        let n = (self.p1 - self.p0).cross(&(self.p2 - self.p0));
+        let norm = n.norm();
+        if norm > 0.0 {
+            n / norm
+        } else {
+            SVec::<f32, 3>::new(0.0, 0.0, 1.0)
         }
     }
 }
@@ -297,6 +613,42 @@ pub struct TexturedTriangle3 {
     pub tex2: SVec<f32, 2>,
 }
 
+/// Template geometry of instanced renderables, in the instance frame
+#[derive(Clone, Debug)]
+pub enum InstanceTemplate3 {
+    /// triangle mesh
+    Mesh(Vec<Triangle3>),
+    /// line segments, the line width is in pixels and hence not scaled
+    Lines(Vec<LineSegment3>),
+}
+
+/// Instance of a template
+#[derive(Clone, Debug)]
+pub struct Instance3 {
+    /// Pose of the instance
+    pub entity_from_instance: Isometry3F64,
+    /// Color, multiplied with the template colors
+    pub color: Color,
+    /// Uniform scale of the template
+    pub scale: f32,
+}
+
+impl Instance3 {
+    /// Instance with the template colors and scale
+    pub fn new(entity_from_instance: Isometry3F64) -> Self {
+        Instance3 {
+            entity_from_instance,
+            color: Color {
+                r: 1.0,
+                g: 1.0,
+                b: 1.0,
+                a: 1.0,
+            },
+            scale: 1.0,
+        }
+    }
+}
+
 /// Can be converted to Vec3F32
 pub trait HasToVec3F32 {
     /// returns Vec3F32
@@ -332,6 +684,114 @@ pub struct PointCloud3 {
     pub world_from_entity: Isometry3F64,
 }
 
+/// Polyline vertex
+#[derive(Clone, Debug)]
+pub struct PolylineVertex3 {
+    /// Position
+    pub p: SVec<f32, 3>,
+    /// Color, interpolated along the adjacent segments
+    pub color: Color,
+    /// Timestamp, e.g. in seconds, used by the time window
+    pub t: f64,
+}
+
+/// Vertices appended to a polyline, e.g. a trajectory
+///
+/// Unlike [LineSegments3], which replaces the named entity, the vertices are appended to the
+/// vertices already sent and connected to the last one. The line width, time window and pose
+/// replace the ones of previous appends. To start over, delete the entity first.
+#[derive(Clone, Debug)]
+pub struct Polyline3 {
+    /// Name of the entity
+    pub name: String,
+    /// Vertices to append, in order
+    pub vertices: Vec<PolylineVertex3>,
+    /// Line width in pixels
+    pub line_width: f32,
+    /// If set, vertices older than the window, relative to the newest vertex, are dropped
+    pub time_window: Option<f64>,
+    /// world-anchored pose of the entity
+    pub world_from_entity: Isometry3F64,
+}
+
+/// Named per-point scalar field, e.g. intensity, range or timestamp
+#[derive(Clone, Debug)]
+pub struct ScalarField3 {
+    /// Name of the field, shown in the viewer
+    pub name: String,
+    /// One value per point
+    pub values: Vec<f32>,
+}
+
+/// How a point cloud with scalar fields is colored
+///
+/// This is the initial coloring only, it can be changed in the viewer at runtime.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PointColoring {
+    /// Index of the field to color by
+    pub field: usize,
+    /// Colormap
+    pub colormap: Colormap,
+    /// Values mapped to the start and end of the colormap, the range of the field if `None`
+    pub range: Option<[f32; 2]>,
+}
+
+/// 3D points with scalar fields, colored by one of the fields on the GPU
+#[derive(Clone, Debug)]
+pub struct ScalarPointCloud3 {
+    /// Name of the entity
+    pub name: String,
+    /// Point positions
+    pub positions: Vec<SVec<f32, 3>>,
+    /// Scalar fields, each with one value per point
+    pub fields: Vec<ScalarField3>,
+    /// Point size in pixels
+    pub point_size: f32,
+    /// Initial coloring
+    pub coloring: PointColoring,
+    /// world-anchored pose of the entity
+    pub world_from_entity: Isometry3F64,
+}
+
+/// Memory budget of a streamed point cloud
+///
+/// Appended points falling into an already occupied voxel are dropped. Once more than
+/// `max_points` points are retained, the voxel size is doubled and the retained points are
+/// decimated again, i.e. the cloud switches to a coarser level of detail.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PointBudget {
+    /// Maximal number of retained points, each costing roughly 110 bytes of CPU and GPU memory
+    pub max_points: usize,
+    /// Edge length of the voxels at the finest level of detail, zero to keep all points until the
+    /// budget is reached
+    pub voxel_size: f32,
+}
+
+impl Default for PointBudget {
+    fn default() -> Self {
+        PointBudget {
+            max_points: usize::MAX,
+            voxel_size: 0.0,
+        }
+    }
+}
+
+/// Points appended to a streamed point cloud
+///
+/// Unlike [PointCloud3], which replaces the named entity, the points are added to the points
+/// already sent. The budget and pose replace the ones of previous appends.
+#[derive(Clone, Debug)]
+pub struct PointStream3 {
+    /// Name of the entity
+    pub name: String,
+    /// Points to append
+    pub points: Vec<Point3>,
+    /// Memory budget
+    pub budget: PointBudget,
+    /// world-anchored pose of the entity
+    pub world_from_entity: Isometry3F64,
+}
+
 /// 3D lines
 #[derive(Clone, Debug)]
 pub struct LineSegments3 {
@@ -361,6 +821,34 @@ pub struct TexturedTriangleMesh3 {
     pub name: String,
     /// List of textured triangles
     pub triangles: Vec<TexturedTriangle3>,
+    /// Texture image
+    pub texture: ArcImage4U8,
+    /// world-anchored pose of the entity
+    pub world_from_entity: Isometry3F64,
+}
+
+/// 3D-anchored text labels
+#[derive(Clone, Debug)]
+pub struct Texts3 {
+    /// Name of the entity
+    pub name: String,
+    /// List of text labels
+    pub texts: Vec<Text3>,
+    /// world-anchored pose of the entity
+    pub world_from_entity: Isometry3F64,
+}
+
+/// Template geometry drawn once per instance, with a single instanced draw call
+///
+/// Picking reports the index of the instance as primitive index.
+#[derive(Clone, Debug)]
+pub struct Instances3 {
+    /// Name of the entity
+    pub name: String,
+    /// Template geometry
+    pub template: InstanceTemplate3,
+    /// List of instances
+    pub instances: Vec<Instance3>,
     /// world-anchored pose of the entity
     pub world_from_entity: Isometry3F64,
 }
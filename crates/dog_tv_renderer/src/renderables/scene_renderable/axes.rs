@@ -1,6 +1,10 @@
 use crate::preludes::*;
 use crate::renderables::color::Color;
+use crate::renderables::scene_renderable::named_instances3;
+use crate::renderables::scene_renderable::Instance3;
+use crate::renderables::scene_renderable::InstanceTemplate3;
 use crate::renderables::scene_renderable::LineSegment3;
+use crate::renderables::scene_renderable::SceneRenderable;
 use sophus::core::linalg::VecF64;
 use sophus::lie::Isometry3F64;
 
@@ -91,6 +95,28 @@ impl Axes3Builder {
 
         lines
     }
+
+    /// Build the axes as a single instanced renderable
+    ///
+    /// Only one set of axes is uploaded, which is drawn once per pose. Prefer this over `build`
+    /// for many poses, e.g. of long trajectories.
+    pub fn build_instanced(&self, name: impl ToString) -> SceneRenderable {
+        let template = Axes3Builder {
+            world_from_local_axes: vec![Isometry3F64::identity()],
+            scale: 1.0,
+            line_width: self.line_width,
+        }
+        .build();
+        let instances = self
+            .world_from_local_axes
+            .iter()
+            .map(|world_from_local| Instance3 {
+                scale: self.scale as f32,
+                ..Instance3::new(*world_from_local)
+            })
+            .collect();
+        named_instances3(name, InstanceTemplate3::Lines(template), instances)
+    }
 }
 
 /// Make 3d axes, with default scale of 1.0 and line width of 5.0
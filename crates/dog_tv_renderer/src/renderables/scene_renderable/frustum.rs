@@ -0,0 +1,136 @@
+use crate::camera::properties::RenderCameraProperties;
+use crate::preludes::*;
+use crate::renderables::color::Color;
+#[cfg(feature = "render")]
+use crate::renderables::scene_renderable::LineSegment3;
+#[cfg(feature = "render")]
+use crate::renderables::scene_renderable::LineSegments3;
+#[cfg(feature = "render")]
+use crate::renderables::scene_renderable::TexturedTriangle3;
+#[cfg(feature = "render")]
+use crate::renderables::scene_renderable::TexturedTriangleMesh3;
+#[cfg(feature = "render")]
+use sophus::core::linalg::SVec;
+#[cfg(feature = "render")]
+use sophus::core::linalg::VecF64;
+use sophus::image::arc_image::ArcImage4U8;
+use sophus::lie::Isometry3F64;
+
+/// Camera frustum, optionally with the camera image on its image plane
+///
+/// The frustum edges are unprojected through the camera model and hence curved for distorted
+/// cameras.
+#[derive(Clone, Debug)]
+pub struct CameraFrustum3 {
+    /// Name of the entity
+    pub name: String,
+    /// Intrinsics of the camera, the clipping planes are ignored
+    pub camera_properties: RenderCameraProperties,
+    /// Depth of the image plane, i.e. the length of the frustum along the optical axis
+    pub depth: f64,
+    /// Image shown on the image plane, any image size is stretched to the one of the intrinsics
+    pub image: Option<ArcImage4U8>,
+    /// Color of the frustum edges
+    pub color: Color,
+    /// Line width of the frustum edges
+    pub line_width: f32,
+    /// world-anchored pose of the camera
+    pub world_from_entity: Isometry3F64,
+}
+
+/// Number of segments each image border is subdivided into
+#[cfg(feature = "render")]
+const BORDER_SEGMENTS: usize = 8;
+
+#[cfg(feature = "render")]
+impl CameraFrustum3 {
+    /// Point of the image plane at the given normalized image coordinates, i.e. `[0, 0]` at the
+    /// top-left and `[1, 1]` at the bottom-right corner of the image
+    fn image_plane_point(&self, st: [f64; 2]) -> SVec<f32, 3> {
+        let image_size = self.camera_properties.intrinsics.image_size();
+        // pixel centers are at integer coordinates, hence the image spans [-0.5, size - 0.5]
+        let uv = VecF64::<2>::new(
+            st[0] * image_size.width as f64 - 0.5,
+            st[1] * image_size.height as f64 - 0.5,
+        );
+        self.camera_properties
+            .intrinsics
+            .cam_unproj_with_z(&uv, self.depth)
+            .cast()
+    }
+
+    /// Tessellates the frustum into line segments and, if there is an image, a textured mesh,
+    /// both named as the entity
+    pub(crate) fn tessellate(&self) -> (LineSegments3, Option<TexturedTriangleMesh3>) {
+        let n = BORDER_SEGMENTS;
+        let t = |i: usize| i as f64 / n as f64;
+        let corners = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
+
+        let mut segments = vec![];
+        let mut segment = |p0, p1| {
+            segments.push(LineSegment3 {
+                p0,
+                p1,
+                color: self.color,
+                line_width: self.line_width,
+            })
+        };
+        for corner in corners {
+            segment(SVec::<f32, 3>::zeros(), self.image_plane_point(corner));
+        }
+        for side in 0..4 {
+            let [s0, t0] = corners[side];
+            let [s1, t1] = corners[(side + 1) % 4];
+            let border =
+                |i: usize| self.image_plane_point([s0 + (s1 - s0) * t(i), t0 + (t1 - t0) * t(i)]);
+            for i in 0..n {
+                segment(border(i), border(i + 1));
+            }
+        }
+        let lines = LineSegments3 {
+            name: self.name.clone(),
+            segments,
+            world_from_entity: self.world_from_entity,
+        };
+
+        let mesh = self.image.as_ref().map(|image| {
+            let vertex = |i: usize, j: usize| {
+                let st = [t(i), t(j)];
+                (
+                    self.image_plane_point(st),
+                    SVec::<f32, 2>::new(st[0] as f32, st[1] as f32),
+                )
+            };
+            let mut triangles = vec![];
+            for i in 0..n {
+                for j in 0..n {
+                    let quad = [
+                        vertex(i, j),
+                        vertex(i + 1, j),
+                        vertex(i + 1, j + 1),
+                        vertex(i, j + 1),
+                    ];
+                    // both windings, so that the image is visible from both sides with culling
+                    for [a, b, c] in [[0, 1, 2], [0, 2, 3], [0, 2, 1], [0, 3, 2]] {
+                        triangles.push(TexturedTriangle3 {
+                            p0: quad[a].0,
+                            p1: quad[b].0,
+                            p2: quad[c].0,
+                            tex0: quad[a].1,
+                            tex1: quad[b].1,
+                            tex2: quad[c].1,
+                        });
+                    }
+                }
+            }
+            TexturedTriangleMesh3 {
+                name: self.name.clone(),
+                triangles,
+                texture: image.clone(),
+                world_from_entity: self.world_from_entity,
+            }
+        });
+
+        (lines, mesh)
+    }
+}
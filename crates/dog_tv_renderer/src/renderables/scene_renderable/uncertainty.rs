@@ -0,0 +1,288 @@
+use crate::preludes::*;
+use crate::renderables::color::Color;
+#[cfg(feature = "render")]
+use crate::renderables::scene_renderable::LineSegment3;
+#[cfg(feature = "render")]
+use crate::renderables::scene_renderable::LineSegments3;
+#[cfg(feature = "render")]
+use crate::renderables::scene_renderable::Triangle3;
+#[cfg(feature = "render")]
+use crate::renderables::scene_renderable::TriangleMesh3;
+#[cfg(feature = "render")]
+use core::f32::consts::PI;
+#[cfg(feature = "render")]
+use core::f32::consts::TAU;
+use sophus::core::linalg::MatF64;
+use sophus::core::linalg::SMat;
+use sophus::core::linalg::SVec;
+#[cfg(feature = "render")]
+use sophus::core::linalg::VecF64;
+use sophus::lie::Isometry3F64;
+
+/// How 3D gaussians are drawn
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UncertaintyStyle {
+    /// sigma-scaled ellipsoid mesh
+    #[default]
+    Ellipsoid,
+    /// the three sigma-scaled ellipses spanned by pairs of principal axes
+    PrincipalEllipses,
+}
+
+/// 3D gaussian, e.g. the position uncertainty of a landmark
+#[derive(Clone, Debug)]
+pub struct Gaussian3 {
+    /// Mean
+    pub mean: SVec<f32, 3>,
+    /// Covariance, only the symmetric part is used
+    pub covariance: SMat<f32, 3, 3>,
+    /// Color, the ellipsoid is transparent if alpha is smaller than one
+    pub color: Color,
+}
+
+/// 3D gaussians
+#[derive(Clone, Debug)]
+pub struct Gaussians3 {
+    /// Name of the entity
+    pub name: String,
+    /// List of gaussians
+    pub gaussians: Vec<Gaussian3>,
+    /// Number of standard deviations the ellipsoids are scaled with
+    pub sigma: f32,
+    /// Ellipsoid mesh or principal ellipses
+    pub style: UncertaintyStyle,
+    /// Line width of the principal ellipses
+    pub line_width: f32,
+    /// world-anchored pose of the entity
+    pub world_from_entity: Isometry3F64,
+}
+
+/// Pose with covariance
+///
+/// The covariance is the one of the tangent vector `[translation, rotation]` of a perturbation
+/// in the pose frame, i.e. of `world_from_pose * exp(delta)`.
+#[derive(Clone, Debug)]
+pub struct PoseCovariance3 {
+    /// Pose
+    pub world_from_pose: Isometry3F64,
+    /// 6x6 covariance of the tangent vector
+    pub covariance: MatF64<6, 6>,
+}
+
+/// Poses with covariance
+///
+/// Each pose is drawn as its axes, the ellipsoid of the translational uncertainty at its origin
+/// and, at the tip of each axis, the ellipse the tip moves on due to the rotational uncertainty.
+#[derive(Clone, Debug)]
+pub struct PoseCovariances3 {
+    /// Name of the entity
+    pub name: String,
+    /// List of poses
+    pub poses: Vec<PoseCovariance3>,
+    /// Number of standard deviations the ellipsoids and ellipses are scaled with
+    pub sigma: f32,
+    /// Length of the drawn axes
+    pub axis_length: f32,
+    /// Line width of the axes and the rotational ellipses
+    pub line_width: f32,
+    /// Color of the translational ellipsoid
+    pub color: Color,
+    /// world-anchored pose of the entity
+    pub world_from_entity: Isometry3F64,
+}
+
+#[cfg(feature = "render")]
+const SPHERE_RINGS: usize = 12;
+#[cfg(feature = "render")]
+const SPHERE_SEGMENTS: usize = 24;
+#[cfg(feature = "render")]
+const ELLIPSE_SEGMENTS: usize = 48;
+
+/// Principal axes of a covariance, scaled by `sigma` standard deviations
+///
+/// Returns the columns of a right-handed rotation and the matching semi-axis lengths, sorted by
+/// decreasing length.
+#[cfg(feature = "render")]
+fn principal_axes(covariance: &SMat<f32, 3, 3>, sigma: f32) -> ([SVec<f32, 3>; 3], [f32; 3]) {
+    let symmetric = 0.5 * (covariance + covariance.transpose());
+    let eigen = symmetric.symmetric_eigen();
+    let mut order = [0, 1, 2];
+    order.sort_by(|&a, &b| eigen.eigenvalues[b].total_cmp(&eigen.eigenvalues[a]));
+
+    let mut axes = order.map(|i| SVec::<f32, 3>::from(eigen.eigenvectors.column(i)));
+    if axes[0].cross(&axes[1]).dot(&axes[2]) < 0.0 {
+        axes[2] = -axes[2];
+    }
+    let lengths = order.map(|i| sigma * eigen.eigenvalues[i].max(0.0).sqrt());
+    (axes, lengths)
+}
+
+#[cfg(feature = "render")]
+fn ellipsoid_triangles(
+    mean: SVec<f32, 3>,
+    covariance: &SMat<f32, 3, 3>,
+    sigma: f32,
+    color: Color,
+    triangles: &mut Vec<Triangle3>,
+) {
+    let (axes, lengths) = principal_axes(covariance, sigma);
+    // position and normal of the point of the unit sphere at the given ring and segment
+    let vertex = |ring: usize, segment: usize| {
+        let theta = PI * ring as f32 / SPHERE_RINGS as f32;
+        let phi = TAU * segment as f32 / SPHERE_SEGMENTS as f32;
+        let u = [
+            theta.sin() * phi.cos(),
+            theta.sin() * phi.sin(),
+            theta.cos(),
+        ];
+        let mut p = mean;
+        let mut n = SVec::<f32, 3>::zeros();
+        for i in 0..3 {
+            p += axes[i] * (lengths[i] * u[i]);
+            n += axes[i] * (u[i] / lengths[i].max(1e-9));
+        }
+        (p, n.normalize())
+    };
+
+    for ring in 0..SPHERE_RINGS {
+        for segment in 0..SPHERE_SEGMENTS {
+            let a = vertex(ring, segment);
+            let b = vertex(ring + 1, segment);
+            let c = vertex(ring + 1, segment + 1);
+            let d = vertex(ring, segment + 1);
+            // skip the degenerate triangles at the poles
+            if ring + 1 < SPHERE_RINGS {
+                triangles.push(Triangle3::new(a.0, b.0, c.0, color).with_normals([a.1, b.1, c.1]));
+            }
+            if ring > 0 {
+                triangles.push(Triangle3::new(a.0, c.0, d.0, color).with_normals([a.1, c.1, d.1]));
+            }
+        }
+    }
+}
+
+#[cfg(feature = "render")]
+fn ellipse_segments(
+    center: SVec<f32, 3>,
+    semi_axes: [SVec<f32, 3>; 2],
+    color: Color,
+    line_width: f32,
+    segments: &mut Vec<LineSegment3>,
+) {
+    let point = |i: usize| {
+        let t = TAU * i as f32 / ELLIPSE_SEGMENTS as f32;
+        center + semi_axes[0] * t.cos() + semi_axes[1] * t.sin()
+    };
+    for i in 0..ELLIPSE_SEGMENTS {
+        segments.push(LineSegment3 {
+            p0: point(i),
+            p1: point(i + 1),
+            color,
+            line_width,
+        });
+    }
+}
+
+#[cfg(feature = "render")]
+fn to_mesh_and_lines(
+    name: &str,
+    triangles: Vec<Triangle3>,
+    segments: Vec<LineSegment3>,
+    world_from_entity: Isometry3F64,
+) -> (Option<TriangleMesh3>, Option<LineSegments3>) {
+    let mesh = (!triangles.is_empty()).then(|| TriangleMesh3 {
+        name: name.to_string(),
+        triangles,
+        world_from_entity,
+    });
+    let lines = (!segments.is_empty()).then(|| LineSegments3 {
+        name: name.to_string(),
+        segments,
+        world_from_entity,
+    });
+    (mesh, lines)
+}
+
+#[cfg(feature = "render")]
+impl Gaussians3 {
+    /// Tessellates the gaussians into a mesh and line segments, both named as the entity
+    pub(crate) fn tessellate(&self) -> (Option<TriangleMesh3>, Option<LineSegments3>) {
+        let mut triangles = vec![];
+        let mut segments = vec![];
+        for gaussian in self.gaussians.iter() {
+            match self.style {
+                UncertaintyStyle::Ellipsoid => ellipsoid_triangles(
+                    gaussian.mean,
+                    &gaussian.covariance,
+                    self.sigma,
+                    gaussian.color,
+                    &mut triangles,
+                ),
+                UncertaintyStyle::PrincipalEllipses => {
+                    let (axes, lengths) = principal_axes(&gaussian.covariance, self.sigma);
+                    for (i, j) in [(0, 1), (1, 2), (2, 0)] {
+                        ellipse_segments(
+                            gaussian.mean,
+                            [axes[i] * lengths[i], axes[j] * lengths[j]],
+                            gaussian.color,
+                            self.line_width,
+                            &mut segments,
+                        );
+                    }
+                }
+            }
+        }
+        to_mesh_and_lines(&self.name, triangles, segments, self.world_from_entity)
+    }
+}
+
+#[cfg(feature = "render")]
+impl PoseCovariances3 {
+    /// Tessellates the poses into a mesh and line segments, both named as the entity
+    pub(crate) fn tessellate(&self) -> (Option<TriangleMesh3>, Option<LineSegments3>) {
+        let axis_colors = [Color::red(), Color::green(), Color::blue()];
+        let mut triangles = vec![];
+        let mut segments = vec![];
+        for pose in self.poses.iter() {
+            let rotation: SMat<f32, 3, 3> = pose.world_from_pose.rotation().matrix().cast();
+            let origin: SVec<f32, 3> = pose.world_from_pose.transform(&VecF64::<3>::zeros()).cast();
+            let translation_covariance: SMat<f32, 3, 3> =
+                pose.covariance.fixed_view::<3, 3>(0, 0).into_owned().cast();
+            let rotation_covariance: SMat<f32, 3, 3> =
+                pose.covariance.fixed_view::<3, 3>(3, 3).into_owned().cast();
+
+            ellipsoid_triangles(
+                origin,
+                &(rotation * translation_covariance * rotation.transpose()),
+                self.sigma,
+                self.color,
+                &mut triangles,
+            );
+
+            for (i, color) in axis_colors.iter().enumerate() {
+                let axis = SVec::<f32, 3>::ith(i, self.axis_length);
+                let tip = origin + rotation * axis;
+                segments.push(LineSegment3 {
+                    p0: origin,
+                    p1: tip,
+                    color: *color,
+                    line_width: self.line_width,
+                });
+
+                // a small rotation omega moves the tip by omega x axis = -[axis]_x omega
+                let skew = axis.cross_matrix();
+                let tip_covariance =
+                    rotation * skew * rotation_covariance * skew.transpose() * rotation.transpose();
+                let (axes, lengths) = principal_axes(&tip_covariance, self.sigma);
+                ellipse_segments(
+                    tip,
+                    [axes[0] * lengths[0], axes[1] * lengths[1]],
+                    *color,
+                    self.line_width,
+                    &mut segments,
+                );
+            }
+        }
+        to_mesh_and_lines(&self.name, triangles, segments, self.world_from_entity)
+    }
+}
@@ -0,0 +1,75 @@
+/// Plane of the ground grid, in the world frame
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GridPlane {
+    /// z = 0
+    #[default]
+    XY,
+    /// y = 0
+    XZ,
+    /// x = 0
+    YZ,
+}
+
+impl GridPlane {
+    /// All grid planes
+    pub const ALL: [GridPlane; 3] = [GridPlane::XY, GridPlane::XZ, GridPlane::YZ];
+
+    /// Name shown in the viewer
+    pub fn label(&self) -> &'static str {
+        match self {
+            GridPlane::XY => "xy",
+            GridPlane::XZ => "xz",
+            GridPlane::YZ => "yz",
+        }
+    }
+
+    /// Normal and the two in-plane axes
+    #[cfg(feature = "render")]
+    pub(crate) fn axes(&self) -> [[f32; 3]; 3] {
+        let x = [1.0, 0.0, 0.0];
+        let y = [0.0, 1.0, 0.0];
+        let z = [0.0, 0.0, 1.0];
+        match self {
+            GridPlane::XY => [z, x, y],
+            GridPlane::XZ => [y, x, z],
+            GridPlane::YZ => [x, y, z],
+        }
+    }
+}
+
+/// Infinite ground grid
+///
+/// Every tenth line is emphasized. The grid fades out with the distance to the camera and where
+/// the cells get too small to be resolved.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GroundGrid {
+    /// plane of the grid
+    pub plane: GridPlane,
+    /// distance between neighboring grid lines
+    pub spacing: f64,
+    /// distance to the camera at which the grid has faded out completely
+    pub fade_distance: f64,
+}
+
+impl Default for GroundGrid {
+    fn default() -> Self {
+        GroundGrid {
+            plane: GridPlane::default(),
+            spacing: 1.0,
+            fade_distance: 50.0,
+        }
+    }
+}
+
+/// Reference geometry drawn by the renderer itself, i.e. not sent by the application
+///
+/// Helpers are not pickable and do not write the depth buffer, except for the origin axes.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SceneHelpers {
+    /// whether to draw the ground grid
+    pub show_grid: bool,
+    /// ground grid
+    pub grid: GroundGrid,
+    /// whether to draw the unit axes at the world origin, x red, y green and z blue
+    pub show_origin_axes: bool,
+}
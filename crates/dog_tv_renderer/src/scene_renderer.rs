@@ -1,40 +1,121 @@
+/// gradient and environment image background renderer
+pub mod background;
 /// distortion renderer
 pub mod distortion;
+/// ground grid and origin axes renderer
+pub mod helpers;
+/// instanced renderer
+pub mod instanced;
 /// line renderer
 pub mod line;
 /// mesh renderer
 pub mod mesh;
 /// point renderer
 pub mod point;
+/// streamed point cloud renderer
+pub mod point_stream;
+/// appendable polylines, drawn by the line renderer
+pub mod polyline;
+/// scalar field point cloud renderer
+pub mod scalar_point;
+/// text renderer
+pub mod text;
 /// textured mesh renderer
 pub mod textured_mesh;
 
+use crate::background::Background;
+use crate::lighting::SceneLighting;
 use crate::pipeline_builder::PipelineBuilder;
 use crate::pipeline_builder::TargetTexture;
 use crate::preludes::*;
+use crate::scene_helpers::SceneHelpers;
+use crate::scene_renderer::background::BackgroundRenderer;
+use crate::scene_renderer::helpers::SceneHelperRenderer;
+use crate::scene_renderer::instanced::InstancedEntity;
+use crate::scene_renderer::instanced::InstancedRenderer;
+use crate::scene_renderer::line::Line3dEntity;
+use crate::scene_renderer::mesh::Mesh3dEntity;
 use crate::scene_renderer::mesh::MeshRenderer;
+use crate::scene_renderer::point::Point3dEntity;
 use crate::scene_renderer::point::ScenePointRenderer;
+use crate::scene_renderer::point_stream::PointStreamEntity;
+use crate::scene_renderer::point_stream::PointStreamRenderer;
+use crate::scene_renderer::polyline::Polyline3dEntity;
+use crate::scene_renderer::scalar_point::ScalarPointRenderer;
+use crate::scene_renderer::text::SceneTextRenderer;
+use crate::scene_renderer::textured_mesh::TexturedMeshEntity;
+use crate::scene_renderer::textured_mesh::TexturedMeshRenderer;
 use crate::textures::depth::DepthTextures;
+use crate::textures::pick::PickTextures;
 use crate::textures::rgba::RgbdTexture;
+use crate::types::ScenePickKind;
 use crate::uniform_buffers::VertexShaderUniformBuffers;
 use crate::RenderContext;
+use sophus::core::linalg::VecF64;
 use sophus::lie::Isometry3F64;
 use wgpu::DepthStencilState;
 
+/// Mean of the vertex positions, used to sort transparent entities by depth
+pub(crate) fn centroid(positions: impl Iterator<Item = [f32; 3]>) -> VecF64<3> {
+    let mut sum = VecF64::<3>::zeros();
+    let mut num = 0;
+    for p in positions {
+        sum += VecF64::<3>::new(p[0] as f64, p[1] as f64, p[2] as f64);
+        num += 1;
+    }
+    if num > 0 {
+        sum / num as f64
+    } else {
+        sum
+    }
+}
+
+enum TransparentEntity<'a> {
+    Mesh(&'a Mesh3dEntity),
+    TexturedMesh(&'a TexturedMeshEntity),
+    Point(&'a Point3dEntity),
+    Line(&'a Line3dEntity),
+    Polyline(&'a Polyline3dEntity),
+    Instanced(&'a InstancedEntity),
+    PointStream(&'a PointStreamEntity),
+}
+
 /// Scene renderer
+///
+/// Renderables with any color alpha below 1, or textures with any alpha below 255, are
+/// transparent. They are drawn after all opaque renderables with alpha blending and without depth
+/// writes, sorted back-to-front by the depth of their centroid. Triangles within a single
+/// transparent renderable are not sorted. Text labels are drawn last.
+///
+/// For picking, all renderables are drawn once more into an id texture, see
+/// `RenderBuilder::pick`.
 pub struct SceneRenderer {
     /// uniforms
     pub uniforms: Arc<VertexShaderUniformBuffers>,
     /// Mesh renderer
     pub mesh_renderer: MeshRenderer,
-    // /// Textured mesh renderer
-    // pub textured_mesh_renderer: textured_mesh::TexturedMeshRenderer,
+    /// Textured mesh renderer
+    pub textured_mesh_renderer: TexturedMeshRenderer,
     /// Point renderer
     pub point_renderer: ScenePointRenderer,
     /// Line renderer
     pub line_renderer: line::SceneLineRenderer,
+    /// Text renderer
+    pub text_renderer: SceneTextRenderer,
+    /// Instanced renderer
+    pub instanced_renderer: InstancedRenderer,
+    /// Streamed point cloud renderer
+    pub point_stream_renderer: PointStreamRenderer,
+    /// Renderer of point clouds colored by scalar fields
+    pub scalar_point_renderer: ScalarPointRenderer,
+    /// Renderer of the ground grid and origin axes
+    pub helper_renderer: SceneHelperRenderer,
+    /// Renderer of gradient and environment image backgrounds
+    pub background_renderer: BackgroundRenderer,
     /// World from scene
     pub world_from_scene: Isometry3F64,
+    /// Lighting of triangle meshes
+    pub lighting: SceneLighting,
 }
 
 impl SceneRenderer {
@@ -58,14 +139,56 @@ impl SceneRenderer {
             mesh_renderer: MeshRenderer::new(render_context, &scene_pipeline_builder),
             line_renderer: line::SceneLineRenderer::new(render_context, &scene_pipeline_builder),
             point_renderer: ScenePointRenderer::new(render_context, &scene_pipeline_builder),
-            // textured_mesh_renderer: TexturedMeshRenderer::new(
-            //     render_context,
-            //     &scene_pipeline_builder,
-            // ),
+            text_renderer: SceneTextRenderer::new(render_context, &scene_pipeline_builder),
+            instanced_renderer: InstancedRenderer::new(render_context, &scene_pipeline_builder),
+            point_stream_renderer: PointStreamRenderer::new(
+                render_context,
+                &scene_pipeline_builder,
+            ),
+            scalar_point_renderer: ScalarPointRenderer::new(
+                render_context,
+                &scene_pipeline_builder,
+            ),
+            helper_renderer: SceneHelperRenderer::new(render_context, &scene_pipeline_builder),
+            background_renderer: BackgroundRenderer::new(render_context, &scene_pipeline_builder),
+            textured_mesh_renderer: TexturedMeshRenderer::new(
+                render_context,
+                &scene_pipeline_builder,
+            ),
             world_from_scene: Isometry3F64::identity(),
+            lighting: SceneLighting::default(),
         }
     }
 
+    /// Remove the named renderables, names which are not present are ignored
+    pub fn remove(&mut self, names: &[String]) {
+        for name in names {
+            self.mesh_renderer.mesh_table.remove(name);
+            self.textured_mesh_renderer.mesh_table.remove(name);
+            self.point_renderer.point_table.remove(name);
+            self.line_renderer.line_table.remove(name);
+            self.line_renderer.polyline_table.remove(name);
+            self.text_renderer.text_table.remove(name);
+            self.instanced_renderer.instanced_table.remove(name);
+            self.point_stream_renderer.point_stream_table.remove(name);
+            self.scalar_point_renderer.scalar_point_table.remove(name);
+        }
+    }
+
+    /// Remove all renderables
+    pub fn clear(&mut self) {
+        self.mesh_renderer.mesh_table.clear();
+        self.textured_mesh_renderer.mesh_table.clear();
+        self.point_renderer.point_table.clear();
+        self.line_renderer.line_table.clear();
+        self.line_renderer.polyline_table.clear();
+        self.text_renderer.text_table.clear();
+        self.instanced_renderer.instanced_table.clear();
+        self.point_stream_renderer.point_stream_table.clear();
+        self.scalar_point_renderer.scalar_point_table.clear();
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn paint<'rp>(
         &'rp self,
         state: &RenderContext,
@@ -74,18 +197,34 @@ impl SceneRenderer {
         rgba: &'rp RgbdTexture,
         depth: &DepthTextures,
         backface_culling: bool,
+        helpers: &SceneHelpers,
+        background: &Background,
     ) {
+        self.uniforms.update_lighting(
+            state,
+            &self.lighting,
+            &self.world_from_scene.group_mul(scene_from_camera),
+        );
+
         let mut render_pass = command_encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: None,
             color_attachments: &[Some(wgpu::RenderPassColorAttachment {
                 view: &rgba.multisample_texture_view,
                 resolve_target: Some(&rgba.resolved_texture_view),
                 ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(wgpu::Color {
-                        r: 0.0,
-                        g: 0.0,
-                        b: 0.0,
-                        a: 0.0,
+                    load: wgpu::LoadOp::Clear(match background {
+                        Background::Color(color) => wgpu::Color {
+                            r: color.r as f64,
+                            g: color.g as f64,
+                            b: color.b as f64,
+                            a: color.a as f64,
+                        },
+                        _ => wgpu::Color {
+                            r: 0.0,
+                            g: 0.0,
+                            b: 0.0,
+                            a: 0.0,
+                        },
                     }),
                     store: wgpu::StoreOp::Store,
                 },
@@ -103,6 +242,12 @@ impl SceneRenderer {
         });
         render_pass.set_bind_group(0, &self.uniforms.render_bind_group, &[]);
 
+        self.background_renderer.paint(
+            state,
+            &self.world_from_scene.group_mul(scene_from_camera),
+            background,
+            &mut render_pass,
+        );
         self.mesh_renderer.paint(
             state,
             scene_from_camera,
@@ -111,6 +256,14 @@ impl SceneRenderer {
             &mut render_pass,
             backface_culling,
         );
+        self.textured_mesh_renderer.paint(
+            state,
+            scene_from_camera,
+            &self.world_from_scene,
+            &self.uniforms,
+            &mut render_pass,
+            backface_culling,
+        );
         self.point_renderer.paint(
             state,
             scene_from_camera,
@@ -118,6 +271,20 @@ impl SceneRenderer {
             &self.uniforms,
             &mut render_pass,
         );
+        self.point_stream_renderer.paint(
+            state,
+            scene_from_camera,
+            &self.world_from_scene,
+            &self.uniforms,
+            &mut render_pass,
+        );
+        self.scalar_point_renderer.paint(
+            state,
+            scene_from_camera,
+            &self.world_from_scene,
+            &self.uniforms,
+            &mut render_pass,
+        );
         self.line_renderer.paint(
             state,
             scene_from_camera,
@@ -125,11 +292,269 @@ impl SceneRenderer {
             &self.uniforms,
             &mut render_pass,
         );
-        // self.textured_mesh_renderer.paint(
-        //     state,
-        //     scene_from_camera,
-        //     &self.uniforms,
-        //     &mut render_pass,
-        // );
+        self.instanced_renderer.paint(
+            state,
+            scene_from_camera,
+            &self.world_from_scene,
+            &self.uniforms,
+            &mut render_pass,
+            backface_culling,
+        );
+        if helpers.show_origin_axes {
+            self.helper_renderer.paint_origin_axes(
+                state,
+                &self.world_from_scene.group_mul(scene_from_camera),
+                &self.uniforms,
+                &self.line_renderer,
+                &mut render_pass,
+            );
+        }
+        if helpers.show_grid {
+            self.helper_renderer.paint_grid(
+                state,
+                &self.world_from_scene.group_mul(scene_from_camera),
+                &helpers.grid,
+                &mut render_pass,
+            );
+        }
+
+        self.paint_transparent(
+            state,
+            &self.world_from_scene.group_mul(scene_from_camera),
+            &mut render_pass,
+            backface_culling,
+        );
+
+        self.text_renderer.paint(
+            state,
+            &self.world_from_scene.group_mul(scene_from_camera),
+            &self.uniforms,
+            &mut render_pass,
+        );
+    }
+
+    /// Draws all renderables, opaque and transparent, into the pick id texture
+    ///
+    /// Returns the pick entity table: entity id `i` refers to the table entry `i - 1`.
+    pub(crate) fn paint_pick(
+        &self,
+        state: &RenderContext,
+        scene_from_camera: &Isometry3F64,
+        command_encoder: &mut wgpu::CommandEncoder,
+        pick: &PickTextures,
+    ) -> Vec<(ScenePickKind, String)> {
+        let world_from_camera = self.world_from_scene.group_mul(scene_from_camera);
+
+        let mut render_pass = command_encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("pick pass"),
+            color_attachments: &[
+                Some(wgpu::RenderPassColorAttachment {
+                    view: &pick.id_texture_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                }),
+                Some(wgpu::RenderPassColorAttachment {
+                    view: &pick.normal_texture_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                }),
+            ],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &pick.depth_texture_view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            occlusion_query_set: None,
+            timestamp_writes: None,
+        });
+        render_pass.set_bind_group(0, &self.uniforms.render_bind_group, &[]);
+
+        let mut entities = vec![];
+        self.mesh_renderer.paint_pick(
+            state,
+            &world_from_camera,
+            &self.uniforms,
+            &mut render_pass,
+            &mut entities,
+        );
+        self.textured_mesh_renderer.paint_pick(
+            state,
+            &world_from_camera,
+            &self.uniforms,
+            &mut render_pass,
+            &mut entities,
+        );
+        self.point_renderer.paint_pick(
+            state,
+            &world_from_camera,
+            &self.uniforms,
+            &mut render_pass,
+            &mut entities,
+        );
+        self.point_stream_renderer.paint_pick(
+            state,
+            &world_from_camera,
+            &self.uniforms,
+            &mut render_pass,
+            &mut entities,
+        );
+        self.scalar_point_renderer.paint_pick(
+            state,
+            &world_from_camera,
+            &self.uniforms,
+            &mut render_pass,
+            &mut entities,
+        );
+        self.line_renderer.paint_pick(
+            state,
+            &world_from_camera,
+            &self.uniforms,
+            &mut render_pass,
+            &mut entities,
+        );
+        self.instanced_renderer.paint_pick(
+            state,
+            &world_from_camera,
+            &self.uniforms,
+            &mut render_pass,
+            &mut entities,
+        );
+        entities
+    }
+
+    fn paint_transparent<'rp>(
+        &'rp self,
+        state: &RenderContext,
+        world_from_camera: &Isometry3F64,
+        render_pass: &mut wgpu::RenderPass<'rp>,
+        backface_culling: bool,
+    ) {
+        let camera_from_world = world_from_camera.inverse();
+        let depth = |world_from_entity: &Isometry3F64, centroid: &VecF64<3>| {
+            camera_from_world
+                .group_mul(world_from_entity)
+                .transform(centroid)[2]
+        };
+
+        let mut entities: Vec<(f64, TransparentEntity)> = vec![];
+        for mesh in self.mesh_renderer.mesh_table.values() {
+            if mesh.transparent {
+                let z = depth(&mesh.world_from_entity, &mesh.centroid);
+                entities.push((z, TransparentEntity::Mesh(mesh)));
+            }
+        }
+        for mesh in self.textured_mesh_renderer.mesh_table.values() {
+            if mesh.transparent {
+                let z = depth(&mesh.world_from_entity, &mesh.centroid);
+                entities.push((z, TransparentEntity::TexturedMesh(mesh)));
+            }
+        }
+        for points in self.point_renderer.point_table.values() {
+            if points.transparent {
+                let z = depth(&points.world_from_entity, &points.centroid);
+                entities.push((z, TransparentEntity::Point(points)));
+            }
+        }
+        for points in self.point_stream_renderer.point_stream_table.values() {
+            if points.transparent {
+                let z = depth(&points.world_from_entity, &points.centroid);
+                entities.push((z, TransparentEntity::PointStream(points)));
+            }
+        }
+        for lines in self.line_renderer.line_table.values() {
+            if lines.transparent {
+                let z = depth(&lines.world_from_entity, &lines.centroid);
+                entities.push((z, TransparentEntity::Line(lines)));
+            }
+        }
+        for polyline in self.line_renderer.polyline_table.values() {
+            if polyline.transparent {
+                let z = depth(&polyline.world_from_entity, &polyline.centroid);
+                entities.push((z, TransparentEntity::Polyline(polyline)));
+            }
+        }
+        for instanced in self.instanced_renderer.instanced_table.values() {
+            if instanced.transparent {
+                let z = depth(&instanced.world_from_entity, &instanced.centroid);
+                entities.push((z, TransparentEntity::Instanced(instanced)));
+            }
+        }
+
+        // back-to-front
+        entities.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(core::cmp::Ordering::Equal));
+
+        for (_z, entity) in entities {
+            match entity {
+                TransparentEntity::Mesh(mesh) => self.mesh_renderer.paint_transparent(
+                    state,
+                    world_from_camera,
+                    &self.uniforms,
+                    render_pass,
+                    mesh,
+                    backface_culling,
+                ),
+                TransparentEntity::TexturedMesh(mesh) => {
+                    self.textured_mesh_renderer.paint_transparent(
+                        state,
+                        world_from_camera,
+                        &self.uniforms,
+                        render_pass,
+                        mesh,
+                        backface_culling,
+                    )
+                }
+                TransparentEntity::Point(points) => self.point_renderer.paint_transparent(
+                    state,
+                    world_from_camera,
+                    &self.uniforms,
+                    render_pass,
+                    points,
+                ),
+                TransparentEntity::PointStream(points) => {
+                    self.point_stream_renderer.paint_transparent(
+                        state,
+                        world_from_camera,
+                        &self.uniforms,
+                        render_pass,
+                        points,
+                    )
+                }
+                TransparentEntity::Line(lines) => self.line_renderer.paint_transparent(
+                    state,
+                    world_from_camera,
+                    &self.uniforms,
+                    render_pass,
+                    lines,
+                ),
+                TransparentEntity::Polyline(polyline) => {
+                    self.line_renderer.paint_transparent_polyline(
+                        state,
+                        world_from_camera,
+                        &self.uniforms,
+                        render_pass,
+                        polyline,
+                    )
+                }
+                TransparentEntity::Instanced(instanced) => {
+                    self.instanced_renderer.paint_transparent(
+                        state,
+                        world_from_camera,
+                        &self.uniforms,
+                        render_pass,
+                        instanced,
+                        backface_culling,
+                    )
+                }
+            }
+        }
     }
 }
@@ -0,0 +1,258 @@
+use crate::background::Background;
+use crate::pipeline_builder::PipelineBuilder;
+use crate::preludes::*;
+use crate::RenderContext;
+use sophus::core::IsTensorLike;
+use sophus::image::arc_image::ArcImage4U8;
+use sophus::image::image_view::IsImageView;
+use sophus::image::ImageSize;
+use sophus::lie::Isometry3F64;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct BackgroundUniform {
+    camera_from_world: [[f32; 4]; 4],
+    top: [f32; 4],
+    bottom: [f32; 4],
+    mode: u32,
+    _padding: [u32; 3],
+}
+
+/// Whether both images share the same pixel buffer
+fn is_same_image(a: &ArcImage4U8, b: &ArcImage4U8) -> bool {
+    a.image_size() == b.image_size()
+        && core::ptr::eq(
+            a.tensor.scalar_view().as_slice().unwrap().as_ptr(),
+            b.tensor.scalar_view().as_slice().unwrap().as_ptr(),
+        )
+}
+
+/// Renderer of gradient and environment image backgrounds
+///
+/// Solid colors are cleared to instead. The environment image is uploaded once and kept until
+/// a different image is requested.
+pub struct BackgroundRenderer {
+    pipeline: wgpu::RenderPipeline,
+    buffer: wgpu::Buffer,
+    bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    bind_group: wgpu::BindGroup,
+    environment: Option<ArcImage4U8>,
+}
+
+impl BackgroundRenderer {
+    /// Create a new background renderer
+    pub fn new(render_context: &RenderContext, scene_pipelines: &PipelineBuilder) -> Self {
+        let device = &render_context.wgpu_device;
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("scene background shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                format!(
+                    "{} {}",
+                    include_str!("./../shaders/utils.wgsl"),
+                    include_str!("./../shaders/scene_background.wgsl")
+                )
+                .into(),
+            ),
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("background bind group layout"),
+        });
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("background buffer"),
+            size: core::mem::size_of::<BackgroundUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            // the longitude wraps around, the latitude does not
+            address_mode_u: wgpu::AddressMode::Repeat,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        // placeholder until an environment image is set
+        let texture = Self::create_texture(render_context, ImageSize::new(1, 1));
+        let bind_group = Self::create_bind_group(
+            render_context,
+            &bind_group_layout,
+            &buffer,
+            &texture,
+            &sampler,
+        );
+
+        Self {
+            pipeline: scene_pipelines.create_fullscreen(
+                "background".to_string(),
+                &shader,
+                &[&bind_group_layout],
+            ),
+            buffer,
+            bind_group_layout,
+            sampler,
+            bind_group,
+            environment: None,
+        }
+    }
+
+    fn create_texture(render_context: &RenderContext, image_size: ImageSize) -> wgpu::Texture {
+        render_context
+            .wgpu_device
+            .create_texture(&wgpu::TextureDescriptor {
+                size: wgpu::Extent3d {
+                    width: image_size.width as u32,
+                    height: image_size.height as u32,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8Unorm,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                label: Some("environment texture"),
+                view_formats: &[],
+            })
+    }
+
+    fn create_bind_group(
+        render_context: &RenderContext,
+        layout: &wgpu::BindGroupLayout,
+        buffer: &wgpu::Buffer,
+        texture: &wgpu::Texture,
+        sampler: &wgpu::Sampler,
+    ) -> wgpu::BindGroup {
+        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        render_context
+            .wgpu_device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::TextureView(&texture_view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: wgpu::BindingResource::Sampler(sampler),
+                    },
+                ],
+                label: Some("background bind group"),
+            })
+    }
+
+    /// Uploads the environment image, unless it is the one uploaded last
+    pub(crate) fn update(&mut self, render_context: &RenderContext, background: &Background) {
+        let Background::Environment(image) = background else {
+            return;
+        };
+        if self
+            .environment
+            .as_ref()
+            .is_some_and(|environment| is_same_image(environment, image))
+        {
+            return;
+        }
+
+        let texture = Self::create_texture(render_context, image.image_size());
+        render_context.wgpu_queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            bytemuck::cast_slice(image.tensor.scalar_view().as_slice().unwrap()),
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * image.image_size().width as u32),
+                rows_per_image: Some(image.image_size().height as u32),
+            },
+            texture.size(),
+        );
+        self.bind_group = Self::create_bind_group(
+            render_context,
+            &self.bind_group_layout,
+            &self.buffer,
+            &texture,
+            &self.sampler,
+        );
+        self.environment = Some(image.clone());
+    }
+
+    /// Draws gradient and environment backgrounds, before anything else
+    pub(crate) fn paint<'rp>(
+        &'rp self,
+        render_context: &RenderContext,
+        world_from_camera: &Isometry3F64,
+        background: &Background,
+        render_pass: &mut wgpu::RenderPass<'rp>,
+    ) {
+        let rgba = |c: &crate::renderables::color::Color| [c.r, c.g, c.b, c.a];
+        let (top, bottom, mode) = match background {
+            Background::Transparent | Background::Color(_) => return,
+            Background::VerticalGradient { top, bottom } => (rgba(top), rgba(bottom), 0),
+            Background::Environment(_) => ([0.0; 4], [0.0; 4], 1),
+        };
+
+        let camera_from_world = world_from_camera.inverse().matrix();
+        let mut camera_from_world_uniform = [[0.0; 4]; 4];
+        for i in 0..4 {
+            for j in 0..4 {
+                camera_from_world_uniform[j][i] = camera_from_world[(i, j)] as f32;
+            }
+        }
+        render_context.wgpu_queue.write_buffer(
+            &self.buffer,
+            0,
+            bytemuck::cast_slice(&[BackgroundUniform {
+                camera_from_world: camera_from_world_uniform,
+                top,
+                bottom,
+                mode,
+                _padding: [0; 3],
+            }]),
+        );
+
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(1, &self.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
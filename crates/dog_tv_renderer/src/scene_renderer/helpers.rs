@@ -0,0 +1,152 @@
+use crate::pipeline_builder::PipelineBuilder;
+use crate::preludes::*;
+use crate::renderables::scene_renderable::axes::make_axis3;
+use crate::renderables::scene_renderable::LineSegments3;
+use crate::scene_helpers::GroundGrid;
+use crate::scene_renderer::line::Line3dEntity;
+use crate::scene_renderer::line::SceneLineRenderer;
+use crate::uniform_buffers::VertexShaderUniformBuffers;
+use crate::RenderContext;
+use sophus::lie::Isometry3F64;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct GroundGridUniform {
+    camera_from_world: [[f32; 4]; 4],
+    normal: [f32; 4],
+    axis_u: [f32; 4],
+    axis_v: [f32; 4],
+    spacing: f32,
+    fade_distance: f32,
+    _padding: [f32; 2],
+}
+
+/// Renderer of the scene helpers, see `SceneHelpers`
+pub struct SceneHelperRenderer {
+    grid_pipeline: wgpu::RenderPipeline,
+    grid_buffer: wgpu::Buffer,
+    grid_bind_group: wgpu::BindGroup,
+    origin_axes: Line3dEntity,
+}
+
+impl SceneHelperRenderer {
+    /// Create a new scene helper renderer
+    pub fn new(render_context: &RenderContext, scene_pipelines: &PipelineBuilder) -> Self {
+        let device = &render_context.wgpu_device;
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("scene grid shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                format!(
+                    "{} {}",
+                    include_str!("./../shaders/utils.wgsl"),
+                    include_str!("./../shaders/scene_grid.wgsl")
+                )
+                .into(),
+            ),
+        });
+
+        let grid_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+                label: Some("ground grid bind group layout"),
+            });
+        let grid_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("ground grid buffer"),
+            size: core::mem::size_of::<GroundGridUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let grid_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &grid_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: grid_buffer.as_entire_binding(),
+            }],
+            label: Some("ground grid bind group"),
+        });
+
+        Self {
+            grid_pipeline: scene_pipelines.create_fullscreen(
+                "ground grid".to_string(),
+                &shader,
+                &[&grid_bind_group_layout],
+            ),
+            grid_buffer,
+            grid_bind_group,
+            origin_axes: Line3dEntity::new(
+                render_context,
+                &LineSegments3 {
+                    name: "origin axes".to_string(),
+                    segments: make_axis3(Isometry3F64::identity()),
+                    world_from_entity: Isometry3F64::identity(),
+                },
+            ),
+        }
+    }
+
+    /// Draws the ground grid, after the opaque renderables since it is blended onto them
+    pub(crate) fn paint_grid<'rp>(
+        &'rp self,
+        render_context: &RenderContext,
+        world_from_camera: &Isometry3F64,
+        grid: &GroundGrid,
+        render_pass: &mut wgpu::RenderPass<'rp>,
+    ) {
+        let camera_from_world = world_from_camera.inverse().matrix();
+        let mut camera_from_world_uniform = [[0.0; 4]; 4];
+        for i in 0..4 {
+            for j in 0..4 {
+                camera_from_world_uniform[j][i] = camera_from_world[(i, j)] as f32;
+            }
+        }
+        let [normal, axis_u, axis_v] = grid.plane.axes().map(|a| [a[0], a[1], a[2], 0.0]);
+        render_context.wgpu_queue.write_buffer(
+            &self.grid_buffer,
+            0,
+            bytemuck::cast_slice(&[GroundGridUniform {
+                camera_from_world: camera_from_world_uniform,
+                normal,
+                axis_u,
+                axis_v,
+                spacing: grid.spacing as f32,
+                fade_distance: grid.fade_distance as f32,
+                _padding: [0.0; 2],
+            }]),
+        );
+
+        render_pass.set_pipeline(&self.grid_pipeline);
+        render_pass.set_bind_group(1, &self.grid_bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+
+    /// Draws the origin axes with the opaque line pipeline
+    pub(crate) fn paint_origin_axes<'rp>(
+        &'rp self,
+        render_context: &RenderContext,
+        world_from_camera: &Isometry3F64,
+        uniforms: &'rp VertexShaderUniformBuffers,
+        line_renderer: &'rp SceneLineRenderer,
+        render_pass: &mut wgpu::RenderPass<'rp>,
+    ) {
+        render_pass.set_pipeline(&line_renderer.pipeline);
+        uniforms
+            .camera_from_entity_pose_buffer
+            .update_given_camera_and_entity(
+                &render_context.wgpu_queue,
+                world_from_camera,
+                &self.origin_axes.world_from_entity,
+            );
+        render_pass.set_vertex_buffer(0, self.origin_axes.vertex_buffer.slice(..));
+        render_pass.draw(0..self.origin_axes.vertex_data.len() as u32, 0..1);
+    }
+}
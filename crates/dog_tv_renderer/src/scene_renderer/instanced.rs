@@ -0,0 +1,385 @@
+use crate::pipeline_builder::InstanceVertex3;
+use crate::pipeline_builder::IsVertex;
+use crate::pipeline_builder::LineVertex3;
+use crate::pipeline_builder::MeshVertex3;
+use crate::pipeline_builder::PipelineBuilder;
+use crate::pipeline_builder::PipelineVariant;
+use crate::preludes::*;
+use crate::renderables::scene_renderable::InstanceTemplate3;
+use crate::renderables::scene_renderable::Instances3;
+use crate::renderables::scene_renderable::LineSegments3;
+use crate::renderables::scene_renderable::TriangleMesh3;
+use crate::scene_renderer::centroid;
+use crate::scene_renderer::line::Line3dEntity;
+use crate::scene_renderer::mesh::Mesh3dEntity;
+use crate::types::ScenePickKind;
+use crate::uniform_buffers::VertexShaderUniformBuffers;
+use crate::RenderContext;
+use sophus::core::linalg::VecF64;
+use sophus::lie::traits::IsTranslationProductGroup;
+use sophus::lie::Isometry3F64;
+use wgpu::util::DeviceExt;
+
+/// Template geometry, uploaded once per entity
+pub(crate) enum InstanceTemplateEntity {
+    Mesh(Mesh3dEntity),
+    Lines(Line3dEntity),
+}
+
+pub(crate) struct InstancedEntity {
+    pub(crate) template: InstanceTemplateEntity,
+    pub(crate) instance_data: Vec<InstanceVertex3>,
+    pub(crate) instance_buffer: wgpu::Buffer,
+    /// entity id of the pick pass, zero in all other passes
+    pub(crate) pick_id_buffer: wgpu::Buffer,
+    pub(crate) pick_id_bind_group: wgpu::BindGroup,
+    pub(crate) world_from_entity: Isometry3F64,
+    pub(crate) transparent: bool,
+    pub(crate) centroid: VecF64<3>,
+}
+
+impl InstancedEntity {
+    /// Create a new instanced entity
+    pub fn new(
+        render_context: &RenderContext,
+        instances: Instances3,
+        pick_id_bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> Self {
+        let template = match instances.template {
+            InstanceTemplate3::Mesh(triangles) => InstanceTemplateEntity::Mesh(Mesh3dEntity::new(
+                render_context,
+                &TriangleMesh3 {
+                    name: instances.name.clone(),
+                    triangles,
+                    world_from_entity: Isometry3F64::identity(),
+                },
+            )),
+            InstanceTemplate3::Lines(segments) => InstanceTemplateEntity::Lines(Line3dEntity::new(
+                render_context,
+                &LineSegments3 {
+                    name: instances.name.clone(),
+                    segments,
+                    world_from_entity: Isometry3F64::identity(),
+                },
+            )),
+        };
+
+        let instance_data: Vec<InstanceVertex3> = instances
+            .instances
+            .iter()
+            .enumerate()
+            .map(|(index, instance)| {
+                let scaled_rotation =
+                    instance.entity_from_instance.rotation().matrix() * instance.scale as f64;
+                let column = |i: usize| {
+                    let c = scaled_rotation.column(i);
+                    [c[0] as f32, c[1] as f32, c[2] as f32]
+                };
+                let translation = instance.entity_from_instance.translation();
+                InstanceVertex3 {
+                    _scaled_rotation_x: column(0),
+                    _scaled_rotation_y: column(1),
+                    _scaled_rotation_z: column(2),
+                    _translation: [
+                        translation[0] as f32,
+                        translation[1] as f32,
+                        translation[2] as f32,
+                    ],
+                    _color: [
+                        instance.color.r,
+                        instance.color.g,
+                        instance.color.b,
+                        instance.color.a,
+                    ],
+                    _index: index as u32,
+                }
+            })
+            .collect();
+
+        let instance_buffer =
+            render_context
+                .wgpu_device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some(&format!("3D instance buffer: {}", instances.name)),
+                    contents: bytemuck::cast_slice(&instance_data),
+                    usage: wgpu::BufferUsages::VERTEX,
+                });
+
+        let pick_id_buffer =
+            render_context
+                .wgpu_device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some(&format!("3D instance pick id buffer: {}", instances.name)),
+                    contents: bytemuck::cast_slice(&[0u32; 4]),
+                    usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                });
+        let pick_id_bind_group =
+            render_context
+                .wgpu_device
+                .create_bind_group(&wgpu::BindGroupDescriptor {
+                    layout: pick_id_bind_group_layout,
+                    entries: &[wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: pick_id_buffer.as_entire_binding(),
+                    }],
+                    label: Some(&format!(
+                        "3D instance pick id bind group: {}",
+                        instances.name
+                    )),
+                });
+
+        let template_transparent = match &template {
+            InstanceTemplateEntity::Mesh(mesh) => mesh.transparent,
+            InstanceTemplateEntity::Lines(lines) => lines.transparent,
+        };
+
+        Self {
+            transparent: template_transparent
+                || instance_data
+                    .iter()
+                    .any(|instance| instance._color[3] < 1.0),
+            // the template extent is neglected
+            centroid: centroid(instance_data.iter().map(|instance| instance._translation)),
+            template,
+            instance_data,
+            instance_buffer,
+            pick_id_buffer,
+            pick_id_bind_group,
+            world_from_entity: instances.world_from_entity,
+        }
+    }
+
+    fn template_buffer_and_vertex_count(&self) -> (&wgpu::Buffer, u32) {
+        match &self.template {
+            InstanceTemplateEntity::Mesh(mesh) => {
+                (&mesh.vertex_buffer, mesh.vertex_data.len() as u32)
+            }
+            InstanceTemplateEntity::Lines(lines) => {
+                (&lines.vertex_buffer, lines.vertex_data.len() as u32)
+            }
+        }
+    }
+}
+
+struct InstancedPipelines {
+    opaque_with_culling: wgpu::RenderPipeline,
+    opaque_without_culling: wgpu::RenderPipeline,
+    transparent_with_culling: wgpu::RenderPipeline,
+    transparent_without_culling: wgpu::RenderPipeline,
+    pick: wgpu::RenderPipeline,
+}
+
+impl InstancedPipelines {
+    fn new<Vertex: IsVertex>(
+        scene_pipelines: &PipelineBuilder,
+        name: &str,
+        shader: &wgpu::ShaderModule,
+        culling: Option<wgpu::Face>,
+        pick_id_bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> Self {
+        let create = |label: &str, cull_mode, variant| {
+            scene_pipelines.create_instanced::<Vertex, InstanceVertex3>(
+                format!("{} {}", label, name),
+                shader,
+                cull_mode,
+                &[pick_id_bind_group_layout],
+                variant,
+            )
+        };
+        InstancedPipelines {
+            opaque_with_culling: create("instanced", culling, PipelineVariant::Opaque),
+            opaque_without_culling: create("instanced", None, PipelineVariant::Opaque),
+            transparent_with_culling: create(
+                "transparent instanced",
+                culling,
+                PipelineVariant::Transparent,
+            ),
+            transparent_without_culling: create(
+                "transparent instanced",
+                None,
+                PipelineVariant::Transparent,
+            ),
+            pick: create("pick instanced", None, PipelineVariant::Pick),
+        }
+    }
+}
+
+/// Scene renderer of instanced template geometry
+///
+/// Each entity is drawn with a single instanced draw call, the template vertices are in vertex
+/// buffer slot 0 and the per-instance transforms and colors in slot 1. The entity id of the pick
+/// pass is passed in a per-entity uniform.
+pub struct InstancedRenderer {
+    mesh_pipelines: InstancedPipelines,
+    line_pipelines: InstancedPipelines,
+    pub(crate) pick_id_bind_group_layout: wgpu::BindGroupLayout,
+    pub(crate) instanced_table: BTreeMap<String, InstancedEntity>,
+}
+
+impl InstancedRenderer {
+    /// Create a new instanced renderer
+    pub fn new(render_context: &RenderContext, scene_pipelines: &PipelineBuilder) -> Self {
+        let device = &render_context.wgpu_device;
+
+        let mesh_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("scene instanced mesh shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                format!(
+                    "{} {}",
+                    include_str!("./../shaders/utils.wgsl"),
+                    include_str!("./../shaders/scene_instanced_mesh.wgsl")
+                )
+                .into(),
+            ),
+        });
+        let line_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("scene instanced line shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                format!(
+                    "{} {}",
+                    include_str!("./../shaders/utils.wgsl"),
+                    include_str!("./../shaders/scene_instanced_line.wgsl")
+                )
+                .into(),
+            ),
+        });
+
+        let pick_id_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+                label: Some("instance pick id bind group layout"),
+            });
+
+        Self {
+            mesh_pipelines: InstancedPipelines::new::<MeshVertex3>(
+                scene_pipelines,
+                "mesh",
+                &mesh_shader,
+                Some(wgpu::Face::Back),
+                &pick_id_bind_group_layout,
+            ),
+            line_pipelines: InstancedPipelines::new::<LineVertex3>(
+                scene_pipelines,
+                "line",
+                &line_shader,
+                None,
+                &pick_id_bind_group_layout,
+            ),
+            pick_id_bind_group_layout,
+            instanced_table: BTreeMap::new(),
+        }
+    }
+
+    fn pipelines(&self, entity: &InstancedEntity) -> &InstancedPipelines {
+        match entity.template {
+            InstanceTemplateEntity::Mesh(_) => &self.mesh_pipelines,
+            InstanceTemplateEntity::Lines(_) => &self.line_pipelines,
+        }
+    }
+
+    fn draw<'rp>(render_pass: &mut wgpu::RenderPass<'rp>, entity: &'rp InstancedEntity) {
+        let (template_buffer, vertex_count) = entity.template_buffer_and_vertex_count();
+        let num_instances = entity.instance_data.len() as u32;
+        render_pass.set_bind_group(1, &entity.pick_id_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, template_buffer.slice(..));
+        render_pass.set_vertex_buffer(1, entity.instance_buffer.slice(..));
+        render_pass.draw(0..vertex_count, 0..num_instances);
+    }
+
+    pub(crate) fn paint<'rp>(
+        &'rp self,
+        render_context: &RenderContext,
+        scene_from_camera: &Isometry3F64,
+        world_from_scene: &Isometry3F64,
+        uniforms: &'rp VertexShaderUniformBuffers,
+        render_pass: &mut wgpu::RenderPass<'rp>,
+        backface_culling: bool,
+    ) {
+        for entity in self
+            .instanced_table
+            .values()
+            .filter(|entity| !entity.transparent)
+        {
+            let pipelines = self.pipelines(entity);
+            render_pass.set_pipeline(if backface_culling {
+                &pipelines.opaque_with_culling
+            } else {
+                &pipelines.opaque_without_culling
+            });
+            uniforms
+                .camera_from_entity_pose_buffer
+                .update_given_camera_and_entity(
+                    &render_context.wgpu_queue,
+                    &world_from_scene.group_mul(scene_from_camera),
+                    &entity.world_from_entity,
+                );
+            Self::draw(render_pass, entity);
+        }
+    }
+
+    pub(crate) fn paint_transparent<'rp>(
+        &'rp self,
+        render_context: &RenderContext,
+        world_from_camera: &Isometry3F64,
+        uniforms: &'rp VertexShaderUniformBuffers,
+        render_pass: &mut wgpu::RenderPass<'rp>,
+        entity: &'rp InstancedEntity,
+        backface_culling: bool,
+    ) {
+        let pipelines = self.pipelines(entity);
+        render_pass.set_pipeline(if backface_culling {
+            &pipelines.transparent_with_culling
+        } else {
+            &pipelines.transparent_without_culling
+        });
+        uniforms
+            .camera_from_entity_pose_buffer
+            .update_given_camera_and_entity(
+                &render_context.wgpu_queue,
+                world_from_camera,
+                &entity.world_from_entity,
+            );
+        Self::draw(render_pass, entity);
+    }
+
+    /// Draws all entities into the pick id texture, appending them to the pick entity table
+    ///
+    /// The entity id, i.e. the position in the table plus one, is written to the pick id uniform
+    /// of each entity, which only the pick pipelines read.
+    pub(crate) fn paint_pick<'rp>(
+        &'rp self,
+        render_context: &RenderContext,
+        world_from_camera: &Isometry3F64,
+        uniforms: &'rp VertexShaderUniformBuffers,
+        render_pass: &mut wgpu::RenderPass<'rp>,
+        entities: &mut Vec<(ScenePickKind, String)>,
+    ) {
+        for (name, entity) in self.instanced_table.iter() {
+            entities.push((ScenePickKind::Instanced, name.clone()));
+            let entity_id = entities.len() as u32;
+            render_pass.set_pipeline(&self.pipelines(entity).pick);
+            uniforms
+                .camera_from_entity_pose_buffer
+                .update_given_camera_and_entity(
+                    &render_context.wgpu_queue,
+                    world_from_camera,
+                    &entity.world_from_entity,
+                );
+            render_context.wgpu_queue.write_buffer(
+                &entity.pick_id_buffer,
+                0,
+                bytemuck::cast_slice(&[entity_id, 0, 0, 0]),
+            );
+            Self::draw(render_pass, entity);
+        }
+    }
+}
@@ -2,8 +2,13 @@ use crate::pipeline_builder::LineVertex3;
 use crate::pipeline_builder::PipelineBuilder;
 use crate::preludes::*;
 use crate::renderables::scene_renderable::LineSegments3;
+use crate::renderables::scene_renderable::Polyline3;
+use crate::scene_renderer::centroid;
+use crate::scene_renderer::polyline::Polyline3dEntity;
+use crate::types::ScenePickKind;
 use crate::uniform_buffers::VertexShaderUniformBuffers;
 use crate::RenderContext;
+use sophus::core::linalg::VecF64;
 use sophus::lie::Isometry3F64;
 use wgpu::util::DeviceExt;
 
@@ -11,6 +16,8 @@ pub(crate) struct Line3dEntity {
     pub(crate) vertex_data: Vec<LineVertex3>,
     pub(crate) vertex_buffer: wgpu::Buffer,
     pub(crate) world_from_entity: Isometry3F64,
+    pub(crate) transparent: bool,
+    pub(crate) centroid: VecF64<3>,
 }
 
 impl Line3dEntity {
@@ -20,25 +27,18 @@ impl Line3dEntity {
         for line in lines.segments.iter() {
             let p0 = line.p0;
             let p1 = line.p1;
+            let color = [line.color.r, line.color.g, line.color.b, line.color.a];
 
-            let v0 = LineVertex3 {
+            let v = LineVertex3 {
                 _p0: [p0[0], p0[1], p0[2]],
                 _p1: [p1[0], p1[1], p1[2]],
-                _color: [line.color.r, line.color.g, line.color.b, line.color.a],
+                _color0: color,
                 _line_width: line.line_width,
+                _color1: color,
             };
-            let v1 = LineVertex3 {
-                _p0: [p0[0], p0[1], p0[2]],
-                _p1: [p1[0], p1[1], p1[2]],
-                _color: [line.color.r, line.color.g, line.color.b, line.color.a],
-                _line_width: line.line_width,
-            };
-            vertex_data.push(v0);
-            vertex_data.push(v0);
-            vertex_data.push(v1);
-            vertex_data.push(v0);
-            vertex_data.push(v1);
-            vertex_data.push(v1);
+            for _i in 0..6 {
+                vertex_data.push(v);
+            }
         }
 
         let vertex_buffer =
@@ -51,6 +51,8 @@ impl Line3dEntity {
                 });
 
         Self {
+            transparent: vertex_data.iter().any(|v| v._color0[3] < 1.0),
+            centroid: centroid(vertex_data.iter().flat_map(|v| [v._p0, v._p1])),
             vertex_data,
             vertex_buffer,
             world_from_entity: lines.world_from_entity,
@@ -58,10 +60,13 @@ impl Line3dEntity {
     }
 }
 
-/// Scene line renderer
+/// Scene line renderer, of line segments as well as polylines
 pub struct SceneLineRenderer {
     pub(crate) pipeline: wgpu::RenderPipeline,
+    pub(crate) transparent_pipeline: wgpu::RenderPipeline,
+    pub(crate) pick_pipeline: wgpu::RenderPipeline,
     pub(crate) line_table: BTreeMap<String, Line3dEntity>,
+    pub(crate) polyline_table: BTreeMap<String, Polyline3dEntity>,
 }
 
 impl SceneLineRenderer {
@@ -84,7 +89,32 @@ impl SceneLineRenderer {
 
         Self {
             pipeline: scene_pipelines.create::<LineVertex3>("line".to_string(), &shader, None),
+            transparent_pipeline: scene_pipelines.create_transparent::<LineVertex3>(
+                "transparent line".to_string(),
+                &shader,
+                None,
+                &[],
+            ),
+            pick_pipeline: scene_pipelines.create_pick::<LineVertex3>(
+                "pick line".to_string(),
+                &shader,
+                &[],
+            ),
             line_table: BTreeMap::new(),
+            polyline_table: BTreeMap::new(),
+        }
+    }
+
+    /// Appends to the named polyline, creating it if not present
+    pub(crate) fn append_polyline(&mut self, render_context: &RenderContext, polyline: Polyline3) {
+        match self.polyline_table.get_mut(&polyline.name) {
+            Some(entity) => entity.append(render_context, polyline),
+            None => {
+                self.polyline_table.insert(
+                    polyline.name.clone(),
+                    Polyline3dEntity::new(render_context, polyline),
+                );
+            }
         }
     }
 
@@ -98,7 +128,7 @@ impl SceneLineRenderer {
     ) {
         render_pass.set_pipeline(&self.pipeline);
 
-        for line in self.line_table.values() {
+        for line in self.line_table.values().filter(|line| !line.transparent) {
             uniforms
                 .camera_from_entity_pose_buffer
                 .update_given_camera_and_entity(
@@ -109,5 +139,99 @@ impl SceneLineRenderer {
             render_pass.set_vertex_buffer(0, line.vertex_buffer.slice(..));
             render_pass.draw(0..line.vertex_data.len() as u32, 0..1);
         }
+        for polyline in self
+            .polyline_table
+            .values()
+            .filter(|polyline| !polyline.transparent)
+        {
+            uniforms
+                .camera_from_entity_pose_buffer
+                .update_given_camera_and_entity(
+                    &render_context.wgpu_queue,
+                    &world_from_scene.group_mul(scene_from_camera),
+                    &polyline.world_from_entity,
+                );
+            polyline.draw(render_pass, 0);
+        }
+    }
+
+    pub(crate) fn paint_transparent<'rp>(
+        &'rp self,
+        render_context: &RenderContext,
+        world_from_camera: &Isometry3F64,
+        uniforms: &'rp VertexShaderUniformBuffers,
+        render_pass: &mut wgpu::RenderPass<'rp>,
+        line: &'rp Line3dEntity,
+    ) {
+        render_pass.set_pipeline(&self.transparent_pipeline);
+        uniforms
+            .camera_from_entity_pose_buffer
+            .update_given_camera_and_entity(
+                &render_context.wgpu_queue,
+                world_from_camera,
+                &line.world_from_entity,
+            );
+        render_pass.set_vertex_buffer(0, line.vertex_buffer.slice(..));
+        render_pass.draw(0..line.vertex_data.len() as u32, 0..1);
+    }
+
+    pub(crate) fn paint_transparent_polyline<'rp>(
+        &'rp self,
+        render_context: &RenderContext,
+        world_from_camera: &Isometry3F64,
+        uniforms: &'rp VertexShaderUniformBuffers,
+        render_pass: &mut wgpu::RenderPass<'rp>,
+        polyline: &'rp Polyline3dEntity,
+    ) {
+        render_pass.set_pipeline(&self.transparent_pipeline);
+        uniforms
+            .camera_from_entity_pose_buffer
+            .update_given_camera_and_entity(
+                &render_context.wgpu_queue,
+                world_from_camera,
+                &polyline.world_from_entity,
+            );
+        polyline.draw(render_pass, 0);
+    }
+
+    /// Draws all entities into the pick id texture, appending them to the pick entity table
+    ///
+    /// The entity id passed as instance index is the position in the table plus one, 0 is
+    /// reserved for the background.
+    pub(crate) fn paint_pick<'rp>(
+        &'rp self,
+        render_context: &RenderContext,
+        world_from_camera: &Isometry3F64,
+        uniforms: &'rp VertexShaderUniformBuffers,
+        render_pass: &mut wgpu::RenderPass<'rp>,
+        entities: &mut Vec<(ScenePickKind, String)>,
+    ) {
+        render_pass.set_pipeline(&self.pick_pipeline);
+
+        for (name, line) in self.line_table.iter() {
+            entities.push((ScenePickKind::Line, name.clone()));
+            let entity_id = entities.len() as u32;
+            uniforms
+                .camera_from_entity_pose_buffer
+                .update_given_camera_and_entity(
+                    &render_context.wgpu_queue,
+                    world_from_camera,
+                    &line.world_from_entity,
+                );
+            render_pass.set_vertex_buffer(0, line.vertex_buffer.slice(..));
+            render_pass.draw(0..line.vertex_data.len() as u32, entity_id..entity_id + 1);
+        }
+        for (name, polyline) in self.polyline_table.iter() {
+            entities.push((ScenePickKind::Polyline, name.clone()));
+            let entity_id = entities.len() as u32;
+            uniforms
+                .camera_from_entity_pose_buffer
+                .update_given_camera_and_entity(
+                    &render_context.wgpu_queue,
+                    world_from_camera,
+                    &polyline.world_from_entity,
+                );
+            polyline.draw(render_pass, entity_id);
+        }
     }
 }
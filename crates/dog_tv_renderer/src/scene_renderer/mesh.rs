@@ -2,8 +2,11 @@ use crate::pipeline_builder::MeshVertex3;
 use crate::pipeline_builder::PipelineBuilder;
 use crate::preludes::*;
 use crate::renderables::scene_renderable::TriangleMesh3;
+use crate::scene_renderer::centroid;
+use crate::types::ScenePickKind;
 use crate::uniform_buffers::VertexShaderUniformBuffers;
 use crate::RenderContext;
+use sophus::core::linalg::VecF64;
 use sophus::lie::Isometry3F64;
 use wgpu::util::DeviceExt;
 
@@ -11,6 +14,8 @@ pub(crate) struct Mesh3dEntity {
     pub(crate) vertex_data: Vec<MeshVertex3>,
     pub(crate) vertex_buffer: wgpu::Buffer,
     pub(crate) world_from_entity: Isometry3F64,
+    pub(crate) transparent: bool,
+    pub(crate) centroid: VecF64<3>,
 }
 
 impl Mesh3dEntity {
@@ -20,18 +25,25 @@ impl Mesh3dEntity {
             .triangles
             .iter()
             .flat_map(|trig| {
+                let [n0, n1, n2] = trig.normals.unwrap_or_else(|| {
+                    let n = trig.face_normal();
+                    [n, n, n]
+                });
                 vec![
                     MeshVertex3 {
                         _pos: [trig.p0[0], trig.p0[1], trig.p0[2]],
                         _color: [trig.color0.r, trig.color0.g, trig.color0.b, trig.color0.a],
+                        _normal: [n0[0], n0[1], n0[2]],
                     },
                     MeshVertex3 {
                         _pos: [trig.p1[0], trig.p1[1], trig.p1[2]],
                         _color: [trig.color1.r, trig.color1.g, trig.color1.b, trig.color1.a],
+                        _normal: [n1[0], n1[1], n1[2]],
                     },
                     MeshVertex3 {
                         _pos: [trig.p2[0], trig.p2[1], trig.p2[2]],
                         _color: [trig.color2.r, trig.color2.g, trig.color2.b, trig.color2.a],
+                        _normal: [n2[0], n2[1], n2[2]],
                     },
                 ]
             })
@@ -47,6 +59,8 @@ impl Mesh3dEntity {
                 });
 
         Self {
+            transparent: vertex_data.iter().any(|v| v._color[3] < 1.0),
+            centroid: centroid(vertex_data.iter().map(|v| v._pos)),
             vertex_data,
             vertex_buffer,
             world_from_entity: mesh.world_from_entity,
@@ -58,6 +72,9 @@ impl Mesh3dEntity {
 pub struct MeshRenderer {
     pub(crate) pipeline_without_culling: wgpu::RenderPipeline,
     pub(crate) pipeline_with_culling: wgpu::RenderPipeline,
+    pub(crate) transparent_pipeline_without_culling: wgpu::RenderPipeline,
+    pub(crate) transparent_pipeline_with_culling: wgpu::RenderPipeline,
+    pub(crate) pick_pipeline: wgpu::RenderPipeline,
     pub(crate) mesh_table: BTreeMap<String, Mesh3dEntity>,
 }
 
@@ -89,6 +106,24 @@ impl MeshRenderer {
                 &shader,
                 Some(wgpu::Face::Back),
             ),
+            transparent_pipeline_with_culling: scene_pipelines.create_transparent::<MeshVertex3>(
+                "transparent mesh with culling".to_string(),
+                &shader,
+                Some(wgpu::Face::Back),
+                &[],
+            ),
+            transparent_pipeline_without_culling: scene_pipelines
+                .create_transparent::<MeshVertex3>(
+                    "transparent mesh".to_string(),
+                    &shader,
+                    None,
+                    &[],
+                ),
+            pick_pipeline: scene_pipelines.create_pick::<MeshVertex3>(
+                "pick mesh".to_string(),
+                &shader,
+                &[],
+            ),
             mesh_table: BTreeMap::new(),
         }
     }
@@ -109,7 +144,7 @@ impl MeshRenderer {
         };
         render_pass.set_pipeline(pipeline);
 
-        for mesh in self.mesh_table.values() {
+        for mesh in self.mesh_table.values().filter(|mesh| !mesh.transparent) {
             uniforms
                 .camera_from_entity_pose_buffer
                 .update_given_camera_and_entity(
@@ -121,4 +156,59 @@ impl MeshRenderer {
             render_pass.draw(0..mesh.vertex_data.len() as u32, 0..1);
         }
     }
+
+    pub(crate) fn paint_transparent<'rp>(
+        &'rp self,
+        render_context: &RenderContext,
+        world_from_camera: &Isometry3F64,
+        uniforms: &'rp VertexShaderUniformBuffers,
+        render_pass: &mut wgpu::RenderPass<'rp>,
+        mesh: &'rp Mesh3dEntity,
+        backface_culling: bool,
+    ) {
+        let pipeline = if backface_culling {
+            &self.transparent_pipeline_with_culling
+        } else {
+            &self.transparent_pipeline_without_culling
+        };
+        render_pass.set_pipeline(pipeline);
+        uniforms
+            .camera_from_entity_pose_buffer
+            .update_given_camera_and_entity(
+                &render_context.wgpu_queue,
+                world_from_camera,
+                &mesh.world_from_entity,
+            );
+        render_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+        render_pass.draw(0..mesh.vertex_data.len() as u32, 0..1);
+    }
+
+    /// Draws all entities into the pick id texture, appending them to the pick entity table
+    ///
+    /// The entity id passed as instance index is the position in the table plus one, 0 is
+    /// reserved for the background.
+    pub(crate) fn paint_pick<'rp>(
+        &'rp self,
+        render_context: &RenderContext,
+        world_from_camera: &Isometry3F64,
+        uniforms: &'rp VertexShaderUniformBuffers,
+        render_pass: &mut wgpu::RenderPass<'rp>,
+        entities: &mut Vec<(ScenePickKind, String)>,
+    ) {
+        render_pass.set_pipeline(&self.pick_pipeline);
+
+        for (name, mesh) in self.mesh_table.iter() {
+            entities.push((ScenePickKind::Mesh, name.clone()));
+            let entity_id = entities.len() as u32;
+            uniforms
+                .camera_from_entity_pose_buffer
+                .update_given_camera_and_entity(
+                    &render_context.wgpu_queue,
+                    world_from_camera,
+                    &mesh.world_from_entity,
+                );
+            render_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+            render_pass.draw(0..mesh.vertex_data.len() as u32, entity_id..entity_id + 1);
+        }
+    }
 }
@@ -2,14 +2,19 @@ use crate::pipeline_builder::PipelineBuilder;
 use crate::pipeline_builder::PointVertex3;
 use crate::preludes::*;
 use crate::renderables::scene_renderable::PointCloud3;
+use crate::scene_renderer::centroid;
+use crate::types::ScenePickKind;
 use crate::uniform_buffers::VertexShaderUniformBuffers;
 use crate::RenderContext;
+use sophus::core::linalg::VecF64;
 use sophus::lie::Isometry3F64;
 use wgpu::util::DeviceExt;
 pub(crate) struct Point3dEntity {
     pub(crate) vertex_data: Vec<PointVertex3>,
     pub(crate) vertex_buffer: wgpu::Buffer,
     pub(crate) world_from_entity: Isometry3F64,
+    pub(crate) transparent: bool,
+    pub(crate) centroid: VecF64<3>,
 }
 
 impl Point3dEntity {
@@ -37,6 +42,8 @@ impl Point3dEntity {
                 });
 
         Self {
+            transparent: vertex_data.iter().any(|v| v._color[3] < 1.0),
+            centroid: centroid(vertex_data.iter().map(|v| v._pos)),
             vertex_data,
             vertex_buffer,
             world_from_entity: points.world_from_entity,
@@ -47,6 +54,8 @@ impl Point3dEntity {
 /// Scene point renderer
 pub struct ScenePointRenderer {
     pub(crate) pipeline: wgpu::RenderPipeline,
+    pub(crate) transparent_pipeline: wgpu::RenderPipeline,
+    pub(crate) pick_pipeline: wgpu::RenderPipeline,
     pub(crate) point_table: BTreeMap<String, Point3dEntity>,
 }
 
@@ -69,6 +78,17 @@ impl ScenePointRenderer {
 
         Self {
             pipeline: scene_pipelines.create::<PointVertex3>("point".to_string(), &shader, None),
+            transparent_pipeline: scene_pipelines.create_transparent::<PointVertex3>(
+                "transparent point".to_string(),
+                &shader,
+                None,
+                &[],
+            ),
+            pick_pipeline: scene_pipelines.create_pick::<PointVertex3>(
+                "pick point".to_string(),
+                &shader,
+                &[],
+            ),
             point_table: BTreeMap::new(),
         }
     }
@@ -84,7 +104,7 @@ impl ScenePointRenderer {
         render_pass.set_pipeline(&self.pipeline);
         render_pass.set_bind_group(0, &buffers.render_bind_group, &[]);
 
-        for point in self.point_table.values() {
+        for point in self.point_table.values().filter(|point| !point.transparent) {
             buffers
                 .camera_from_entity_pose_buffer
                 .update_given_camera_and_entity(
@@ -96,4 +116,53 @@ impl ScenePointRenderer {
             render_pass.draw(0..point.vertex_data.len() as u32, 0..1);
         }
     }
+
+    pub(crate) fn paint_transparent<'rp>(
+        &'rp self,
+        render_context: &RenderContext,
+        world_from_camera: &Isometry3F64,
+        buffers: &'rp VertexShaderUniformBuffers,
+        render_pass: &mut wgpu::RenderPass<'rp>,
+        point: &'rp Point3dEntity,
+    ) {
+        render_pass.set_pipeline(&self.transparent_pipeline);
+        buffers
+            .camera_from_entity_pose_buffer
+            .update_given_camera_and_entity(
+                &render_context.wgpu_queue,
+                world_from_camera,
+                &point.world_from_entity,
+            );
+        render_pass.set_vertex_buffer(0, point.vertex_buffer.slice(..));
+        render_pass.draw(0..point.vertex_data.len() as u32, 0..1);
+    }
+
+    /// Draws all entities into the pick id texture, appending them to the pick entity table
+    ///
+    /// The entity id passed as instance index is the position in the table plus one, 0 is
+    /// reserved for the background.
+    pub(crate) fn paint_pick<'rp>(
+        &'rp self,
+        render_context: &RenderContext,
+        world_from_camera: &Isometry3F64,
+        uniforms: &'rp VertexShaderUniformBuffers,
+        render_pass: &mut wgpu::RenderPass<'rp>,
+        entities: &mut Vec<(ScenePickKind, String)>,
+    ) {
+        render_pass.set_pipeline(&self.pick_pipeline);
+
+        for (name, point) in self.point_table.iter() {
+            entities.push((ScenePickKind::Point, name.clone()));
+            let entity_id = entities.len() as u32;
+            uniforms
+                .camera_from_entity_pose_buffer
+                .update_given_camera_and_entity(
+                    &render_context.wgpu_queue,
+                    world_from_camera,
+                    &point.world_from_entity,
+                );
+            render_pass.set_vertex_buffer(0, point.vertex_buffer.slice(..));
+            render_pass.draw(0..point.vertex_data.len() as u32, entity_id..entity_id + 1);
+        }
+    }
 }
@@ -0,0 +1,419 @@
+use crate::pipeline_builder::PipelineBuilder;
+use crate::pipeline_builder::StreamPointVertex3;
+use crate::preludes::*;
+use crate::renderables::scene_renderable::Point3;
+use crate::renderables::scene_renderable::PointBudget;
+use crate::renderables::scene_renderable::PointStream3;
+use crate::types::ScenePickKind;
+use crate::uniform_buffers::VertexShaderUniformBuffers;
+use crate::RenderContext;
+use sophus::core::linalg::VecF64;
+use sophus::lie::Isometry3F64;
+
+/// Number of points per GPU buffer chunk
+const CHUNK_CAPACITY: usize = 1 << 16;
+
+fn voxel(p: [f32; 3], voxel_size: f32) -> [i64; 3] {
+    p.map(|x| (x / voxel_size).floor() as i64)
+}
+
+/// Retained points of a streamed point cloud and their level of detail, without GPU state
+///
+/// Per retained point, a 36-byte vertex and a 24-byte voxel key in a set are kept on the CPU, and
+/// the vertex once more on the GPU. Including the set overhead, this is roughly 110 bytes per
+/// point, see `PointBudget::max_points`.
+pub(crate) struct StreamPoints {
+    pub(crate) points: Vec<StreamPointVertex3>,
+    occupied_voxels: BTreeSet<[i64; 3]>,
+    budget: PointBudget,
+    // current voxel size, at least the one of the budget
+    voxel_size: f32,
+    position_sum: VecF64<3>,
+    pub(crate) transparent: bool,
+}
+
+impl StreamPoints {
+    pub(crate) fn new(budget: PointBudget) -> Self {
+        Self {
+            points: vec![],
+            occupied_voxels: BTreeSet::new(),
+            budget,
+            voxel_size: budget.voxel_size,
+            position_sum: VecF64::<3>::zeros(),
+            transparent: false,
+        }
+    }
+
+    /// Appends points, decimating the cloud if the budget is exceeded
+    ///
+    /// Returns the index of the first retained point which changed, i.e. which needs an upload.
+    pub(crate) fn append(&mut self, points: &[Point3], budget: PointBudget) -> usize {
+        self.budget = budget;
+
+        let mut first_changed = self.points.len();
+        if self.budget.voxel_size > self.voxel_size {
+            self.voxel_size = self.budget.voxel_size;
+            self.decimate();
+            first_changed = 0;
+        }
+
+        for point in points.iter() {
+            self.push(StreamPointVertex3 {
+                _pos: [point.p[0], point.p[1], point.p[2]],
+                _point_size: point.point_size,
+                _color: [point.color.r, point.color.g, point.color.b, point.color.a],
+                _index: 0,
+            });
+        }
+
+        let max_points = self.budget.max_points.max(1);
+        while self.points.len() > max_points {
+            let extent = self.extent();
+            if self.voxel_size > 2.0 * extent {
+                // all points are within a few voxels already, coarser ones would not help
+                self.points.truncate(max_points);
+                self.decimate();
+                first_changed = 0;
+                break;
+            }
+            self.voxel_size = if self.voxel_size > 0.0 {
+                2.0 * self.voxel_size
+            } else {
+                // roughly max_points voxels if the bounding box were filled
+                (extent / (max_points as f32).cbrt()).max(f32::MIN_POSITIVE)
+            };
+            self.decimate();
+            first_changed = 0;
+        }
+        first_changed.min(self.points.len())
+    }
+
+    /// Mean position of the retained points
+    pub(crate) fn centroid(&self) -> VecF64<3> {
+        if self.points.is_empty() {
+            return VecF64::<3>::zeros();
+        }
+        self.position_sum / self.points.len() as f64
+    }
+
+    /// Pushes the point unless its voxel is already occupied
+    fn push(&mut self, point: StreamPointVertex3) {
+        if self.voxel_size > 0.0
+            && !self
+                .occupied_voxels
+                .insert(voxel(point._pos, self.voxel_size))
+        {
+            return;
+        }
+        let p = point._pos;
+        self.position_sum += VecF64::<3>::new(p[0] as f64, p[1] as f64, p[2] as f64);
+        self.transparent |= point._color[3] < 1.0;
+        self.points.push(StreamPointVertex3 {
+            _index: self.points.len() as u32,
+            ..point
+        });
+    }
+
+    /// Keeps the first retained point of each voxel of the current size
+    fn decimate(&mut self) {
+        let points = core::mem::take(&mut self.points);
+        self.occupied_voxels.clear();
+        self.position_sum = VecF64::<3>::zeros();
+        self.transparent = false;
+        for point in points {
+            self.push(point);
+        }
+    }
+
+    /// Largest edge length of the bounding box of the retained points
+    fn extent(&self) -> f32 {
+        let mut min = [f32::MAX; 3];
+        let mut max = [f32::MIN; 3];
+        for point in self.points.iter() {
+            for i in 0..3 {
+                min[i] = min[i].min(point._pos[i]);
+                max[i] = max[i].max(point._pos[i]);
+            }
+        }
+        (0..3).map(|i| max[i] - min[i]).fold(0.0, f32::max)
+    }
+}
+
+/// Append-only point cloud, stored in fixed-size GPU buffer chunks
+///
+/// Appending writes the new points into the free tail of the last chunk and into new chunks, the
+/// points already uploaded are only rewritten when the level of detail changes.
+pub(crate) struct PointStreamEntity {
+    name: String,
+    pub(crate) points: StreamPoints,
+    chunks: Vec<wgpu::Buffer>,
+    pub(crate) world_from_entity: Isometry3F64,
+    pub(crate) transparent: bool,
+    pub(crate) centroid: VecF64<3>,
+}
+
+impl PointStreamEntity {
+    /// Create a new streamed point cloud from its first append
+    pub fn new(render_context: &RenderContext, stream: PointStream3) -> Self {
+        let mut entity = Self {
+            name: stream.name.clone(),
+            points: StreamPoints::new(stream.budget),
+            chunks: vec![],
+            world_from_entity: stream.world_from_entity,
+            transparent: false,
+            centroid: VecF64::<3>::zeros(),
+        };
+        entity.append(render_context, stream);
+        entity
+    }
+
+    /// Appends points, decimating the cloud if the budget is exceeded
+    pub fn append(&mut self, render_context: &RenderContext, stream: PointStream3) {
+        self.world_from_entity = stream.world_from_entity;
+        let first_changed = self.points.append(&stream.points, stream.budget);
+        self.transparent = self.points.transparent;
+        self.centroid = self.points.centroid();
+        self.upload(render_context, first_changed);
+    }
+
+    /// Writes the points starting at `first` to the chunks, allocating chunks as needed
+    fn upload(&mut self, render_context: &RenderContext, first: usize) {
+        let stride = core::mem::size_of::<StreamPointVertex3>();
+        let points = &self.points.points;
+        self.chunks.truncate(points.len().div_ceil(CHUNK_CAPACITY));
+
+        let mut start = first;
+        while start < points.len() {
+            let chunk_index = start / CHUNK_CAPACITY;
+            if chunk_index == self.chunks.len() {
+                self.chunks.push(render_context.wgpu_device.create_buffer(
+                    &wgpu::BufferDescriptor {
+                        label: Some(&format!(
+                            "3d point stream buffer {}: {}",
+                            chunk_index, self.name
+                        )),
+                        size: (CHUNK_CAPACITY * stride) as wgpu::BufferAddress,
+                        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                        mapped_at_creation: false,
+                    },
+                ));
+            }
+            let end = points.len().min((chunk_index + 1) * CHUNK_CAPACITY);
+            render_context.wgpu_queue.write_buffer(
+                &self.chunks[chunk_index],
+                ((start % CHUNK_CAPACITY) * stride) as wgpu::BufferAddress,
+                bytemuck::cast_slice(&points[start..end]),
+            );
+            start = end;
+        }
+    }
+
+    /// Draws each chunk as instances of a six-vertex quad
+    fn draw<'rp>(&'rp self, render_pass: &mut wgpu::RenderPass<'rp>, first_vertex: u32) {
+        for (chunk_index, chunk) in self.chunks.iter().enumerate() {
+            let len = (self.points.points.len() - chunk_index * CHUNK_CAPACITY).min(CHUNK_CAPACITY);
+            render_pass.set_vertex_buffer(0, chunk.slice(..));
+            render_pass.draw(first_vertex..first_vertex + 6, 0..len as u32);
+        }
+    }
+}
+
+/// Scene renderer of streamed point clouds
+pub struct PointStreamRenderer {
+    pub(crate) pipeline: wgpu::RenderPipeline,
+    pub(crate) transparent_pipeline: wgpu::RenderPipeline,
+    pub(crate) pick_pipeline: wgpu::RenderPipeline,
+    pub(crate) point_stream_table: BTreeMap<String, PointStreamEntity>,
+}
+
+impl PointStreamRenderer {
+    /// Create a new point stream renderer
+    pub fn new(render_context: &RenderContext, scene_pipelines: &PipelineBuilder) -> Self {
+        let device = &render_context.wgpu_device;
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("scene point stream shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                format!(
+                    "{} {}",
+                    include_str!("./../shaders/utils.wgsl"),
+                    include_str!("./../shaders/scene_point_stream.wgsl")
+                )
+                .into(),
+            ),
+        });
+
+        Self {
+            pipeline: scene_pipelines.create::<StreamPointVertex3>(
+                "point stream".to_string(),
+                &shader,
+                None,
+            ),
+            transparent_pipeline: scene_pipelines.create_transparent::<StreamPointVertex3>(
+                "transparent point stream".to_string(),
+                &shader,
+                None,
+                &[],
+            ),
+            pick_pipeline: scene_pipelines.create_pick::<StreamPointVertex3>(
+                "pick point stream".to_string(),
+                &shader,
+                &[],
+            ),
+            point_stream_table: BTreeMap::new(),
+        }
+    }
+
+    /// Appends to the named streamed point cloud, creating it if not present
+    pub(crate) fn append(&mut self, render_context: &RenderContext, stream: PointStream3) {
+        match self.point_stream_table.get_mut(&stream.name) {
+            Some(entity) => entity.append(render_context, stream),
+            None => {
+                self.point_stream_table.insert(
+                    stream.name.clone(),
+                    PointStreamEntity::new(render_context, stream),
+                );
+            }
+        }
+    }
+
+    pub(crate) fn paint<'rp>(
+        &'rp self,
+        render_context: &RenderContext,
+        scene_from_camera: &Isometry3F64,
+        world_from_scene: &Isometry3F64,
+        buffers: &'rp VertexShaderUniformBuffers,
+        render_pass: &mut wgpu::RenderPass<'rp>,
+    ) {
+        render_pass.set_pipeline(&self.pipeline);
+
+        for points in self
+            .point_stream_table
+            .values()
+            .filter(|points| !points.transparent)
+        {
+            buffers
+                .camera_from_entity_pose_buffer
+                .update_given_camera_and_entity(
+                    &render_context.wgpu_queue,
+                    &world_from_scene.group_mul(scene_from_camera),
+                    &points.world_from_entity,
+                );
+            points.draw(render_pass, 0);
+        }
+    }
+
+    pub(crate) fn paint_transparent<'rp>(
+        &'rp self,
+        render_context: &RenderContext,
+        world_from_camera: &Isometry3F64,
+        buffers: &'rp VertexShaderUniformBuffers,
+        render_pass: &mut wgpu::RenderPass<'rp>,
+        points: &'rp PointStreamEntity,
+    ) {
+        render_pass.set_pipeline(&self.transparent_pipeline);
+        buffers
+            .camera_from_entity_pose_buffer
+            .update_given_camera_and_entity(
+                &render_context.wgpu_queue,
+                world_from_camera,
+                &points.world_from_entity,
+            );
+        points.draw(render_pass, 0);
+    }
+
+    /// Draws all entities into the pick id texture, appending them to the pick entity table
+    ///
+    /// The instance index is the point index within its chunk, hence the entity id is passed as
+    /// the first vertex divided by six.
+    pub(crate) fn paint_pick<'rp>(
+        &'rp self,
+        render_context: &RenderContext,
+        world_from_camera: &Isometry3F64,
+        uniforms: &'rp VertexShaderUniformBuffers,
+        render_pass: &mut wgpu::RenderPass<'rp>,
+        entities: &mut Vec<(ScenePickKind, String)>,
+    ) {
+        render_pass.set_pipeline(&self.pick_pipeline);
+
+        for (name, points) in self.point_stream_table.iter() {
+            entities.push((ScenePickKind::PointStream, name.clone()));
+            let entity_id = entities.len() as u32;
+            uniforms
+                .camera_from_entity_pose_buffer
+                .update_given_camera_and_entity(
+                    &render_context.wgpu_queue,
+                    world_from_camera,
+                    &points.world_from_entity,
+                );
+            points.draw(render_pass, 6 * entity_id);
+        }
+    }
+}
+
+#[test]
+fn stream_points_keep_one_point_per_voxel() {
+    use crate::renderables::color::Color;
+    use sophus::core::linalg::SVec;
+
+    let point = |x: f32, y: f32, z: f32| Point3 {
+        p: SVec::<f32, 3>::new(x, y, z),
+        color: Color::red(),
+        point_size: 1.0,
+    };
+    let budget = PointBudget {
+        max_points: usize::MAX,
+        voxel_size: 1.0,
+    };
+    let mut points = StreamPoints::new(budget);
+    assert_eq!(
+        points.append(&[point(0.1, 0.1, 0.1), point(0.9, 0.5, 0.2)], budget),
+        0
+    );
+    assert_eq!(points.points.len(), 1);
+    // the first point of the voxel is kept, only the new one needs an upload
+    assert_eq!(
+        points.append(&[point(0.5, 0.5, 0.5), point(1.5, 0.5, 0.5)], budget),
+        1
+    );
+    assert_eq!(points.points.len(), 2);
+    assert_eq!(points.points[0]._pos, [0.1, 0.1, 0.1]);
+    assert_eq!(points.points[1]._pos, [1.5, 0.5, 0.5]);
+    assert_eq!(points.points[1]._index, 1);
+}
+
+#[test]
+fn stream_points_respect_max_points() {
+    use crate::renderables::color::Color;
+    use sophus::core::linalg::SVec;
+
+    let budget = PointBudget {
+        max_points: 100,
+        voxel_size: 0.0,
+    };
+    let mut points = StreamPoints::new(budget);
+    for batch in 0..10 {
+        let grid: Vec<_> = (0..1000)
+            .map(|i| Point3 {
+                p: SVec::<f32, 3>::new(
+                    (i % 10) as f32,
+                    (i / 10 % 10) as f32,
+                    (i / 100) as f32 + 0.01 * batch as f32,
+                ),
+                color: Color::red(),
+                point_size: 1.0,
+            })
+            .collect();
+        points.append(&grid, budget);
+        assert!(!points.points.is_empty());
+        assert!(points.points.len() <= budget.max_points);
+
+        // no two retained points share a voxel of the current size
+        let voxels: BTreeSet<_> = points
+            .points
+            .iter()
+            .map(|point| voxel(point._pos, points.voxel_size))
+            .collect();
+        assert_eq!(voxels.len(), points.points.len());
+    }
+}
@@ -0,0 +1,154 @@
+use crate::pipeline_builder::LineVertex3;
+use crate::preludes::*;
+use crate::renderables::scene_renderable::Polyline3;
+use crate::renderables::scene_renderable::PolylineVertex3;
+use crate::scene_renderer::centroid;
+use crate::RenderContext;
+use sophus::core::linalg::VecF64;
+use sophus::lie::Isometry3F64;
+
+/// Minimal number of segments the vertex buffer is allocated for
+const MIN_SEGMENT_CAPACITY: usize = 256;
+
+/// Append-only polyline, drawn by the scene line renderer
+///
+/// The vertex buffer grows geometrically and appending only writes the new segments. Vertices
+/// outside the time window are skipped when drawing and dropped once they make up more than half
+/// of the buffer.
+pub(crate) struct Polyline3dEntity {
+    name: String,
+    // the vertices before `first` are outside the time window
+    vertices: Vec<PolylineVertex3>,
+    first: usize,
+    // six line vertices per segment between consecutive vertices
+    vertex_data: Vec<LineVertex3>,
+    vertex_buffer: wgpu::Buffer,
+    segment_capacity: usize,
+    line_width: f32,
+    pub(crate) world_from_entity: Isometry3F64,
+    pub(crate) transparent: bool,
+    pub(crate) centroid: VecF64<3>,
+}
+
+fn create_vertex_buffer(
+    render_context: &RenderContext,
+    name: &str,
+    segment_capacity: usize,
+) -> wgpu::Buffer {
+    render_context
+        .wgpu_device
+        .create_buffer(&wgpu::BufferDescriptor {
+            label: Some(&format!("3D polyline vertex buffer: {}", name)),
+            size: (6 * segment_capacity * core::mem::size_of::<LineVertex3>())
+                as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+}
+
+impl Polyline3dEntity {
+    /// Create a new polyline from its first append
+    pub fn new(render_context: &RenderContext, polyline: Polyline3) -> Self {
+        let mut entity = Self {
+            vertex_buffer: create_vertex_buffer(
+                render_context,
+                &polyline.name,
+                MIN_SEGMENT_CAPACITY,
+            ),
+            name: polyline.name.clone(),
+            vertices: vec![],
+            first: 0,
+            vertex_data: vec![],
+            segment_capacity: MIN_SEGMENT_CAPACITY,
+            line_width: polyline.line_width,
+            world_from_entity: polyline.world_from_entity,
+            transparent: false,
+            centroid: VecF64::<3>::zeros(),
+        };
+        entity.append(render_context, polyline);
+        entity
+    }
+
+    fn segment(&self, v0: &PolylineVertex3, v1: &PolylineVertex3) -> LineVertex3 {
+        LineVertex3 {
+            _p0: [v0.p[0], v0.p[1], v0.p[2]],
+            _p1: [v1.p[0], v1.p[1], v1.p[2]],
+            _color0: [v0.color.r, v0.color.g, v0.color.b, v0.color.a],
+            _line_width: self.line_width,
+            _color1: [v1.color.r, v1.color.g, v1.color.b, v1.color.a],
+        }
+    }
+
+    /// Appends vertices and drops the ones outside the time window
+    pub fn append(&mut self, render_context: &RenderContext, polyline: Polyline3) {
+        self.world_from_entity = polyline.world_from_entity;
+
+        let mut rewrite_all = false;
+        if polyline.line_width != self.line_width {
+            self.line_width = polyline.line_width;
+            for v in self.vertex_data.iter_mut() {
+                v._line_width = polyline.line_width;
+            }
+            rewrite_all = true;
+        }
+
+        let num_uploaded = self.vertex_data.len();
+        for vertex in polyline.vertices {
+            if let Some(last) = self.vertices.last() {
+                let segment = self.segment(last, &vertex);
+                for _i in 0..6 {
+                    self.vertex_data.push(segment);
+                }
+            }
+            self.vertices.push(vertex);
+        }
+
+        if let (Some(window), Some(newest)) = (polyline.time_window, self.vertices.last()) {
+            let oldest_t = newest.t - window;
+            while self.first + 1 < self.vertices.len() && self.vertices[self.first].t < oldest_t {
+                self.first += 1;
+            }
+        }
+        if self.first > self.vertices.len() / 2 {
+            self.vertices.drain(..self.first);
+            self.vertex_data.drain(..6 * self.first);
+            self.first = 0;
+            rewrite_all = true;
+        }
+
+        let num_segments = self.vertices.len().saturating_sub(1);
+        if num_segments > self.segment_capacity {
+            self.segment_capacity = (2 * num_segments).max(MIN_SEGMENT_CAPACITY);
+            self.vertex_buffer =
+                create_vertex_buffer(render_context, &self.name, self.segment_capacity);
+            rewrite_all = true;
+        }
+        let first_written = if rewrite_all { 0 } else { num_uploaded };
+        render_context.wgpu_queue.write_buffer(
+            &self.vertex_buffer,
+            (first_written * core::mem::size_of::<LineVertex3>()) as wgpu::BufferAddress,
+            bytemuck::cast_slice(&self.vertex_data[first_written..]),
+        );
+
+        let retained = &self.vertices[self.first..];
+        self.transparent = retained.iter().any(|v| v.color.a < 1.0);
+        self.centroid = centroid(retained.iter().map(|v| [v.p[0], v.p[1], v.p[2]]));
+    }
+
+    /// Draws the segments within the time window
+    pub(crate) fn draw<'rp>(&'rp self, render_pass: &mut wgpu::RenderPass<'rp>, entity_id: u32) {
+        let num_segments = self.vertices.len().saturating_sub(self.first + 1);
+        if num_segments == 0 {
+            return;
+        }
+        let stride = core::mem::size_of::<LineVertex3>();
+        let start = 6 * self.first * stride;
+        let end = start + 6 * num_segments * stride;
+        render_pass.set_vertex_buffer(
+            0,
+            self.vertex_buffer
+                .slice(start as wgpu::BufferAddress..end as wgpu::BufferAddress),
+        );
+        render_pass.draw(0..6 * num_segments as u32, entity_id..entity_id + 1);
+    }
+}
@@ -0,0 +1,302 @@
+use crate::pipeline_builder::PipelineBuilder;
+use crate::pipeline_builder::PipelineVariant;
+use crate::pipeline_builder::ScalarPointVertex3;
+use crate::pipeline_builder::ScalarVertex;
+use crate::preludes::*;
+use crate::renderables::color::Colormap;
+use crate::renderables::scene_renderable::PointColoring;
+use crate::renderables::scene_renderable::ScalarPointCloud3;
+use crate::types::ScenePickKind;
+use crate::uniform_buffers::VertexShaderUniformBuffers;
+use crate::RenderContext;
+use sophus::lie::Isometry3F64;
+use wgpu::util::DeviceExt;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct ScalarColoringUniform {
+    range_min: f32,
+    range_max: f32,
+    colormap: u32,
+    _padding: u32,
+}
+
+/// Point cloud with scalar fields
+///
+/// Each field is uploaded once into its own buffer, hence changing the coloring only rebinds a
+/// buffer and updates a uniform.
+pub struct ScalarPoint3dEntity {
+    num_points: u32,
+    vertex_buffer: wgpu::Buffer,
+    field_names: Vec<String>,
+    field_ranges: Vec<[f32; 2]>,
+    field_buffers: Vec<wgpu::Buffer>,
+    coloring: PointColoring,
+    coloring_buffer: wgpu::Buffer,
+    coloring_bind_group: wgpu::BindGroup,
+    pub(crate) world_from_entity: Isometry3F64,
+}
+
+impl ScalarPoint3dEntity {
+    /// Create a new point cloud with scalar fields
+    pub(crate) fn new(
+        render_context: &RenderContext,
+        points: &ScalarPointCloud3,
+        coloring_bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> Self {
+        let device = &render_context.wgpu_device;
+        let vertex_data: Vec<ScalarPointVertex3> = points
+            .positions
+            .iter()
+            .map(|p| ScalarPointVertex3 {
+                _pos: [p[0], p[1], p[2]],
+                _point_size: points.point_size,
+            })
+            .collect();
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(&format!("3d scalar point vertex buffer: {}", points.name)),
+            contents: bytemuck::cast_slice(&vertex_data),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        let mut field_ranges = vec![];
+        let mut field_buffers = vec![];
+        for field in points.fields.iter() {
+            // missing values are padded with the first value, extra ones are dropped
+            let pad = field.values.first().copied().unwrap_or_default();
+            let values: Vec<ScalarVertex> = (0..vertex_data.len())
+                .map(|i| ScalarVertex {
+                    _value: field.values.get(i).copied().unwrap_or(pad),
+                })
+                .collect();
+            let finite = || values.iter().map(|v| v._value).filter(|v| v.is_finite());
+            field_ranges.push(
+                match (finite().reduce(f32::min), finite().reduce(f32::max)) {
+                    (Some(min), Some(max)) => [min, max],
+                    _ => [0.0, 1.0],
+                },
+            );
+            field_buffers.push(
+                device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some(&format!(
+                        "3d scalar point field buffer: {}, {}",
+                        points.name, field.name
+                    )),
+                    contents: bytemuck::cast_slice(&values),
+                    usage: wgpu::BufferUsages::VERTEX,
+                }),
+            );
+        }
+
+        let coloring_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(&format!("3d scalar point coloring: {}", points.name)),
+            size: core::mem::size_of::<ScalarColoringUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let coloring_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: coloring_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: coloring_buffer.as_entire_binding(),
+            }],
+            label: Some(&format!("3d scalar point bind group: {}", points.name)),
+        });
+
+        Self {
+            num_points: vertex_data.len() as u32,
+            vertex_buffer,
+            field_names: points.fields.iter().map(|f| f.name.clone()).collect(),
+            field_ranges,
+            field_buffers,
+            coloring: points.coloring,
+            coloring_buffer,
+            coloring_bind_group,
+            world_from_entity: points.world_from_entity,
+        }
+    }
+
+    /// Names of the scalar fields
+    pub fn field_names(&self) -> &[String] {
+        &self.field_names
+    }
+
+    /// Minimum and maximum of the finite values of the given field
+    pub fn field_range(&self, field: usize) -> Option<[f32; 2]> {
+        self.field_ranges.get(field).copied()
+    }
+
+    /// Current coloring
+    pub fn coloring(&self) -> PointColoring {
+        self.coloring
+    }
+
+    /// Changes the coloring, a field index out of range colors by the first field
+    pub fn set_coloring(&mut self, coloring: PointColoring) {
+        self.coloring = coloring;
+    }
+
+    fn field(&self) -> usize {
+        if self.coloring.field < self.field_buffers.len() {
+            self.coloring.field
+        } else {
+            0
+        }
+    }
+
+    fn update_coloring_uniform(&self, render_context: &RenderContext) {
+        let field = self.field();
+        let [range_min, range_max] = self
+            .coloring
+            .range
+            .or(self.field_range(field))
+            .unwrap_or([0.0, 1.0]);
+        let colormap = match self.coloring.colormap {
+            Colormap::BlueWhiteRedBlack => 0,
+            Colormap::Grayscale => 1,
+            Colormap::Viridis => 2,
+            Colormap::Turbo => 3,
+        };
+        render_context.wgpu_queue.write_buffer(
+            &self.coloring_buffer,
+            0,
+            bytemuck::cast_slice(&[ScalarColoringUniform {
+                range_min,
+                range_max,
+                colormap,
+                _padding: 0,
+            }]),
+        );
+    }
+
+    fn draw<'rp>(&'rp self, render_pass: &mut wgpu::RenderPass<'rp>, first_vertex: u32) {
+        // without fields there is nothing to color by
+        let Some(field_buffer) = self.field_buffers.get(self.field()) else {
+            return;
+        };
+        if self.num_points == 0 {
+            return;
+        }
+        render_pass.set_bind_group(1, &self.coloring_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_vertex_buffer(1, field_buffer.slice(..));
+        render_pass.draw(first_vertex..first_vertex + 6, 0..self.num_points);
+    }
+}
+
+/// Scene renderer of point clouds colored by scalar fields
+pub struct ScalarPointRenderer {
+    pipeline: wgpu::RenderPipeline,
+    pick_pipeline: wgpu::RenderPipeline,
+    pub(crate) coloring_bind_group_layout: wgpu::BindGroupLayout,
+    pub(crate) scalar_point_table: BTreeMap<String, ScalarPoint3dEntity>,
+}
+
+impl ScalarPointRenderer {
+    /// Create a new scalar point renderer
+    pub fn new(render_context: &RenderContext, scene_pipelines: &PipelineBuilder) -> Self {
+        let device = &render_context.wgpu_device;
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("scene scalar point shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                format!(
+                    "{} {}",
+                    include_str!("./../shaders/utils.wgsl"),
+                    include_str!("./../shaders/scene_scalar_point.wgsl")
+                )
+                .into(),
+            ),
+        });
+
+        let coloring_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+                label: Some("scalar point coloring bind group layout"),
+            });
+
+        Self {
+            pipeline: scene_pipelines.create_instanced::<ScalarPointVertex3, ScalarVertex>(
+                "scalar point".to_string(),
+                &shader,
+                None,
+                &[&coloring_bind_group_layout],
+                PipelineVariant::Opaque,
+            ),
+            pick_pipeline: scene_pipelines.create_instanced::<ScalarPointVertex3, ScalarVertex>(
+                "pick scalar point".to_string(),
+                &shader,
+                None,
+                &[&coloring_bind_group_layout],
+                PipelineVariant::Pick,
+            ),
+            coloring_bind_group_layout,
+            scalar_point_table: BTreeMap::new(),
+        }
+    }
+
+    /// Point clouds with scalar fields by name, e.g. to change their coloring
+    pub fn entities_mut(&mut self) -> impl Iterator<Item = (&String, &mut ScalarPoint3dEntity)> {
+        self.scalar_point_table.iter_mut()
+    }
+
+    pub(crate) fn paint<'rp>(
+        &'rp self,
+        render_context: &RenderContext,
+        scene_from_camera: &Isometry3F64,
+        world_from_scene: &Isometry3F64,
+        uniforms: &'rp VertexShaderUniformBuffers,
+        render_pass: &mut wgpu::RenderPass<'rp>,
+    ) {
+        render_pass.set_pipeline(&self.pipeline);
+
+        for points in self.scalar_point_table.values() {
+            uniforms
+                .camera_from_entity_pose_buffer
+                .update_given_camera_and_entity(
+                    &render_context.wgpu_queue,
+                    &world_from_scene.group_mul(scene_from_camera),
+                    &points.world_from_entity,
+                );
+            points.update_coloring_uniform(render_context);
+            points.draw(render_pass, 0);
+        }
+    }
+
+    /// Draws all entities into the pick id texture, appending them to the pick entity table
+    ///
+    /// The instance index is the point index, hence the entity id is passed as the first vertex
+    /// divided by six.
+    pub(crate) fn paint_pick<'rp>(
+        &'rp self,
+        render_context: &RenderContext,
+        world_from_camera: &Isometry3F64,
+        uniforms: &'rp VertexShaderUniformBuffers,
+        render_pass: &mut wgpu::RenderPass<'rp>,
+        entities: &mut Vec<(ScenePickKind, String)>,
+    ) {
+        render_pass.set_pipeline(&self.pick_pipeline);
+
+        for (name, points) in self.scalar_point_table.iter() {
+            entities.push((ScenePickKind::ScalarPoint, name.clone()));
+            let entity_id = entities.len() as u32;
+            uniforms
+                .camera_from_entity_pose_buffer
+                .update_given_camera_and_entity(
+                    &render_context.wgpu_queue,
+                    world_from_camera,
+                    &points.world_from_entity,
+                );
+            points.draw(render_pass, 6 * entity_id);
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use crate::pipeline_builder::PipelineBuilder;
+use crate::pipeline_builder::TextVertex3;
+use crate::preludes::*;
+use crate::renderables::scene_renderable::Texts3;
+use crate::textures::glyph_atlas::GlyphAtlas;
+use crate::uniform_buffers::VertexShaderUniformBuffers;
+use crate::RenderContext;
+use sophus::lie::Isometry3F64;
+use wgpu::util::DeviceExt;
+
+pub(crate) struct Text3dEntity {
+    pub(crate) texts: Texts3,
+    pub(crate) vertex_data: Vec<TextVertex3>,
+    pub(crate) vertex_buffer: wgpu::Buffer,
+    pub(crate) world_from_entity: Isometry3F64,
+}
+
+impl Text3dEntity {
+    pub(crate) fn new(render_context: &RenderContext, texts: &Texts3, atlas: &GlyphAtlas) -> Self {
+        let mut vertex_data = vec![];
+        for text in texts.texts.iter() {
+            for quad in atlas.layout(&text.text, text.font_size, text.anchor) {
+                for (offset, tex) in quad.vertices() {
+                    vertex_data.push(TextVertex3 {
+                        _pos: [text.p[0], text.p[1], text.p[2]],
+                        _offset: offset,
+                        _tex: tex,
+                        _color: [text.color.r, text.color.g, text.color.b, text.color.a],
+                    });
+                }
+            }
+        }
+
+        let vertex_buffer =
+            render_context
+                .wgpu_device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some(&format!("3D text vertex buffer: {}", texts.name)),
+                    contents: bytemuck::cast_slice(&vertex_data),
+                    usage: wgpu::BufferUsages::VERTEX,
+                });
+
+        Self {
+            texts: texts.clone(),
+            vertex_data,
+            vertex_buffer,
+            world_from_entity: texts.world_from_entity,
+        }
+    }
+}
+
+/// Scene text renderer
+///
+/// Labels are alpha blended and depth tested, but do not write depth. They are drawn after all
+/// other renderables.
+pub struct SceneTextRenderer {
+    pub(crate) pipeline: wgpu::RenderPipeline,
+    pub(crate) glyph_atlas: GlyphAtlas,
+    pub(crate) text_table: BTreeMap<String, Text3dEntity>,
+}
+
+impl SceneTextRenderer {
+    /// Create a new scene text renderer
+    pub fn new(render_context: &RenderContext, scene_pipelines: &PipelineBuilder) -> Self {
+        let device = &render_context.wgpu_device;
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("scene text shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                format!(
+                    "{} {}",
+                    include_str!("./../shaders/utils.wgsl"),
+                    include_str!("./../shaders/scene_text.wgsl")
+                )
+                .into(),
+            ),
+        });
+
+        let glyph_atlas = GlyphAtlas::new(render_context);
+
+        Self {
+            pipeline: scene_pipelines.create_transparent::<TextVertex3>(
+                "text".to_string(),
+                &shader,
+                None,
+                &[&glyph_atlas.bind_group_layout],
+            ),
+            glyph_atlas,
+            text_table: BTreeMap::new(),
+        }
+    }
+
+    /// Start a new frame of the glyph atlas, and lay out all texts again if it was rebuilt
+    pub(crate) fn begin_frame(&mut self, render_context: &RenderContext) {
+        if !self.glyph_atlas.begin_frame() {
+            return;
+        }
+        for entity in self.text_table.values_mut() {
+            *entity = Text3dEntity::new(render_context, &entity.texts, &self.glyph_atlas);
+        }
+        self.glyph_atlas.upload(render_context);
+    }
+
+    pub(crate) fn paint<'rp>(
+        &'rp self,
+        render_context: &RenderContext,
+        world_from_camera: &Isometry3F64,
+        uniforms: &'rp VertexShaderUniformBuffers,
+        render_pass: &mut wgpu::RenderPass<'rp>,
+    ) {
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(1, &self.glyph_atlas.bind_group, &[]);
+
+        for text in self.text_table.values() {
+            uniforms
+                .camera_from_entity_pose_buffer
+                .update_given_camera_and_entity(
+                    &render_context.wgpu_queue,
+                    world_from_camera,
+                    &text.world_from_entity,
+                );
+            render_pass.set_vertex_buffer(0, text.vertex_buffer.slice(..));
+            render_pass.draw(0..text.vertex_data.len() as u32, 0..1);
+        }
+    }
+}
@@ -2,29 +2,32 @@ use crate::pipeline_builder::PipelineBuilder;
 use crate::pipeline_builder::TexturedMeshVertex3;
 use crate::preludes::*;
 use crate::renderables::scene_renderable::TexturedTriangleMesh3;
+use crate::scene_renderer::centroid;
+use crate::types::ScenePickKind;
 use crate::uniform_buffers::VertexShaderUniformBuffers;
 use crate::RenderContext;
+use sophus::core::linalg::VecF64;
 use sophus::core::IsTensorLike;
-use sophus::image::arc_image::ArcImage4U8;
 use sophus::image::image_view::IsImageView;
 use sophus::lie::Isometry3F64;
 use wgpu::util::DeviceExt;
 
-/// mesh entity
-pub struct TexturedMeshEntity {
-    pub(crate) _vertex_data: Vec<TexturedMeshVertex3>,
-    pub(crate) _vertex_buffer: wgpu::Buffer,
+pub(crate) struct TexturedMeshEntity {
+    pub(crate) vertex_data: Vec<TexturedMeshVertex3>,
+    pub(crate) vertex_buffer: wgpu::Buffer,
     pub(crate) _texture: wgpu::Texture,
-    pub(crate) _texture_bind_group: wgpu::BindGroup,
-    pub(crate) _scene_from_entity: Isometry3F64,
+    pub(crate) texture_bind_group: wgpu::BindGroup,
+    pub(crate) world_from_entity: Isometry3F64,
+    pub(crate) transparent: bool,
+    pub(crate) centroid: VecF64<3>,
 }
 
 impl TexturedMeshEntity {
-    /// new
+    /// Create a new 3D textured mesh entity
     pub fn new(
         render_context: &RenderContext,
         mesh: &TexturedTriangleMesh3,
-        image: ArcImage4U8,
+        texture_bind_group_layout: &wgpu::BindGroupLayout,
     ) -> Self {
         let vertex_data: Vec<TexturedMeshVertex3> = mesh
             .triangles
@@ -51,46 +54,24 @@ impl TexturedMeshEntity {
             render_context
                 .wgpu_device
                 .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                    label: Some(&format!("3D mesh vertex buffer: {}", mesh.name)),
+                    label: Some(&format!("3D textured mesh vertex buffer: {}", mesh.name)),
                     contents: bytemuck::cast_slice(&vertex_data),
                     usage: wgpu::BufferUsages::VERTEX,
                 });
 
         let device = &render_context.wgpu_device;
 
-        let background_bind_group_layout =
-            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                entries: &[
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 0,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Texture {
-                            multisampled: false,
-                            view_dimension: wgpu::TextureViewDimension::D2,
-                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                        },
-                        count: None,
-                    },
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 1,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                        count: None,
-                    },
-                ],
-                label: Some("background_texture_bind_group_layout"),
-            });
-
         let texture_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
             address_mode_u: wgpu::AddressMode::ClampToEdge,
             address_mode_v: wgpu::AddressMode::ClampToEdge,
             address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter: wgpu::FilterMode::Nearest,
-            min_filter: wgpu::FilterMode::Nearest,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
             mipmap_filter: wgpu::FilterMode::Nearest,
             ..Default::default()
         });
 
+        let image = &mesh.texture;
         let texture_size = wgpu::Extent3d {
             width: image.image_size().width as u32,
             height: image.image_size().height as u32,
@@ -103,27 +84,10 @@ impl TexturedMeshEntity {
             dimension: wgpu::TextureDimension::D2,
             format: wgpu::TextureFormat::Rgba8Unorm,
             usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-            label: Some("dist_texture"),
+            label: Some(&format!("3D textured mesh texture: {}", mesh.name)),
             view_formats: &[],
         });
 
-        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-
-        let texture_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            layout: &background_bind_group_layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: wgpu::BindingResource::TextureView(&texture_view),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: wgpu::BindingResource::Sampler(&texture_sampler),
-                },
-            ],
-            label: Some("background_bind_group"),
-        });
-
         render_context.wgpu_queue.write_texture(
             wgpu::ImageCopyTexture {
                 texture: &texture,
@@ -140,26 +104,56 @@ impl TexturedMeshEntity {
             texture.size(),
         );
 
+        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let texture_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: texture_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&texture_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&texture_sampler),
+                },
+            ],
+            label: Some(&format!("3D textured mesh bind group: {}", mesh.name)),
+        });
+
+        let transparent = image
+            .tensor
+            .scalar_view()
+            .iter()
+            .skip(3)
+            .step_by(4)
+            .any(|alpha| *alpha < 255);
+
         Self {
-            _vertex_data: vertex_data,
-            _vertex_buffer: vertex_buffer,
+            transparent,
+            centroid: centroid(vertex_data.iter().map(|v| v._pos)),
+            vertex_data,
+            vertex_buffer,
             _texture: texture,
-            _texture_bind_group: texture_bind_group,
-            _scene_from_entity: mesh.world_from_entity,
+            texture_bind_group,
+            world_from_entity: mesh.world_from_entity,
         }
     }
 }
 
 /// Scene textured mesh renderer
 pub struct TexturedMeshRenderer {
-    /// pipeline
-    pub pipeline: wgpu::RenderPipeline,
-    /// table
-    pub mesh_table: BTreeMap<String, TexturedMeshEntity>,
+    pub(crate) pipeline_without_culling: wgpu::RenderPipeline,
+    pub(crate) pipeline_with_culling: wgpu::RenderPipeline,
+    pub(crate) transparent_pipeline_without_culling: wgpu::RenderPipeline,
+    pub(crate) transparent_pipeline_with_culling: wgpu::RenderPipeline,
+    pub(crate) pick_pipeline: wgpu::RenderPipeline,
+    pub(crate) texture_bind_group_layout: wgpu::BindGroupLayout,
+    pub(crate) mesh_table: BTreeMap<String, TexturedMeshEntity>,
 }
 
 impl TexturedMeshRenderer {
-    /// new
+    /// Create a new scene textured mesh renderer
     pub fn new(render_context: &RenderContext, scene_pipelines: &PipelineBuilder) -> Self {
         let device = &render_context.wgpu_device;
 
@@ -175,38 +169,152 @@ impl TexturedMeshRenderer {
             ),
         });
 
+        let texture_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            multisampled: false,
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                ],
+                label: Some("textured mesh bind group layout"),
+            });
+
         Self {
-            pipeline: scene_pipelines.create::<TexturedMeshVertex3>(
-                "textured-mesh".to_string(),
+            pipeline_with_culling: scene_pipelines
+                .create_with_bind_group_layouts::<TexturedMeshVertex3>(
+                    "textured-mesh with culling".to_string(),
+                    &shader,
+                    Some(wgpu::Face::Back),
+                    &[&texture_bind_group_layout],
+                ),
+            pipeline_without_culling: scene_pipelines
+                .create_with_bind_group_layouts::<TexturedMeshVertex3>(
+                    "textured-mesh".to_string(),
+                    &shader,
+                    None,
+                    &[&texture_bind_group_layout],
+                ),
+            transparent_pipeline_with_culling: scene_pipelines
+                .create_transparent::<TexturedMeshVertex3>(
+                    "transparent textured-mesh with culling".to_string(),
+                    &shader,
+                    Some(wgpu::Face::Back),
+                    &[&texture_bind_group_layout],
+                ),
+            transparent_pipeline_without_culling: scene_pipelines
+                .create_transparent::<TexturedMeshVertex3>(
+                    "transparent textured-mesh".to_string(),
+                    &shader,
+                    None,
+                    &[&texture_bind_group_layout],
+                ),
+            pick_pipeline: scene_pipelines.create_pick::<TexturedMeshVertex3>(
+                "pick textured-mesh".to_string(),
                 &shader,
-                Some(wgpu::Face::Back),
+                &[&texture_bind_group_layout],
             ),
+            texture_bind_group_layout,
             mesh_table: BTreeMap::new(),
         }
     }
 
-    /// paint
-    pub fn paint<'rp>(
+    pub(crate) fn paint<'rp>(
         &'rp self,
         render_context: &RenderContext,
         scene_from_camera: &Isometry3F64,
         world_from_scene: &Isometry3F64,
         uniforms: &'rp VertexShaderUniformBuffers,
         render_pass: &mut wgpu::RenderPass<'rp>,
+        backface_culling: bool,
     ) {
-        render_pass.set_pipeline(&self.pipeline);
+        let pipeline = if backface_culling {
+            &self.pipeline_with_culling
+        } else {
+            &self.pipeline_without_culling
+        };
+        render_pass.set_pipeline(pipeline);
 
-        for mesh in self.mesh_table.values() {
+        for mesh in self.mesh_table.values().filter(|mesh| !mesh.transparent) {
             uniforms
                 .camera_from_entity_pose_buffer
                 .update_given_camera_and_entity(
                     &render_context.wgpu_queue,
                     &world_from_scene.group_mul(scene_from_camera),
-                    &mesh._scene_from_entity,
+                    &mesh.world_from_entity,
+                );
+            render_pass.set_bind_group(1, &mesh.texture_bind_group, &[]);
+            render_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+            render_pass.draw(0..mesh.vertex_data.len() as u32, 0..1);
+        }
+    }
+
+    pub(crate) fn paint_transparent<'rp>(
+        &'rp self,
+        render_context: &RenderContext,
+        world_from_camera: &Isometry3F64,
+        uniforms: &'rp VertexShaderUniformBuffers,
+        render_pass: &mut wgpu::RenderPass<'rp>,
+        mesh: &'rp TexturedMeshEntity,
+        backface_culling: bool,
+    ) {
+        let pipeline = if backface_culling {
+            &self.transparent_pipeline_with_culling
+        } else {
+            &self.transparent_pipeline_without_culling
+        };
+        render_pass.set_pipeline(pipeline);
+        uniforms
+            .camera_from_entity_pose_buffer
+            .update_given_camera_and_entity(
+                &render_context.wgpu_queue,
+                world_from_camera,
+                &mesh.world_from_entity,
+            );
+        render_pass.set_bind_group(1, &mesh.texture_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+        render_pass.draw(0..mesh.vertex_data.len() as u32, 0..1);
+    }
+
+    /// Draws all entities into the pick id texture, appending them to the pick entity table
+    ///
+    /// The entity id passed as instance index is the position in the table plus one, 0 is
+    /// reserved for the background.
+    pub(crate) fn paint_pick<'rp>(
+        &'rp self,
+        render_context: &RenderContext,
+        world_from_camera: &Isometry3F64,
+        uniforms: &'rp VertexShaderUniformBuffers,
+        render_pass: &mut wgpu::RenderPass<'rp>,
+        entities: &mut Vec<(ScenePickKind, String)>,
+    ) {
+        render_pass.set_pipeline(&self.pick_pipeline);
+
+        for (name, mesh) in self.mesh_table.iter() {
+            entities.push((ScenePickKind::TexturedMesh, name.clone()));
+            let entity_id = entities.len() as u32;
+            uniforms
+                .camera_from_entity_pose_buffer
+                .update_given_camera_and_entity(
+                    &render_context.wgpu_queue,
+                    world_from_camera,
+                    &mesh.world_from_entity,
                 );
-            render_pass.set_bind_group(1, &mesh._texture_bind_group, &[]);
-            render_pass.set_vertex_buffer(0, mesh._vertex_buffer.slice(..));
-            render_pass.draw(0..mesh._vertex_data.len() as u32, 0..1);
+            render_pass.set_bind_group(1, &mesh.texture_bind_group, &[]);
+            render_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+            render_pass.draw(0..mesh.vertex_data.len() as u32, entity_id..entity_id + 1);
         }
     }
 }
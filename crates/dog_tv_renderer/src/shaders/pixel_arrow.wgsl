@@ -0,0 +1,61 @@
+@group(0) @binding(1)
+var<uniform> zoom_2d: Zoom2d;
+
+@group(0) @binding(2)
+var<uniform> ortho_camera: PinholeModel;
+
+struct VertexOut {
+    @location(0) color: vec4<f32>,
+    @builtin(position) position: vec4<f32>,
+};
+
+@vertex
+fn vs_main(
+     @location(0) tail: vec2<f32>,
+     @location(1) tip: vec2<f32>,
+     @location(2) color: vec4<f32>,
+     @location(3) line_width: f32,
+     @location(4) head_size: f32,
+     @builtin(vertex_index) idx: u32)-> VertexOut
+{
+    var out: VertexOut;
+
+    var length_in_pixels = length(tip - tail);
+    var dir = vec2<f32>(1.0, 0.0);
+    if length_in_pixels > 1e-6 {
+        dir = (tip - tail) / length_in_pixels;
+    }
+    var normal = vec2<f32>(dir.y, -dir.x);
+
+    var line_half_width = 0.5 * line_width * ortho_camera.viewport_scale;
+    // the head is never longer than the arrow
+    var head_length = min(head_size * ortho_camera.viewport_scale, length_in_pixels);
+    var neck = tip - dir * head_length;
+
+    // vertices 0-5: shaft quad from tail to neck, vertices 6-8: head triangle
+    var p = tip;
+    var mod9 = idx % 9u;
+    if mod9 == 0u || mod9 == 5u {
+        p = tail + normal * line_half_width;
+    } else if mod9 == 1u {
+        p = tail - normal * line_half_width;
+    } else if mod9 == 2u || mod9 == 3u {
+        p = neck - normal * line_half_width;
+    } else if mod9 == 4u {
+        p = neck + normal * line_half_width;
+    } else if mod9 == 6u {
+        p = neck + normal * 0.5 * head_length;
+    } else if mod9 == 7u {
+        p = neck - normal * 0.5 * head_length;
+    }
+
+    out.position = ortho_pixel_and_z_to_clip(p, zoom_2d, ortho_camera);
+    out.color = color;
+
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
+    return in.color;
+}
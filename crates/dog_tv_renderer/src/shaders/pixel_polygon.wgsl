@@ -0,0 +1,28 @@
+@group(0) @binding(1)
+var<uniform> zoom_2d: Zoom2d;
+
+@group(0) @binding(2)
+var<uniform> ortho_camera: PinholeModel;
+
+struct VertexOut {
+    @location(0) color: vec4<f32>,
+    @builtin(position) position: vec4<f32>,
+};
+
+@vertex
+fn vs_main(
+     @location(0) position: vec2<f32>,
+     @location(1) offset: vec2<f32>,
+     @location(2) color: vec4<f32>)-> VertexOut
+{
+    var out: VertexOut;
+    var p = position + offset * ortho_camera.viewport_scale;
+    out.position = ortho_pixel_and_z_to_clip(p, zoom_2d, ortho_camera);
+    out.color = color;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
+    return in.color;
+}
@@ -0,0 +1,101 @@
+@group(0) @binding(1)
+var<uniform> zoom_2d: Zoom2d;
+
+@group(0) @binding(2)
+var<uniform> ortho_camera: PinholeModel;
+
+struct VertexOut {
+    // position relative to the center, in the frame of the shape, in image pixels
+    @location(0) local: vec2<f32>,
+    @location(1) @interpolate(flat) half_size: vec2<f32>,
+    @location(2) @interpolate(flat) line_width: f32,
+    @location(3) @interpolate(flat) color: vec4<f32>,
+    @location(4) @interpolate(flat) fill_color: vec4<f32>,
+    @location(5) @interpolate(flat) kind: u32,
+    @builtin(position) position: vec4<f32>,
+};
+
+@vertex
+fn vs_main(
+     @location(0) center: vec2<f32>,
+     @location(1) half_size: vec2<f32>,
+     @location(2) angle: f32,
+     @location(3) line_width: f32,
+     @location(4) color: vec4<f32>,
+     @location(5) fill_color: vec4<f32>,
+     @location(6) kind: u32,
+     @builtin(vertex_index) idx: u32)-> VertexOut
+{
+    var out: VertexOut;
+
+    // enlarge the quad by half the line width plus one viewport pixel for anti-aliasing
+    var margin = (0.5 * line_width + 1.0) * ortho_camera.viewport_scale;
+    var corner = vec2<f32>(-1.0, -1.0);
+    var mod6 = idx % 6u;
+    if mod6 == 1u || mod6 == 3u {
+        corner = vec2<f32>(1.0, -1.0);
+    } else if mod6 == 2u || mod6 == 4u {
+        corner = vec2<f32>(-1.0, 1.0);
+    } else if mod6 == 5u {
+        corner = vec2<f32>(1.0, 1.0);
+    }
+    var local = corner * (half_size + vec2<f32>(margin, margin));
+
+    var c = cos(angle);
+    var s = sin(angle);
+    var p = center + vec2<f32>(c * local.x - s * local.y, s * local.x + c * local.y);
+
+    out.position = ortho_pixel_and_z_to_clip(p, zoom_2d, ortho_camera);
+    out.local = local;
+    out.half_size = half_size;
+    out.line_width = line_width;
+    out.color = color;
+    out.fill_color = fill_color;
+    out.kind = kind;
+
+    return out;
+}
+
+// signed distance to the ellipse, approximated by the first order Taylor expansion
+fn ellipse_distance(p: vec2<f32>, radii: vec2<f32>) -> f32 {
+    var r = max(radii, vec2<f32>(1e-6, 1e-6));
+    var k = length(p / r);
+    var grad = length(p / (r * r));
+    if grad < 1e-6 {
+        return -min(r.x, r.y);
+    }
+    return k * (k - 1.0) / grad;
+}
+
+fn box_distance(p: vec2<f32>, half_size: vec2<f32>) -> f32 {
+    var q = abs(p) - half_size;
+    return length(max(q, vec2<f32>(0.0, 0.0))) + min(max(q.x, q.y), 0.0);
+}
+
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
+    var d = 0.0;
+    if in.kind == 0u {
+        d = ellipse_distance(in.local, in.half_size);
+    } else {
+        d = box_distance(in.local, in.half_size);
+    }
+
+    // one viewport pixel, in image pixels
+    var aa = ortho_camera.viewport_scale;
+    var outline_alpha = 0.0;
+    if in.line_width > 0.0 {
+        var half_width = 0.5 * in.line_width * aa;
+        outline_alpha = in.color.a * clamp(0.5 - (abs(d) - half_width) / aa, 0.0, 1.0);
+    }
+    var fill_alpha = in.fill_color.a * clamp(0.5 - d / aa, 0.0, 1.0);
+
+    // outline over fill
+    var alpha = outline_alpha + fill_alpha * (1.0 - outline_alpha);
+    if alpha <= 0.0 {
+        discard;
+    }
+    var rgb = (in.color.rgb * outline_alpha + in.fill_color.rgb * fill_alpha * (1.0 - outline_alpha))
+        / alpha;
+    return vec4<f32>(rgb, alpha);
+}
@@ -0,0 +1,39 @@
+@group(0) @binding(1)
+var<uniform> zoom_2d: Zoom2d;
+
+@group(0) @binding(2)
+var<uniform> ortho_camera: PinholeModel;
+
+@group(1) @binding(0)
+var glyph_atlas: texture_2d<f32>;
+
+@group(1) @binding(1)
+var glyph_atlas_sampler: sampler;
+
+struct VertexOut {
+    @location(0) tex: vec2<f32>,
+    @location(1) color: vec4<f32>,
+    @builtin(position) position: vec4<f32>,
+};
+
+@vertex
+fn vs_main(
+     @location(0) position: vec2<f32>,
+     @location(1) offset: vec2<f32>,
+     @location(2) tex: vec2<f32>,
+     @location(3) color: vec4<f32>)-> VertexOut
+{
+    var out: VertexOut;
+    let uv = position + offset * ortho_camera.viewport_scale;
+    out.position = ortho_pixel_and_z_to_clip(uv, zoom_2d, ortho_camera);
+    out.tex = tex;
+    out.color = color;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
+    let atlas_size = vec2<f32>(textureDimensions(glyph_atlas));
+    let coverage = textureSample(glyph_atlas, glyph_atlas_sampler, in.tex / atlas_size).r;
+    return vec4<f32>(in.color.rgb, in.color.a * coverage);
+}
@@ -0,0 +1,70 @@
+@group(0) @binding(0)
+var<uniform> camera: CameraProperties;
+@group(0) @binding(1)
+var<uniform> zoom: Zoom2d;
+@group(0) @binding(2)
+var<uniform> pinhole: PinholeModel;
+
+struct Background {
+    camera_from_world: mat4x4<f32>,
+    top: vec4<f32>,
+    bottom: vec4<f32>,
+    // 0: vertical gradient, 1: environment image
+    mode: u32,
+    padding0: u32,
+    padding1: u32,
+    padding2: u32,
+};
+
+@group(1) @binding(0)
+var<uniform> background: Background;
+@group(1) @binding(1)
+var environment_texture: texture_2d<f32>;
+@group(1) @binding(2)
+var environment_sampler: sampler;
+
+struct VertexOut {
+    // viewing ray through the fragment, in the camera frame and scaled to z = 1
+    @location(0) ray: vec3<f32>,
+    // 0 at the top and 1 at the bottom of the viewport
+    @location(1) height: f32,
+    @builtin(position) position: vec4<f32>,
+};
+
+// A single triangle covering the whole viewport.
+@vertex
+fn vs_main(@builtin(vertex_index) idx: u32) -> VertexOut {
+    let clip = vec2<f32>(select(-1.0, 3.0, idx == 1u), select(-1.0, 3.0, idx == 2u));
+
+    // inverse of pixel_and_z_to_clip
+    let u = ((0.5 * clip.x + 0.5) * camera.camera_image_width - 0.5 - zoom.translation_x)
+        / zoom.scaling_x;
+    let v = ((0.5 - 0.5 * clip.y) * camera.camera_image_height - 0.5 - zoom.translation_y)
+        / zoom.scaling_y;
+
+    var out: VertexOut;
+    out.position = vec4<f32>(clip, 0.0, 1.0);
+    out.ray = vec3<f32>((u - pinhole.px) / pinhole.fx, (v - pinhole.py) / pinhole.fy, 1.0);
+    out.height = 0.5 - 0.5 * clip.y;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
+    if background.mode == 0u {
+        return mix(background.top, background.bottom, clamp(in.height, 0.0, 1.0));
+    }
+
+    let rotation = mat3x3<f32>(
+        background.camera_from_world[0].xyz,
+        background.camera_from_world[1].xyz,
+        background.camera_from_world[2].xyz,
+    );
+    let d = transpose(rotation) * in.ray;
+    let pi = 3.14159265359;
+    let longitude = atan2(d.y, d.x);
+    let latitude = atan2(d.z, length(d.xy));
+    let uv = vec2<f32>(0.5 - longitude / (2.0 * pi), 0.5 - latitude / pi);
+    // explicit level, since the longitude wraps around
+    return textureSampleLevel(environment_texture, environment_sampler, uv, 0.0);
+}
@@ -0,0 +1,102 @@
+@group(0) @binding(0)
+var<uniform> camera: CameraProperties;
+@group(0) @binding(1)
+var<uniform> zoom: Zoom2d;
+@group(0) @binding(2)
+var<uniform> pinhole: PinholeModel;
+
+struct GroundGrid {
+    camera_from_world: mat4x4<f32>,
+    // plane normal and the two in-plane axes, in the world frame
+    normal: vec4<f32>,
+    axis_u: vec4<f32>,
+    axis_v: vec4<f32>,
+    spacing: f32,
+    fade_distance: f32,
+    padding0: f32,
+    padding1: f32,
+};
+
+@group(1) @binding(0)
+var<uniform> grid: GroundGrid;
+
+struct VertexOut {
+    // viewing ray through the fragment, in the camera frame and scaled to z = 1
+    @location(0) ray: vec3<f32>,
+    @location(1) @interpolate(flat) near_far: vec2<f32>,
+    @builtin(position) position: vec4<f32>,
+};
+
+struct FragmentOut {
+    @location(0) rgba: vec4<f32>,
+    @builtin(frag_depth) depth: f32,
+};
+
+// A single triangle covering the whole viewport, the grid is ray cast per fragment.
+@vertex
+fn vs_main(@builtin(vertex_index) idx: u32) -> VertexOut {
+    let clip = vec2<f32>(select(-1.0, 3.0, idx == 1u), select(-1.0, 3.0, idx == 2u));
+
+    // inverse of pixel_and_z_to_clip
+    let u = ((0.5 * clip.x + 0.5) * camera.camera_image_width - 0.5 - zoom.translation_x)
+        / zoom.scaling_x;
+    let v = ((0.5 - 0.5 * clip.y) * camera.camera_image_height - 0.5 - zoom.translation_y)
+        / zoom.scaling_y;
+
+    var out: VertexOut;
+    out.position = vec4<f32>(clip, 0.0, 1.0);
+    out.ray = vec3<f32>((u - pinhole.px) / pinhole.fx, (v - pinhole.py) / pinhole.fy, 1.0);
+    out.near_far = vec2<f32>(camera.near, camera.far);
+    return out;
+}
+
+// coverage of the lines at integer coordinates, anti-aliased over about one pixel
+fn grid_lines(coord: vec2<f32>, width: vec2<f32>) -> f32 {
+    let distance = abs(fract(coord - 0.5) - 0.5) / width;
+    return 1.0 - min(min(distance.x, distance.y), 1.0);
+}
+
+@fragment
+fn fs_main(in: VertexOut) -> FragmentOut {
+    let rotation = mat3x3<f32>(
+        grid.camera_from_world[0].xyz,
+        grid.camera_from_world[1].xyz,
+        grid.camera_from_world[2].xyz,
+    );
+    let world_from_camera_rotation = transpose(rotation);
+    // world origin and plane normal in the camera frame
+    let origin = grid.camera_from_world[3].xyz;
+    let normal = rotation * grid.normal.xyz;
+
+    // intersection with the plane, the ray parameter is the depth since the ray has z = 1
+    let z = dot(normal, origin) / dot(normal, in.ray);
+    let point_in_world = world_from_camera_rotation * (z * in.ray - origin);
+    let camera_in_world = -(world_from_camera_rotation * origin);
+    let uv = vec2<f32>(dot(point_in_world, grid.axis_u.xyz), dot(point_in_world, grid.axis_v.xyz));
+    let camera_uv = vec2<f32>(
+        dot(camera_in_world, grid.axis_u.xyz),
+        dot(camera_in_world, grid.axis_v.xyz),
+    );
+
+    let coord = uv / grid.spacing;
+    let width = fwidth(coord);
+    let minor = grid_lines(coord, width);
+    let major = grid_lines(0.1 * coord, 0.1 * width);
+    // minor lines fade out once the cells are only a few pixels wide
+    let cell_pixels = 1.0 / max(width.x, width.y);
+    let minor_visibility = smoothstep(2.0, 8.0, cell_pixels);
+    let fade = 1.0 - smoothstep(0.5 * grid.fade_distance, grid.fade_distance, distance(uv, camera_uv));
+    let alpha = max(0.35 * minor * minor_visibility, 0.7 * major) * fade;
+
+    let near = in.near_far.x;
+    let far = in.near_far.y;
+    // also discards rays parallel to the plane, for which z is not finite
+    if !(z > near && z < far) || alpha <= 0.0 {
+        discard;
+    }
+
+    var out: FragmentOut;
+    out.rgba = vec4<f32>(0.6, 0.6, 0.6, alpha);
+    out.depth = (far / (far - near)) * (1.0 - near / z);
+    return out;
+}
@@ -0,0 +1,60 @@
+@group(0) @binding(0)
+var<uniform> camera: CameraProperties;
+@group(0) @binding(1)
+var<uniform> zoom: Zoom2d;
+@group(0) @binding(2)
+var<uniform> pinhole: PinholeModel;
+@group(0) @binding(3)
+var<uniform> view_uniform: CameraPose;
+// entity id in x, only set for the pick pass
+@group(1) @binding(0)
+var<uniform> pick_entity: vec4<u32>;
+@group(0) @binding(4)
+var<uniform> lighting: Lighting;
+
+struct VertexOut {
+    @location(0) rgba: vec4<f32>,
+    @location(1) normal_in_cam: vec3<f32>,
+    @location(2) point_in_cam: vec3<f32>,
+    @location(3) @interpolate(flat) pick_id: vec2<u32>,
+    @builtin(position) position: vec4<f32>,
+};
+
+
+@vertex
+fn vs_main(
+     @location(0) position: vec3<f32>,
+     @location(1) color: vec4<f32>,
+     @location(2) normal: vec3<f32>,
+     @location(8) scaled_rotation_x: vec3<f32>,
+     @location(9) scaled_rotation_y: vec3<f32>,
+     @location(10) scaled_rotation_z: vec3<f32>,
+     @location(11) translation: vec3<f32>,
+     @location(12) instance_color: vec4<f32>,
+     @location(13) instance: u32)-> VertexOut
+{
+    let entity_from_instance = mat3x3<f32>(scaled_rotation_x, scaled_rotation_y, scaled_rotation_z);
+    let point_in_entity = entity_from_instance * position + translation;
+    // the scale is uniform, hence the normal is only rotated
+    let normal_in_entity = normalize(entity_from_instance * normal);
+
+    let projection = project_point(point_in_entity, view_uniform, pinhole, camera, zoom);
+    var out: VertexOut;
+    out.position = pixel_and_z_to_clip(projection.uv_undistorted, projection.z, camera, zoom);
+    out.rgba = color * instance_color;
+    out.normal_in_cam = (view_uniform.camera_from_entity * vec4<f32>(normal_in_entity, 0.0)).xyz;
+    out.point_in_cam = (view_uniform.camera_from_entity * vec4<f32>(point_in_entity, 1.0)).xyz;
+    out.pick_id = vec2<u32>(pick_entity.x, instance);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
+    return shade(in.rgba, in.normal_in_cam, in.point_in_cam, lighting);
+}
+
+// entity id (0 for background) and instance index, written by the pick pass
+@fragment
+fn fs_pick(in: VertexOut) -> PickOut {
+    return pick_with_normal(in.pick_id, in.normal_in_cam, in.point_in_cam);
+}
@@ -10,59 +10,78 @@ var<uniform> view_uniform: CameraPose;
 
 struct VertexOut {
     @location(0) rgba: vec4<f32>,
+    @location(1) @interpolate(flat) pick_id: vec2<u32>,
+    // pixel position along and across the segment, relative to its start point
+    @location(2) @interpolate(linear) local: vec2<f32>,
+    // segment length and half the line width, in pixels
+    @location(3) @interpolate(flat) length_and_half_width: vec2<f32>,
     @builtin(position) position: vec4<f32>,
 };
 
 
+// Each segment is a quad, extended by half the line width beyond both end points. The fragment
+// shader cuts round caps out of the extension, which also join consecutive segments smoothly.
 @vertex
 fn vs_main(
      @location(0) p0: vec3<f32>,
      @location(1) p1: vec3<f32>,
-     @location(2) color: vec4<f32>,
+     @location(2) color0: vec4<f32>,
      @location(3) line_width: f32,
-     @builtin(vertex_index) idx: u32)-> VertexOut
+     @location(4) color1: vec4<f32>,
+     @builtin(vertex_index) idx: u32,
+     @builtin(instance_index) entity_id: u32)-> VertexOut
 {
     let projection0 = project_point(p0, view_uniform, pinhole, camera, zoom);
     let projection1 = project_point(p1, view_uniform, pinhole, camera, zoom);
-    let depth0 = projection0.z;
-    let depth1 = projection1.z;
     let uv0 = projection0.uv_undistorted;
     let uv1 = projection1.uv_undistorted;
 
-    var d = normalize(uv0 - uv1);
-    var n = vec2<f32>(-d.y, d.x);
-    var line_half_width = 0.5 * line_width;
-    var uv = vec2<f32>(0.0, 0.0);
-    var z = 0.0;
-    var mod6 = idx % 6u;
-    if mod6 == 0u {
-        uv = uv0 + line_half_width * n;
-        z = depth0;
-    } else if mod6 == 1u {
-        uv = uv0 - line_half_width * n;
-        z = depth0;
-    } else if mod6 == 2u {
-        uv = uv1 + line_half_width * n;
-        z = depth1;
-    } else if mod6 == 3u {
-        uv = uv1 - line_half_width * n;
-        z = depth1;
-    }
-    else if mod6 == 4u {
-        uv = uv1 + line_half_width * n;
-        z = depth1;
-    } else if mod6 == 5u {
-        uv = uv0 - line_half_width * n;
-        z = depth0;
+    let segment_length = length(uv1 - uv0);
+    var d = vec2<f32>(1.0, 0.0);
+    if segment_length > 1e-6 {
+        d = (uv1 - uv0) / segment_length;
     }
+    let n = vec2<f32>(-d.y, d.x);
+    let line_half_width = 0.5 * line_width;
+
+    let mod6 = idx % 6u;
+    let at_p1 = mod6 == 2u || mod6 == 3u || mod6 == 4u;
+    let side = select(-1.0, 1.0, mod6 == 0u || mod6 == 2u || mod6 == 4u);
+    let along = select(-line_half_width, segment_length + line_half_width, at_p1);
+    let across = side * line_half_width;
 
     var out: VertexOut;
-    out.position = pixel_and_z_to_clip(uv, z, camera, zoom);
-    out.rgba = color;
+    out.position = pixel_and_z_to_clip(
+        uv0 + along * d + across * n,
+        select(projection0.z, projection1.z, at_p1),
+        camera,
+        zoom,
+    );
+    out.rgba = select(color0, color1, at_p1);
+    out.pick_id = vec2<u32>(entity_id, idx / 6u);
+    out.local = vec2<f32>(along, across);
+    out.length_and_half_width = vec2<f32>(segment_length, line_half_width);
     return out;
 }
 
+fn is_inside_line(in: VertexOut) -> bool {
+    let along = in.local.x - clamp(in.local.x, 0.0, in.length_and_half_width.x);
+    return length(vec2<f32>(along, in.local.y)) <= in.length_and_half_width.y;
+}
+
 @fragment
 fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
+    if !is_inside_line(in) {
+        discard;
+    }
     return in.rgba;
 }
+
+// entity id, primitive index and surface normal, written by the pick pass
+@fragment
+fn fs_pick(in: VertexOut) -> PickOut {
+    if !is_inside_line(in) {
+        discard;
+    }
+    return pick_without_normal(in.pick_id);
+}
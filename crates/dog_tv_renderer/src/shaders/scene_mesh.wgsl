@@ -6,9 +6,14 @@ var<uniform> zoom: Zoom2d;
 var<uniform> pinhole: PinholeModel;
 @group(0) @binding(3)
 var<uniform> view_uniform: CameraPose;
+@group(0) @binding(4)
+var<uniform> lighting: Lighting;
 
 struct VertexOut {
     @location(0) rgba: vec4<f32>,
+    @location(1) normal_in_cam: vec3<f32>,
+    @location(2) point_in_cam: vec3<f32>,
+    @location(3) @interpolate(flat) pick_id: vec2<u32>,
     @builtin(position) position: vec4<f32>,
 };
 
@@ -16,16 +21,28 @@ struct VertexOut {
 @vertex
 fn vs_main(
      @location(0) position: vec3<f32>,
-     @location(1) color: vec4<f32>)-> VertexOut
+     @location(1) color: vec4<f32>,
+     @location(2) normal: vec3<f32>,
+     @builtin(vertex_index) idx: u32,
+     @builtin(instance_index) entity_id: u32)-> VertexOut
 {
     let projection = project_point(position, view_uniform, pinhole, camera, zoom);
     var out: VertexOut;
     out.position = pixel_and_z_to_clip(projection.uv_undistorted, projection.z, camera, zoom);
     out.rgba = color;
+    out.normal_in_cam = (view_uniform.camera_from_entity * vec4<f32>(normal, 0.0)).xyz;
+    out.point_in_cam = (view_uniform.camera_from_entity * vec4<f32>(position, 1.0)).xyz;
+    out.pick_id = vec2<u32>(entity_id, idx / 3u);
     return out;
 }
 
 @fragment
 fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
-    return in.rgba;
+    return shade(in.rgba, in.normal_in_cam, in.point_in_cam, lighting);
+}
+
+// entity id, primitive index and surface normal, written by the pick pass
+@fragment
+fn fs_pick(in: VertexOut) -> PickOut {
+    return pick_with_normal(in.pick_id, in.normal_in_cam, in.point_in_cam);
 }
@@ -10,6 +10,7 @@ var<uniform> view_uniform: CameraPose;
 
 struct VertexOut {
     @location(0) rgbd: vec4<f32>,
+    @location(1) @interpolate(flat) pick_id: vec2<u32>,
     @builtin(position) position: vec4<f32>,
 };
 
@@ -18,7 +19,8 @@ fn vs_main(
      @location(0) position: vec3<f32>,
      @location(1) point_size: f32,
      @location(2) color: vec4<f32>,
-     @builtin(vertex_index) idx: u32)-> VertexOut
+     @builtin(vertex_index) idx: u32,
+     @builtin(instance_index) entity_id: u32)-> VertexOut
 {
     let projection = project_point(position, view_uniform, pinhole, camera, zoom);
     var u = projection.uv_undistorted.x;
@@ -42,6 +44,7 @@ fn vs_main(
     var out: VertexOut;
     out.position = pixel_and_z_to_clip(vec2<f32>(u, v), projection.z, camera, zoom);
     out.rgbd = color;
+    out.pick_id = vec2<u32>(entity_id, idx / 6u);
     return out;
 }
 
@@ -49,3 +52,9 @@ fn vs_main(
 fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
     return in.rgbd;
 }
+
+// entity id, primitive index and surface normal, written by the pick pass
+@fragment
+fn fs_pick(in: VertexOut) -> PickOut {
+    return pick_without_normal(in.pick_id);
+}
@@ -0,0 +1,61 @@
+@group(0) @binding(0)
+var<uniform> camera: CameraProperties;
+@group(0) @binding(1)
+var<uniform> zoom: Zoom2d;
+@group(0) @binding(2)
+var<uniform> pinhole: PinholeModel;
+@group(0) @binding(3)
+var<uniform> view_uniform: CameraPose;
+
+
+struct VertexOut {
+    @location(0) rgbd: vec4<f32>,
+    @location(1) @interpolate(flat) pick_id: vec2<u32>,
+    @builtin(position) position: vec4<f32>,
+};
+
+// One instance per point. The pick pass starts the six quad vertices at six times the entity id.
+@vertex
+fn vs_main(
+     @location(0) position: vec3<f32>,
+     @location(1) point_size: f32,
+     @location(2) color: vec4<f32>,
+     @location(3) point_index: u32,
+     @builtin(vertex_index) idx: u32)-> VertexOut
+{
+    let projection = project_point(position, view_uniform, pinhole, camera, zoom);
+    var u = projection.uv_undistorted.x;
+    var v = projection.uv_undistorted.y;
+
+    var point_radius = 0.5 * point_size;
+    var mod6 = idx % 6u;
+    if mod6 == 0u {
+        u -= point_radius;
+        v -= point_radius;
+    } else if mod6 == 1u || mod6 == 3u {
+        u += point_radius;
+        v -= point_radius;
+    } else if mod6 == 2u || mod6 == 4u {
+        u -= point_radius;
+        v += point_radius;
+    } else if mod6 == 5u {
+        u += point_radius;
+        v += point_radius;
+    }
+    var out: VertexOut;
+    out.position = pixel_and_z_to_clip(vec2<f32>(u, v), projection.z, camera, zoom);
+    out.rgbd = color;
+    out.pick_id = vec2<u32>(idx / 6u, point_index);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
+    return in.rgbd;
+}
+
+// entity id, primitive index and surface normal, written by the pick pass
+@fragment
+fn fs_pick(in: VertexOut) -> PickOut {
+    return pick_without_normal(in.pick_id);
+}
@@ -0,0 +1,131 @@
+@group(0) @binding(0)
+var<uniform> camera: CameraProperties;
+@group(0) @binding(1)
+var<uniform> zoom: Zoom2d;
+@group(0) @binding(2)
+var<uniform> pinhole: PinholeModel;
+@group(0) @binding(3)
+var<uniform> view_uniform: CameraPose;
+
+struct ScalarColoring {
+    range_min: f32,
+    range_max: f32,
+    // see `Colormap`
+    colormap: u32,
+    padding: u32,
+};
+
+@group(1) @binding(0)
+var<uniform> coloring: ScalarColoring;
+
+struct VertexOut {
+    @location(0) rgbd: vec4<f32>,
+    @location(1) @interpolate(flat) pick_id: vec2<u32>,
+    @builtin(position) position: vec4<f32>,
+};
+
+fn blue_white_red_black(t: f32) -> vec3<f32> {
+    if t < 1.0 / 3.0 {
+        let s = 3.0 * t;
+        return vec3<f32>(s, s, 1.0);
+    } else if t < 2.0 / 3.0 {
+        let s = 3.0 * t - 1.0;
+        return vec3<f32>(1.0, 1.0 - s, 1.0 - s);
+    }
+    let s = 3.0 * t - 2.0;
+    return vec3<f32>(1.0 - s, 0.0, 0.0);
+}
+
+// polynomial fit of matplotlib's viridis
+fn viridis(t: f32) -> vec3<f32> {
+    let c0 = vec3<f32>(0.2777273272234177, 0.005407344544966578, 0.3340998053353061);
+    let c1 = vec3<f32>(0.1050930431085774, 1.404613529898575, 1.384590162594685);
+    let c2 = vec3<f32>(-0.3308618287255563, 0.214847559468213, 0.09509516302823659);
+    let c3 = vec3<f32>(-4.634230498983486, -5.799100973351585, -19.33244095627987);
+    let c4 = vec3<f32>(6.228269936347081, 14.17993336680509, 56.69055260068105);
+    let c5 = vec3<f32>(4.776384997670288, -13.74514537774601, -65.35303263337234);
+    let c6 = vec3<f32>(-5.435455855934631, 4.645852612178535, 26.3124352495832);
+    return c0 + t * (c1 + t * (c2 + t * (c3 + t * (c4 + t * (c5 + t * c6)))));
+}
+
+// polynomial fit of Google's turbo
+fn turbo(t: f32) -> vec3<f32> {
+    let v4 = vec4<f32>(1.0, t, t * t, t * t * t);
+    let v2 = v4.zw * v4.z;
+    return vec3<f32>(
+        dot(v4, vec4<f32>(0.13572138, 4.61539260, -42.66032258, 132.13108234))
+            + dot(v2, vec2<f32>(-152.94239396, 59.28637943)),
+        dot(v4, vec4<f32>(0.09140261, 2.19418839, 4.84296658, -14.18503333))
+            + dot(v2, vec2<f32>(4.27729857, 2.82956604)),
+        dot(v4, vec4<f32>(0.10667330, 12.64194608, -60.58204836, 110.36276771))
+            + dot(v2, vec2<f32>(-89.90310912, 27.34824973)),
+    );
+}
+
+fn apply_colormap(value: f32) -> vec3<f32> {
+    let extent = coloring.range_max - coloring.range_min;
+    var t = 0.5;
+    if extent != 0.0 {
+        t = clamp((value - coloring.range_min) / extent, 0.0, 1.0);
+    }
+    switch coloring.colormap {
+        case 1u: {
+            return vec3<f32>(t, t, t);
+        }
+        case 2u: {
+            return clamp(viridis(t), vec3<f32>(0.0), vec3<f32>(1.0));
+        }
+        case 3u: {
+            return clamp(turbo(t), vec3<f32>(0.0), vec3<f32>(1.0));
+        }
+        default: {
+            return blue_white_red_black(t);
+        }
+    }
+}
+
+// One instance per point. The pick pass starts the six quad vertices at six times the entity id.
+@vertex
+fn vs_main(
+     @location(0) position: vec3<f32>,
+     @location(1) point_size: f32,
+     @location(2) value: f32,
+     @builtin(vertex_index) idx: u32,
+     @builtin(instance_index) point_index: u32)-> VertexOut
+{
+    let projection = project_point(position, view_uniform, pinhole, camera, zoom);
+    var u = projection.uv_undistorted.x;
+    var v = projection.uv_undistorted.y;
+
+    var point_radius = 0.5 * point_size;
+    var mod6 = idx % 6u;
+    if mod6 == 0u {
+        u -= point_radius;
+        v -= point_radius;
+    } else if mod6 == 1u || mod6 == 3u {
+        u += point_radius;
+        v -= point_radius;
+    } else if mod6 == 2u || mod6 == 4u {
+        u -= point_radius;
+        v += point_radius;
+    } else if mod6 == 5u {
+        u += point_radius;
+        v += point_radius;
+    }
+    var out: VertexOut;
+    out.position = pixel_and_z_to_clip(vec2<f32>(u, v), projection.z, camera, zoom);
+    out.rgbd = vec4<f32>(apply_colormap(value), 1.0);
+    out.pick_id = vec2<u32>(idx / 6u, point_index);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
+    return in.rgbd;
+}
+
+// entity id, primitive index and surface normal, written by the pick pass
+@fragment
+fn fs_pick(in: VertexOut) -> PickOut {
+    return pick_without_normal(in.pick_id);
+}
@@ -0,0 +1,43 @@
+@group(0) @binding(0)
+var<uniform> camera: CameraProperties;
+@group(0) @binding(1)
+var<uniform> zoom: Zoom2d;
+@group(0) @binding(2)
+var<uniform> pinhole: PinholeModel;
+@group(0) @binding(3)
+var<uniform> view_uniform: CameraPose;
+
+@group(1) @binding(0)
+var glyph_atlas: texture_2d<f32>;
+
+@group(1) @binding(1)
+var glyph_atlas_sampler: sampler;
+
+struct VertexOut {
+    @location(0) tex: vec2<f32>,
+    @location(1) color: vec4<f32>,
+    @builtin(position) position: vec4<f32>,
+};
+
+@vertex
+fn vs_main(
+     @location(0) position: vec3<f32>,
+     @location(1) offset: vec2<f32>,
+     @location(2) tex: vec2<f32>,
+     @location(3) color: vec4<f32>)-> VertexOut
+{
+    // billboard: the glyph quad is offset in pixels from the projected anchor point
+    let projection = project_point(position, view_uniform, pinhole, camera, zoom);
+    var out: VertexOut;
+    out.position = pixel_and_z_to_clip(projection.uv_undistorted + offset, projection.z, camera, zoom);
+    out.tex = tex;
+    out.color = color;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
+    let atlas_size = vec2<f32>(textureDimensions(glyph_atlas));
+    let coverage = textureSample(glyph_atlas, glyph_atlas_sampler, in.tex / atlas_size).r;
+    return vec4<f32>(in.color.rgb, in.color.a * coverage);
+}
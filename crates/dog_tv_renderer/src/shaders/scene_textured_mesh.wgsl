@@ -10,6 +10,8 @@ var<uniform> view_uniform: CameraPose;
 
 struct VertexOut {
     @location(0) texCoords: vec2<f32>,
+    @location(1) @interpolate(flat) pick_id: vec2<u32>,
+    @location(2) point_in_cam: vec3<f32>,
     @builtin(position) position: vec4<f32>,
 };
 
@@ -25,13 +27,17 @@ var mesh_texture_sampler: sampler;
 @vertex
 fn vs_main(
     @location(0) position: vec3<f32>,
-    @location(1) tex_coords: vec2<f32>
+    @location(1) tex_coords: vec2<f32>,
+    @builtin(vertex_index) idx: u32,
+    @builtin(instance_index) entity_id: u32
 ) -> VertexOut {
     let projection = project_point(position, view_uniform, pinhole, camera, zoom);
 
     var out: VertexOut;
     out.position = pixel_and_z_to_clip(projection.uv_undistorted, projection.z, camera, zoom);
     out.texCoords = tex_coords;
+    out.pick_id = vec2<u32>(entity_id, idx / 3u);
+    out.point_in_cam = (view_uniform.camera_from_entity * vec4<f32>(position, 1.0)).xyz;
     return out;
 }
 
@@ -39,3 +45,11 @@ fn vs_main(
 fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
     return textureSample(mesh_texture, mesh_texture_sampler, in.texCoords);
 }
+
+// entity id, primitive index and surface normal, written by the pick pass
+@fragment
+fn fs_pick(in: VertexOut) -> PickOut {
+    // flat normal of the triangle
+    let normal_in_cam = cross(dpdx(in.point_in_cam), dpdy(in.point_in_cam));
+    return pick_with_normal(in.pick_id, normal_in_cam, in.point_in_cam);
+}
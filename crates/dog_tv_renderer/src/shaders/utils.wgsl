@@ -7,8 +7,16 @@ struct CameraProperties {
     fy: f32,
     px: f32,
     py: f32,
-    alpha: f32,
-    beta: f32,
+    // 0: pinhole, 1: unified extended, 2: kannala-brandt, 3: brown-conrady
+    model: u32,
+    padding0: u32,
+    padding1: u32,
+    padding2: u32,
+    // unified extended: alpha, beta
+    // kannala-brandt: k0, k1, k2, k3
+    distortion0: vec4<f32>,
+    // brown-conrady: k1, k2, p1, p2 (distortion0), k3, k4, k5, k6 (distortion1)
+    distortion1: vec4<f32>,
 };
 
 struct Zoom2d {
@@ -22,6 +30,16 @@ struct CameraPose {
     camera_from_entity: mat4x4<f32>,
 };
 
+struct Lighting {
+    // direction the light travels in, in the camera frame
+    direction: vec4<f32>,
+    // 0: unlit, 1: lambertian, 2: phong
+    shading: u32,
+    ambient: f32,
+    specular: f32,
+    shininess: f32,
+};
+
 struct PinholeModel {
      width: f32,
      height: f32,
@@ -57,25 +75,68 @@ fn z1_plane_to_undistorted(point_in_z1: vec2<f32>, pinhole: PinholeModel) -> vec
     return vec2<f32>(u, v);
 }
 
-fn z1_plane_to_distorted(point_in_z1: vec2<f32>, camera: CameraProperties) -> vec2<f32> {
-    let fx = camera.fx;
-    let fy = camera.fy;
-    let px = camera.px;
-    let py = camera.py;
-    var u = point_in_z1.x;
-    var v = point_in_z1.y;
-    let alpha = camera.alpha;
-    let beta = camera.beta;
+fn unified_z1_plane_to_distorted(point_in_z1: vec2<f32>, camera: CameraProperties) -> vec2<f32> {
+    let u = point_in_z1.x;
+    let v = point_in_z1.y;
+    let alpha = camera.distortion0.x;
+    let beta = camera.distortion0.y;
     let r2 = u*u + v*v;
     let rho2 = beta * r2 + 1.0;
     let rho = sqrt(rho2);
 
     let norm = alpha * rho + (1.0 - alpha);
 
-    let mx = u / norm;
-    let my = v / norm;
+    return vec2<f32>(u / norm, v / norm);
+}
+
+fn kannala_brandt_z1_plane_to_distorted(point_in_z1: vec2<f32>, camera: CameraProperties) -> vec2<f32> {
+    let k = camera.distortion0;
+    let r = length(point_in_z1);
+    if (r < 1e-8) {
+        return point_in_z1;
+    }
+    let theta = atan(r);
+    let theta2 = theta * theta;
+    let theta_d = theta * (1.0 + theta2 * (k.x + theta2 * (k.y + theta2 * (k.z + theta2 * k.w))));
+
+    return point_in_z1 * (theta_d / r);
+}
+
+fn brown_conrady_z1_plane_to_distorted(point_in_z1: vec2<f32>, camera: CameraProperties) -> vec2<f32> {
+    let k1 = camera.distortion0.x;
+    let k2 = camera.distortion0.y;
+    let p1 = camera.distortion0.z;
+    let p2 = camera.distortion0.w;
+    let k3 = camera.distortion1.x;
+    let k4 = camera.distortion1.y;
+    let k5 = camera.distortion1.z;
+    let k6 = camera.distortion1.w;
+
+    let x = point_in_z1.x;
+    let y = point_in_z1.y;
+    let r2 = x*x + y*y;
+    let r4 = r2 * r2;
+    let r6 = r4 * r2;
+
+    let radial = (1.0 + k1 * r2 + k2 * r4 + k3 * r6) / (1.0 + k4 * r2 + k5 * r4 + k6 * r6);
+
+    return vec2<f32>(
+        x * radial + 2.0 * p1 * x * y + p2 * (r2 + 2.0 * x * x),
+        y * radial + p1 * (r2 + 2.0 * y * y) + 2.0 * p2 * x * y
+    );
+}
+
+fn z1_plane_to_distorted(point_in_z1: vec2<f32>, camera: CameraProperties) -> vec2<f32> {
+    var m = point_in_z1;
+    if (camera.model == 1u) {
+        m = unified_z1_plane_to_distorted(point_in_z1, camera);
+    } else if (camera.model == 2u) {
+        m = kannala_brandt_z1_plane_to_distorted(point_in_z1, camera);
+    } else if (camera.model == 3u) {
+        m = brown_conrady_z1_plane_to_distorted(point_in_z1, camera);
+    }
 
-    return vec2<f32>(fx * mx + px, fy * my + py);
+    return vec2<f32>(camera.fx * m.x + camera.px, camera.fy * m.y + camera.py);
 }
 
 struct Projection {
@@ -99,19 +160,84 @@ fn project_point(
    return out;
 }
 
-fn distorted_to_z1(uv_distorted: vec2<f32>, camera: CameraProperties) -> vec2<f32> {
-    let u = (uv_distorted.x-camera.px)/camera.fx;
-    let v = (uv_distorted.y-camera.py)/camera.fy;
-
-    let r2 = u*u + v*v;
-    let gamma = 1.0 - camera.alpha;
+fn unified_distorted_to_z1(m: vec2<f32>, camera: CameraProperties) -> vec2<f32> {
+    let alpha = camera.distortion0.x;
+    let beta = camera.distortion0.y;
+    let r2 = m.x*m.x + m.y*m.y;
+    let gamma = 1.0 - alpha;
 
-    let nominator = 1.0 - camera.alpha * camera.alpha * camera.beta * r2;
-    let denominator = camera.alpha * sqrt(1.0 - (camera.alpha - gamma) * camera.beta * r2) + gamma;
+    let nominator = 1.0 - alpha * alpha * beta * r2;
+    let denominator = alpha * sqrt(1.0 - (alpha - gamma) * beta * r2) + gamma;
 
     let k = nominator / denominator;
 
-    return vec2<f32>(u / k, v / k);
+    return vec2<f32>(m.x / k, m.y / k);
+}
+
+fn kannala_brandt_distorted_to_z1(m: vec2<f32>, camera: CameraProperties) -> vec2<f32> {
+    let k = camera.distortion0;
+    let theta_d = length(m);
+    if (theta_d < 1e-8) {
+        return m;
+    }
+
+    // Newton iterations to solve theta_d = theta * (1 + k0 theta^2 + ... + k3 theta^8) for theta
+    var theta = theta_d;
+    for (var i = 0; i < 10; i++) {
+        let theta2 = theta * theta;
+        let f = theta * (1.0 + theta2 * (k.x + theta2 * (k.y + theta2 * (k.z + theta2 * k.w))))
+            - theta_d;
+        let df = 1.0
+            + theta2 * (3.0 * k.x + theta2 * (5.0 * k.y + theta2 * (7.0 * k.z + theta2 * 9.0 * k.w)));
+        theta = theta - f / df;
+    }
+    // points beyond 90 degrees cannot be represented on the z=1 plane
+    theta = clamp(theta, 0.0, 1.55);
+
+    return m * (tan(theta) / theta_d);
+}
+
+fn brown_conrady_distorted_to_z1(m: vec2<f32>, camera: CameraProperties) -> vec2<f32> {
+    let k1 = camera.distortion0.x;
+    let k2 = camera.distortion0.y;
+    let p1 = camera.distortion0.z;
+    let p2 = camera.distortion0.w;
+    let k3 = camera.distortion1.x;
+    let k4 = camera.distortion1.y;
+    let k5 = camera.distortion1.z;
+    let k6 = camera.distortion1.w;
+
+    // fixed-point iterations, as in OpenCV's undistortPoints
+    var x = m.x;
+    var y = m.y;
+    for (var i = 0; i < 20; i++) {
+        let r2 = x*x + y*y;
+        let r4 = r2 * r2;
+        let r6 = r4 * r2;
+        let inv_radial = (1.0 + k4 * r2 + k5 * r4 + k6 * r6) / (1.0 + k1 * r2 + k2 * r4 + k3 * r6);
+        let dx = 2.0 * p1 * x * y + p2 * (r2 + 2.0 * x * x);
+        let dy = p1 * (r2 + 2.0 * y * y) + 2.0 * p2 * x * y;
+        x = (m.x - dx) * inv_radial;
+        y = (m.y - dy) * inv_radial;
+    }
+
+    return vec2<f32>(x, y);
+}
+
+fn distorted_to_z1(uv_distorted: vec2<f32>, camera: CameraProperties) -> vec2<f32> {
+    let m = vec2<f32>(
+        (uv_distorted.x - camera.px) / camera.fx,
+        (uv_distorted.y - camera.py) / camera.fy
+    );
+
+    if (camera.model == 1u) {
+        return unified_distorted_to_z1(m, camera);
+    } else if (camera.model == 2u) {
+        return kannala_brandt_distorted_to_z1(m, camera);
+    } else if (camera.model == 3u) {
+        return brown_conrady_distorted_to_z1(m, camera);
+    }
+    return m;
 }
 
 fn undistort(uv_distorted: vec2<f32>, pinhole: PinholeModel, camera: CameraProperties) -> vec2<f32> {
@@ -153,3 +279,44 @@ fn pixel_and_z_to_clip(uv_z: vec2<f32>, z: f32, camera: CameraProperties, zoom:
                     z_clip,
                     1.0);
 }
+
+// Two-sided lighting of a fragment, normal and point are given in the camera frame.
+fn shade(rgba: vec4<f32>, normal_in_cam: vec3<f32>, point_in_cam: vec3<f32>, lighting: Lighting) -> vec4<f32> {
+    if (lighting.shading == 0u) {
+        return rgba;
+    }
+    let view_dir = normalize(-point_in_cam);
+    var n = normalize(normal_in_cam);
+    if (dot(n, view_dir) < 0.0) {
+        n = -n;
+    }
+    let to_light = -normalize(lighting.direction.xyz);
+    let diffuse = max(dot(n, to_light), 0.0);
+    var rgb = rgba.rgb * (lighting.ambient + (1.0 - lighting.ambient) * diffuse);
+    if (lighting.shading == 2u) {
+        let reflected = reflect(-to_light, n);
+        rgb = rgb + lighting.specular * pow(max(dot(reflected, view_dir), 0.0), lighting.shininess);
+    }
+    return vec4<f32>(min(rgb, vec3<f32>(1.0)), rgba.a);
+}
+
+// Output of the pick pass: entity id (0 for background) and primitive index, and the surface
+// normal in the camera frame, with w = 1 on surfaces and zero elsewhere.
+struct PickOut {
+    @location(0) id: vec2<u32>,
+    @location(1) normal: vec4<f32>,
+};
+
+// pick output of primitives without surface, i.e. points and lines
+fn pick_without_normal(id: vec2<u32>) -> PickOut {
+    return PickOut(id, vec4<f32>(0.0));
+}
+
+// pick output of a surface, the normal is flipped to face the camera
+fn pick_with_normal(id: vec2<u32>, normal_in_cam: vec3<f32>, point_in_cam: vec3<f32>) -> PickOut {
+    var n = normalize(normal_in_cam);
+    if (dot(n, point_in_cam) > 0.0) {
+        n = -n;
+    }
+    return PickOut(id, vec4<f32>(n, 1.0));
+}
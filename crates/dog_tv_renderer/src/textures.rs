@@ -1,4 +1,5 @@
 use crate::textures::depth::DepthTextures;
+use crate::textures::pick::PickTextures;
 use crate::textures::rgba::RgbdTexture;
 use crate::RenderContext;
 use sophus::image::ImageSize;
@@ -7,8 +8,12 @@ use sophus::image::ImageSize;
 pub mod depth;
 /// depth image
 pub mod depth_image;
+/// glyph atlas for text labels
+pub mod glyph_atlas;
 /// NDC z buffer textures
 pub mod ndc_z_buffer;
+/// pick textures
+pub mod pick;
 /// RGBA textures
 pub mod rgba;
 /// Visual depth texture
@@ -19,6 +24,7 @@ pub(crate) struct Textures {
     pub(crate) view_port_size: ImageSize,
     pub(crate) rgbd: RgbdTexture,
     pub depth: DepthTextures,
+    pub(crate) pick: PickTextures,
 }
 
 impl Textures {
@@ -27,6 +33,7 @@ impl Textures {
             view_port_size: *view_port_size,
             rgbd: RgbdTexture::new(render_state, view_port_size),
             depth: DepthTextures::new(render_state, view_port_size),
+            pick: PickTextures::new(render_state, view_port_size),
         }
     }
 }
@@ -0,0 +1,235 @@
+use crate::preludes::*;
+use crate::renderables::pixel_renderable::TextAnchor;
+use crate::RenderContext;
+use eframe::egui;
+use eframe::egui::epaint::text::Fonts;
+
+/// Quad of a single glyph of a laid out text
+pub(crate) struct GlyphQuad {
+    /// top-left corner in pixels, relative to the anchor position
+    pub(crate) min: [f32; 2],
+    /// bottom-right corner in pixels, relative to the anchor position
+    pub(crate) max: [f32; 2],
+    /// top-left corner in the atlas, in texels
+    pub(crate) tex_min: [f32; 2],
+    /// bottom-right corner in the atlas, in texels
+    pub(crate) tex_max: [f32; 2],
+}
+
+impl GlyphQuad {
+    /// Offset and texel position of the six vertices of the two triangles of the quad
+    pub(crate) fn vertices(&self) -> [([f32; 2], [f32; 2]); 6] {
+        let top_left = (self.min, self.tex_min);
+        let top_right = (
+            [self.max[0], self.min[1]],
+            [self.tex_max[0], self.tex_min[1]],
+        );
+        let bottom_left = (
+            [self.min[0], self.max[1]],
+            [self.tex_min[0], self.tex_max[1]],
+        );
+        let bottom_right = (self.max, self.tex_max);
+        [
+            top_left,
+            top_right,
+            bottom_left,
+            top_right,
+            bottom_left,
+            bottom_right,
+        ]
+    }
+}
+
+/// Glyph atlas for text labels
+///
+/// Text is laid out and rasterized using the default egui fonts. Glyphs are added to the atlas on
+/// demand, hence `upload` must be called after text was laid out. Quads refer to the atlas in
+/// texels, so that they stay valid when the atlas grows, but not when it is rebuilt, see
+/// `begin_frame`.
+pub(crate) struct GlyphAtlas {
+    fonts: Fonts,
+    texture: wgpu::Texture,
+    sampler: wgpu::Sampler,
+    pub(crate) bind_group_layout: wgpu::BindGroupLayout,
+    pub(crate) bind_group: wgpu::BindGroup,
+}
+
+impl GlyphAtlas {
+    const MAX_TEXTURE_SIDE: usize = 2048;
+
+    pub(crate) fn new(render_context: &RenderContext) -> Self {
+        let device = &render_context.wgpu_device;
+
+        // one point is one pixel
+        let fonts = Fonts::new(
+            1.0,
+            Self::MAX_TEXTURE_SIDE,
+            egui::FontDefinitions::default(),
+        );
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("glyph atlas bind group layout"),
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let texture = Self::create_texture(render_context, fonts.font_image_size());
+        let bind_group =
+            Self::create_bind_group(render_context, &bind_group_layout, &texture, &sampler);
+
+        let mut atlas = GlyphAtlas {
+            fonts,
+            texture,
+            sampler,
+            bind_group_layout,
+            bind_group,
+        };
+        atlas.upload(render_context);
+        atlas
+    }
+
+    fn create_texture(render_context: &RenderContext, size: [usize; 2]) -> wgpu::Texture {
+        render_context
+            .wgpu_device
+            .create_texture(&wgpu::TextureDescriptor {
+                label: Some("glyph atlas texture"),
+                size: wgpu::Extent3d {
+                    width: size[0] as u32,
+                    height: size[1] as u32,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::R8Unorm,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                view_formats: &[],
+            })
+    }
+
+    fn create_bind_group(
+        render_context: &RenderContext,
+        layout: &wgpu::BindGroupLayout,
+        texture: &wgpu::Texture,
+        sampler: &wgpu::Sampler,
+    ) -> wgpu::BindGroup {
+        render_context
+            .wgpu_device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(
+                            &texture.create_view(&wgpu::TextureViewDescriptor::default()),
+                        ),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(sampler),
+                    },
+                ],
+                label: Some("glyph atlas bind group"),
+            })
+    }
+
+    /// Start a new frame, must be called once per render before text is laid out
+    ///
+    /// Drops cached layouts which were not used since the last frame. Returns true if the atlas
+    /// was almost full and hence rebuilt, in which case all quads must be laid out again.
+    pub(crate) fn begin_frame(&self) -> bool {
+        let fill_ratio = self.fonts.font_atlas_fill_ratio();
+        self.fonts.begin_frame(1.0, Self::MAX_TEXTURE_SIDE);
+        self.fonts.font_atlas_fill_ratio() < fill_ratio
+    }
+
+    /// Lay out the text and return one quad per visible glyph
+    pub(crate) fn layout(&self, text: &str, font_size: f32, anchor: TextAnchor) -> Vec<GlyphQuad> {
+        let galley = self.fonts.layout_no_wrap(
+            text.to_string(),
+            egui::FontId::proportional(font_size),
+            egui::Color32::WHITE,
+        );
+        let [align_x, align_y] = anchor.align();
+        let shift = egui::vec2(
+            -align_x * galley.rect.width(),
+            -align_y * galley.rect.height(),
+        ) - galley.rect.min.to_vec2();
+
+        galley
+            .rows
+            .iter()
+            .flat_map(|row| row.glyphs.iter())
+            .filter(|glyph| !glyph.uv_rect.is_nothing())
+            .map(|glyph| {
+                let min = glyph.pos + glyph.uv_rect.offset + shift;
+                let max = min + glyph.uv_rect.size;
+                GlyphQuad {
+                    min: [min.x, min.y],
+                    max: [max.x, max.y],
+                    tex_min: [glyph.uv_rect.min[0] as f32, glyph.uv_rect.min[1] as f32],
+                    tex_max: [glyph.uv_rect.max[0] as f32, glyph.uv_rect.max[1] as f32],
+                }
+            })
+            .collect()
+    }
+
+    /// Upload glyphs, which were rasterized since the last upload, to the atlas texture
+    pub(crate) fn upload(&mut self, render_context: &RenderContext) {
+        if self.fonts.font_image_delta().is_none() {
+            return;
+        }
+        let image = self.fonts.image();
+        let [width, height] = image.size;
+        if self.texture.width() != width as u32 || self.texture.height() != height as u32 {
+            self.texture = Self::create_texture(render_context, image.size);
+            self.bind_group = Self::create_bind_group(
+                render_context,
+                &self.bind_group_layout,
+                &self.texture,
+                &self.sampler,
+            );
+        }
+
+        let coverage: Vec<u8> = image.srgba_pixels(None).map(|c| c.a()).collect();
+        render_context.wgpu_queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &coverage,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(width as u32),
+                rows_per_image: Some(height as u32),
+            },
+            self.texture.size(),
+        );
+    }
+}
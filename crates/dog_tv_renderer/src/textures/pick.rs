@@ -0,0 +1,226 @@
+use crate::preludes::*;
+use crate::RenderContext;
+use sophus::image::ImageSize;
+use wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+
+/// Render targets of the pick pass
+///
+/// The id texture holds, per pixel, the entity id (0 for background) and the primitive index of
+/// the closest scene primitive, the normal texture its surface normal in the camera frame. Both
+/// are rendered without multisampling, so that ids and normals are never blended.
+#[derive(Debug)]
+pub(crate) struct PickTextures {
+    pub(crate) id_texture: wgpu::Texture,
+    pub(crate) id_texture_view: wgpu::TextureView,
+    pub(crate) normal_texture: wgpu::Texture,
+    pub(crate) normal_texture_view: wgpu::TextureView,
+    pub(crate) _depth_texture: wgpu::Texture,
+    pub(crate) depth_texture_view: wgpu::TextureView,
+    staging_buffer: wgpu::Buffer,
+}
+
+impl PickTextures {
+    pub(crate) const ID_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rg32Uint;
+    pub(crate) const NORMAL_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba32Float;
+
+    pub(crate) fn new(render_state: &RenderContext, view_port_size: &ImageSize) -> Self {
+        let size = wgpu::Extent3d {
+            width: view_port_size.width as u32,
+            height: view_port_size.height as u32,
+            depth_or_array_layers: 1,
+        };
+        let id_texture = render_state
+            .wgpu_device
+            .create_texture(&wgpu::TextureDescriptor {
+                label: Some("pick id texture"),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: Self::ID_FORMAT,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+                view_formats: &[],
+            });
+        let id_texture_view = id_texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let normal_texture = render_state
+            .wgpu_device
+            .create_texture(&wgpu::TextureDescriptor {
+                label: Some("pick normal texture"),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: Self::NORMAL_FORMAT,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+                view_formats: &[],
+            });
+        let normal_texture_view =
+            normal_texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let depth_texture = render_state
+            .wgpu_device
+            .create_texture(&wgpu::TextureDescriptor {
+                label: Some("pick depth texture"),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Depth32Float,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                view_formats: &[],
+            });
+        let depth_texture_view = depth_texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let staging_buffer = render_state
+            .wgpu_device
+            .create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Pick Buffer Staging"),
+                size: COPY_BYTES_PER_ROW_ALIGNMENT as u64,
+                usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+
+        PickTextures {
+            id_texture,
+            id_texture_view,
+            normal_texture,
+            normal_texture_view,
+            _depth_texture: depth_texture,
+            depth_texture_view,
+            staging_buffer,
+        }
+    }
+
+    /// Downloads the entity id and primitive index at the given viewport pixel.
+    pub(crate) fn download_texel(
+        &self,
+        state: &RenderContext,
+        mut command_encoder: wgpu::CommandEncoder,
+        x: u32,
+        y: u32,
+    ) -> [u32; 2] {
+        command_encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                texture: &self.id_texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d { x, y, z: 0 },
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &self.staging_buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(COPY_BYTES_PER_ROW_ALIGNMENT),
+                    rows_per_image: Some(1),
+                },
+            },
+            wgpu::Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+        );
+
+        // Submit command encoder and wait for GPU
+        let device = state.wgpu_device.clone();
+        state.wgpu_queue.submit(Some(command_encoder.finish()));
+
+        let buffer_slice = self.staging_buffer.slice(..);
+        buffer_slice.map_async(wgpu::MapMode::Read, move |_result| {});
+        device.poll(wgpu::Maintain::Wait);
+
+        let texel = {
+            let data = buffer_slice.get_mapped_range();
+            let ids: &[u32] = bytemuck::cast_slice(&data[..8]);
+            [ids[0], ids[1]]
+        };
+        self.staging_buffer.unmap();
+        texel
+    }
+
+    /// Downloads the entity ids and the normals of the whole viewport, in row-major order.
+    pub(crate) fn download_images(
+        &self,
+        state: &RenderContext,
+        mut command_encoder: wgpu::CommandEncoder,
+        view_port_size: &ImageSize,
+    ) -> (Vec<u32>, Vec<[f32; 4]>) {
+        let w = view_port_size.width as u32;
+        let h = view_port_size.height as u32;
+        let padded_bytes_per_row = |bytes_per_texel: u32| {
+            (w * bytes_per_texel).div_ceil(COPY_BYTES_PER_ROW_ALIGNMENT)
+                * COPY_BYTES_PER_ROW_ALIGNMENT
+        };
+        let id_bytes_per_row = padded_bytes_per_row(8);
+        let normal_bytes_per_row = padded_bytes_per_row(16);
+
+        let copy = |command_encoder: &mut wgpu::CommandEncoder,
+                    texture: &wgpu::Texture,
+                    bytes_per_row: u32| {
+            let buffer = state.wgpu_device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("pick image staging buffer"),
+                size: (bytes_per_row * h) as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            });
+            command_encoder.copy_texture_to_buffer(
+                wgpu::ImageCopyTexture {
+                    texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                wgpu::ImageCopyBuffer {
+                    buffer: &buffer,
+                    layout: wgpu::ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: Some(bytes_per_row),
+                        rows_per_image: Some(h),
+                    },
+                },
+                wgpu::Extent3d {
+                    width: w,
+                    height: h,
+                    depth_or_array_layers: 1,
+                },
+            );
+            buffer
+        };
+        let id_buffer = copy(&mut command_encoder, &self.id_texture, id_bytes_per_row);
+        let normal_buffer = copy(
+            &mut command_encoder,
+            &self.normal_texture,
+            normal_bytes_per_row,
+        );
+
+        state.wgpu_queue.submit(Some(command_encoder.finish()));
+        id_buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |_result| {});
+        normal_buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |_result| {});
+        state.wgpu_device.poll(wgpu::Maintain::Wait);
+
+        let mut ids = Vec::with_capacity((w * h) as usize);
+        let mut normals = Vec::with_capacity((w * h) as usize);
+        {
+            let id_data = id_buffer.slice(..).get_mapped_range();
+            let normal_data = normal_buffer.slice(..).get_mapped_range();
+            for row in 0..h as usize {
+                let id_row: &[u32] = bytemuck::cast_slice(
+                    &id_data[row * id_bytes_per_row as usize..][..8 * w as usize],
+                );
+                ids.extend(id_row.chunks_exact(2).map(|texel| texel[0]));
+                let normal_row: &[[f32; 4]] = bytemuck::cast_slice(
+                    &normal_data[row * normal_bytes_per_row as usize..][..16 * w as usize],
+                );
+                normals.extend_from_slice(normal_row);
+            }
+        }
+        id_buffer.unmap();
+        normal_buffer.unmap();
+        (ids, normals)
+    }
+}
@@ -1,10 +1,13 @@
 use crate::aspect_ratio::HasAspectRatio;
 use crate::offscreen_renderer::OffscreenRenderer;
+use crate::preludes::*;
 use crate::renderables::color::Color;
 use crate::textures::depth_image::DepthImage;
 use eframe::egui;
 use sophus::core::linalg::VecF64;
+use sophus::image::arc_image::ArcImage4F32;
 use sophus::image::arc_image::ArcImage4U8;
+use sophus::image::ImageSize;
 
 /// Render result
 pub struct RenderResult {
@@ -19,6 +22,77 @@ pub struct RenderResult {
 
     /// depth egui texture id
     pub depth_egui_tex_id: egui::TextureId,
+
+    /// scene entity under the pick pixel, if picking was requested
+    pub scene_pick: Option<ScenePick>,
+
+    /// surface normals in the camera frame, if ground truth was requested
+    ///
+    /// The fourth channel is 1 on surfaces, and 0 on points, lines and the background, for which
+    /// the normal is zero.
+    pub normal_image: Option<ArcImage4F32>,
+
+    /// scene entity per pixel, if ground truth was requested
+    pub entity_id_image: Option<EntityIdImage>,
+}
+
+/// Kind of a picked scene entity
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ScenePickKind {
+    /// point cloud, the primitive is a point
+    #[default]
+    Point,
+    /// line segments, the primitive is a line segment
+    Line,
+    /// triangle mesh, the primitive is a triangle
+    Mesh,
+    /// textured triangle mesh, the primitive is a triangle
+    TexturedMesh,
+    /// instanced template geometry, the primitive is an instance
+    Instanced,
+    /// streamed point cloud, the primitive is a retained point
+    PointStream,
+    /// point cloud with scalar fields, the primitive is a point
+    ScalarPoint,
+    /// polyline, the primitive is a segment, counted from the oldest retained vertex
+    Polyline,
+}
+
+/// Scene entity and primitive under a pixel
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ScenePick {
+    /// name of the scene renderable
+    pub name: String,
+    /// kind of the scene renderable
+    pub kind: ScenePickKind,
+    /// index of the point, line segment or triangle within the renderable
+    pub primitive_index: usize,
+}
+
+/// Scene entity per pixel, i.e. an instance segmentation of the rendered image
+///
+/// Entity ids are only valid for the render they were returned by, use the entity names to
+/// relate pixels across renders.
+#[derive(Clone, Debug, Default)]
+pub struct EntityIdImage {
+    /// image size
+    pub image_size: ImageSize,
+    /// entity id per pixel in row-major order, 0 for background and `i + 1` for `entities[i]`
+    pub ids: Vec<u32>,
+    /// kind and name of the scene renderables
+    pub entities: Vec<(ScenePickKind, String)>,
+}
+
+impl EntityIdImage {
+    /// entity id at pixel
+    pub fn id(&self, u: usize, v: usize) -> u32 {
+        self.ids[v * self.image_size.width + u]
+    }
+
+    /// kind and name of the entity at pixel, `None` for background
+    pub fn entity(&self, u: usize, v: usize) -> Option<&(ScenePickKind, String)> {
+        self.entities.get((self.id(u, v) as usize).checked_sub(1)?)
+    }
 }
 
 impl HasAspectRatio for OffscreenRenderer {
@@ -1,4 +1,5 @@
 use crate::camera::properties::RenderCameraProperties;
+use crate::lighting::SceneLighting;
 use crate::preludes::*;
 use crate::types::TranslationAndScaling;
 use crate::types::Zoom2dPod;
@@ -41,9 +42,21 @@ pub(crate) struct CameraPropertiesUniform {
     pub(crate) fy: f32,
     pub(crate) px: f32,
     pub(crate) py: f32,
-    //
-    pub(crate) alpha: f32, // if alpha == 0, then we use the pinhole model
-    pub(crate) beta: f32,
+    // distortion model, see `DistortionModel`
+    pub(crate) model: u32,
+    pub(crate) _padding: [u32; 3],
+    // distortion parameters, the meaning depends on the distortion model
+    pub(crate) distortion: [f32; 8],
+}
+
+/// Distortion model id, as used in the camera properties uniform
+#[repr(u32)]
+#[derive(Debug, Copy, Clone)]
+pub(crate) enum DistortionModel {
+    Pinhole = 0,
+    UnifiedExtended = 1,
+    KannalaBrandt = 2,
+    BrownConrady = 3,
 }
 
 pub(crate) struct CameraFromEntityPoseUniform {
@@ -85,6 +98,7 @@ pub struct VertexShaderUniformBuffers {
     pub(crate) camera_from_entity_pose_buffer: CameraFromEntityPoseUniform,
     pub(crate) pinhole_buffer: wgpu::Buffer,
     pub(crate) zoom_buffer: wgpu::Buffer,
+    pub(crate) lighting_buffer: wgpu::Buffer,
 }
 
 impl VertexShaderUniformBuffers {
@@ -137,6 +151,17 @@ impl VertexShaderUniformBuffers {
                         },
                         count: None,
                     },
+                    // lighting is evaluated in the fragment shader
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 4,
+                        visibility: stage | ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
                 ],
             })
     }
@@ -189,6 +214,14 @@ impl VertexShaderUniformBuffers {
                 usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
             });
 
+        let lighting_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("lighting buffer"),
+            contents: bytemuck::cast_slice(&[
+                SceneLighting::default().to_uniform(&Isometry3F64::identity())
+            ]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
         let render_uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("render bind group"),
             layout: &render_uniform_bind_group_layout,
@@ -209,6 +242,10 @@ impl VertexShaderUniformBuffers {
                     binding: 3,
                     resource: camera_from_entity_pose_buffer.as_entire_binding(),
                 },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: lighting_buffer.as_entire_binding(),
+                },
             ],
         });
         let compute_uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
@@ -231,6 +268,10 @@ impl VertexShaderUniformBuffers {
                     binding: 3,
                     resource: camera_from_entity_pose_buffer.as_entire_binding(),
                 },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: lighting_buffer.as_entire_binding(),
+                },
             ],
         });
 
@@ -242,6 +283,7 @@ impl VertexShaderUniformBuffers {
             camera_properties_buffer: camera_properties_uniform_buffer,
             zoom_buffer,
             pinhole_buffer,
+            lighting_buffer,
             camera_from_entity_pose_buffer: CameraFromEntityPoseUniform {
                 camera_from_entity_buffer: camera_from_entity_pose_buffer,
             },
@@ -292,4 +334,17 @@ impl VertexShaderUniformBuffers {
             .wgpu_queue
             .write_buffer(&self.zoom_buffer, 0, bytemuck::cast_slice(&[zoom_uniform]));
     }
+
+    pub(crate) fn update_lighting(
+        &self,
+        state: &RenderContext,
+        lighting: &SceneLighting,
+        world_from_camera: &Isometry3F64,
+    ) {
+        state.wgpu_queue.write_buffer(
+            &self.lighting_buffer,
+            0,
+            bytemuck::cast_slice(&[lighting.to_uniform(world_from_camera)]),
+        );
+    }
 }
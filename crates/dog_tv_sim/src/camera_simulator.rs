@@ -1,10 +1,27 @@
+use crate::noise::SensorNoise;
+use crate::noise::SimRng;
+use crate::shutter::ShutterModel;
+use alloc::string::String;
+use alloc::vec;
 use alloc::vec::Vec;
+use dog_tv_renderer::background::Background;
 use dog_tv_renderer::camera::properties::RenderCameraProperties;
+use dog_tv_renderer::lighting::SceneLighting;
+use dog_tv_renderer::offscreen_renderer::OffscreenCamera;
 use dog_tv_renderer::offscreen_renderer::OffscreenRenderer;
 use dog_tv_renderer::renderables::scene_renderable::SceneRenderable;
 use dog_tv_renderer::textures::depth_image::DepthImage;
+use dog_tv_renderer::types::EntityIdImage;
+use dog_tv_renderer::types::RenderResult;
 use dog_tv_renderer::RenderContext;
+use sophus::image::arc_image::ArcImage4F32;
 use sophus::image::arc_image::ArcImage4U8;
+use sophus::image::arc_image::ArcImageF32;
+use sophus::image::mut_image::MutImage4F32;
+use sophus::image::mut_image::MutImage4U8;
+use sophus::image::mut_image::MutImageF32;
+use sophus::image::prelude::IsImageView;
+use sophus::image::prelude::IsMutImageView;
 use sophus::lie::Isometry3F64;
 
 extern crate alloc;
@@ -12,6 +29,9 @@ extern crate alloc;
 /// camera simulator
 pub struct CameraSimulator {
     renderer: OffscreenRenderer,
+    background: Background,
+    maybe_noise: Option<(SensorNoise, SimRng)>,
+    ground_truth: bool,
 }
 
 /// Simulated image
@@ -20,6 +40,19 @@ pub struct SimulatedImage {
     pub rgba_image: ArcImage4U8,
     /// depth
     pub depth_image: DepthImage,
+    /// surface normals in the camera frame, see `CameraSimulator::set_ground_truth`
+    pub normal_image: Option<ArcImage4F32>,
+    /// scene renderable per pixel, see `CameraSimulator::set_ground_truth`
+    pub entity_id_image: Option<EntityIdImage>,
+}
+
+fn simulated_image(result: RenderResult) -> SimulatedImage {
+    SimulatedImage {
+        rgba_image: result.rgba_image.unwrap(),
+        depth_image: result.depth_image,
+        normal_image: result.normal_image,
+        entity_id_image: result.entity_id_image,
+    }
 }
 
 impl CameraSimulator {
@@ -27,6 +60,9 @@ impl CameraSimulator {
     pub fn new(render_state: &RenderContext, camera_properties: &RenderCameraProperties) -> Self {
         CameraSimulator {
             renderer: OffscreenRenderer::new(render_state, camera_properties),
+            background: Background::default(),
+            maybe_noise: None,
+            ground_truth: false,
         }
     }
 
@@ -35,19 +71,176 @@ impl CameraSimulator {
         self.renderer.update_scene(renderables);
     }
 
+    /// delete scene renderables by name
+    pub fn delete_3d_renderables(&mut self, names: &[String]) {
+        self.renderer.delete_scene(names);
+    }
+
+    /// clear all scene renderables
+    pub fn clear_3d_renderables(&mut self) {
+        self.renderer.clear_scene();
+    }
+
+    /// set the lighting of triangle meshes, use `SceneLighting::unlit()` for plain vertex colors
+    pub fn set_lighting(&mut self, lighting: SceneLighting) {
+        self.renderer.scene.lighting = lighting;
+    }
+
+    /// set the background, transparent by default
+    pub fn set_background(&mut self, background: Background) {
+        self.background = background;
+    }
+
+    /// set the sensor noise, none by default
+    ///
+    /// The random number generator is reseeded, hence the noise of the following renders is
+    /// reproducible.
+    pub fn set_sensor_noise(&mut self, maybe_noise: Option<SensorNoise>) {
+        self.maybe_noise = maybe_noise.map(|noise| (noise, SimRng::new(noise.seed)));
+    }
+
+    /// also render ground-truth normal and entity id images, off by default
+    ///
+    /// The ground truth is not affected by the sensor noise.
+    pub fn set_ground_truth(&mut self, ground_truth: bool) {
+        self.ground_truth = ground_truth;
+    }
+
     /// render
     pub fn render(&mut self, scene_from_camera: Isometry3F64) -> SimulatedImage {
+        let image = self.render_without_noise(scene_from_camera);
+        self.apply_noise(image)
+    }
+
+    /// render another camera sharing the renderables, lighting and background of this simulator
+    pub(crate) fn render_camera(
+        &mut self,
+        camera: &mut OffscreenCamera,
+        scene_from_camera: Isometry3F64,
+    ) -> SimulatedImage {
+        let view_port_size = camera.camera_properties().intrinsics.image_size();
+        let result = self
+            .renderer
+            .camera_render_params(camera, &view_port_size, &scene_from_camera)
+            .background(self.background.clone())
+            .download_rgba(true)
+            .download_ground_truth(self.ground_truth)
+            .render();
+        self.apply_noise(simulated_image(result))
+    }
+
+    /// Render a moving camera, with rolling shutter and motion blur
+    ///
+    /// `scene_from_camera_at` returns the camera pose at a given time, with time zero at the
+    /// start of the exposure of the first row, see `interpolate_pose` for a start and end pose.
+    /// Each row band is rendered `shutter.blur_samples` times over its exposure and averaged.
+    /// Depth and ground truth are taken from the sub-frame in the middle of the exposure.
+    ///
+    /// Each sub-frame is a full render of which only the rows of the band are kept, hence the
+    /// cost is `row_bands * blur_samples` renders, see `ShutterModel::row_bands`.
+    pub fn render_trajectory(
+        &mut self,
+        scene_from_camera_at: &dyn Fn(f64) -> Isometry3F64,
+        shutter: &ShutterModel,
+    ) -> SimulatedImage {
+        let image_size = self.renderer.intrinsics().image_size();
+        let width = image_size.width;
+        let height = image_size.height;
+        let bands = if shutter.line_delay > 0.0 {
+            // an empty image has a single empty band
+            shutter.row_bands.clamp(1, height.max(1))
+        } else {
+            1
+        };
+        let blur_samples = shutter.blur_samples.max(1);
+
+        let mut rgba_sum = vec![[0.0_f32; 4]; width * height];
+        let mut ndc_z_image = MutImageF32::from_image_size(image_size);
+        let mut normal_image = self
+            .ground_truth
+            .then(|| MutImage4F32::from_image_size(image_size));
+        let mut entity_id_image: Option<EntityIdImage> = None;
+        let mut clipping_planes = None;
+
+        for band in 0..bands {
+            let rows = band * height / bands..(band + 1) * height / bands;
+            // all rows of a band share the pose of its center row
+            let band_start = 0.5 * (rows.start + rows.end - 1) as f64 * shutter.line_delay;
+            for sample in 0..blur_samples {
+                let time = band_start
+                    + shutter.exposure_time * (sample as f64 + 0.5) / blur_samples as f64;
+                let image = self.render_without_noise(scene_from_camera_at(time));
+
+                for v in rows.clone() {
+                    for u in 0..width {
+                        let rgba = image.rgba_image.pixel(u, v);
+                        for c in 0..4 {
+                            rgba_sum[v * width + u][c] += rgba[c] as f32;
+                        }
+                    }
+                }
+                if sample != blur_samples / 2 {
+                    continue;
+                }
+                clipping_planes = Some(image.depth_image.clipping_planes);
+                for v in rows.clone() {
+                    for u in 0..width {
+                        *ndc_z_image.mut_pixel(u, v) = image.depth_image.ndc_z_image.pixel(u, v);
+                    }
+                }
+                if let (Some(normal_image), Some(normals)) =
+                    (&mut normal_image, &image.normal_image)
+                {
+                    for v in rows.clone() {
+                        for u in 0..width {
+                            *normal_image.mut_pixel(u, v) = normals.pixel(u, v);
+                        }
+                    }
+                }
+                if let Some(ids) = &image.entity_id_image {
+                    let row_range = rows.start * width..rows.end * width;
+                    entity_id_image.get_or_insert_with(|| ids.clone()).ids[row_range.clone()]
+                        .copy_from_slice(&ids.ids[row_range]);
+                }
+            }
+        }
+
+        let mut rgba_image = MutImage4U8::from_image_size(image_size);
+        for v in 0..height {
+            for u in 0..width {
+                let sum = rgba_sum[v * width + u];
+                *rgba_image.mut_pixel(u, v) =
+                    sum.map(|c| (c / blur_samples as f32).round() as u8).into();
+            }
+        }
+
+        let image = SimulatedImage {
+            rgba_image: ArcImage4U8::from(rgba_image),
+            depth_image: DepthImage::new(ArcImageF32::from(ndc_z_image), clipping_planes.unwrap()),
+            normal_image: normal_image.map(ArcImage4F32::from),
+            entity_id_image,
+        };
+        self.apply_noise(image)
+    }
+
+    fn render_without_noise(&mut self, scene_from_camera: Isometry3F64) -> SimulatedImage {
         let view_port_size = self.renderer.intrinsics().image_size();
 
         let result = self
             .renderer
             .render_params(&view_port_size, &scene_from_camera)
+            .background(self.background.clone())
             .download_rgba(true)
+            .download_ground_truth(self.ground_truth)
             .render();
+        simulated_image(result)
+    }
 
-        SimulatedImage {
-            rgba_image: result.rgba_image.unwrap(),
-            depth_image: result.depth_image,
+    fn apply_noise(&mut self, mut image: SimulatedImage) -> SimulatedImage {
+        if let Some((noise, rng)) = &mut self.maybe_noise {
+            image.rgba_image = noise.apply_to_rgba(rng, &image.rgba_image);
+            image.depth_image = noise.apply_to_depth(rng, &image.depth_image);
         }
+        image
     }
 }
@@ -6,3 +6,11 @@
 
 /// camera simulator - camera image renderer
 pub mod camera_simulator;
+/// lidar simulator - resampled depth cube-maps
+pub mod lidar_simulator;
+/// seedable sensor noise and photometric models
+pub mod noise;
+/// multi-camera rig and rectified stereo simulator
+pub mod rig_simulator;
+/// rolling shutter and motion blur of moving cameras
+pub mod shutter;
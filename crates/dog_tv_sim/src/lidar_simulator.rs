@@ -0,0 +1,370 @@
+use crate::camera_simulator::CameraSimulator;
+use alloc::string::String;
+use alloc::vec::Vec;
+use dog_tv_renderer::camera::clipping_planes::ClippingPlanesF64;
+use dog_tv_renderer::camera::properties::RenderCameraProperties;
+use dog_tv_renderer::lighting::SceneLighting;
+use dog_tv_renderer::renderables::scene_renderable::SceneRenderable;
+use dog_tv_renderer::RenderContext;
+use sophus::core::linalg::MatF64;
+use sophus::core::linalg::VecF64;
+use sophus::image::prelude::IsImageView;
+use sophus::image::ImageSize;
+use sophus::lie::Isometry3F64;
+use sophus::lie::Rotation3F64;
+use sophus::sensor::dyn_camera::DynCameraF64;
+
+extern crate alloc;
+
+/// Single lidar beam
+///
+/// The lidar frame has x pointing forward, y to the left and z up. The azimuth is the angle
+/// around z, counted from x towards y, and the elevation the angle above the xy-plane.
+#[derive(Clone, Copy, Debug)]
+pub struct LidarBeam {
+    /// azimuth in radians
+    pub azimuth: f64,
+    /// elevation in radians
+    pub elevation: f64,
+    /// time of the beam, relative to the start of the scan
+    pub time_offset: f64,
+}
+
+impl LidarBeam {
+    /// unit direction in the lidar frame
+    pub fn direction(&self) -> VecF64<3> {
+        VecF64::<3>::new(
+            self.elevation.cos() * self.azimuth.cos(),
+            self.elevation.cos() * self.azimuth.sin(),
+            self.elevation.sin(),
+        )
+    }
+}
+
+/// Beam pattern of a lidar scan
+#[derive(Clone, Debug, Default)]
+pub struct BeamPattern {
+    /// beams, in firing order
+    pub beams: Vec<LidarBeam>,
+}
+
+impl BeamPattern {
+    /// Spinning lidar, all channels fire at each of the `azimuth_steps` evenly spaced azimuths
+    ///
+    /// One revolution takes `scan_period` seconds.
+    pub fn spinning(elevations: &[f64], azimuth_steps: usize, scan_period: f64) -> Self {
+        let mut beams = Vec::with_capacity(elevations.len() * azimuth_steps);
+        for step in 0..azimuth_steps {
+            let fraction = step as f64 / azimuth_steps as f64;
+            for &elevation in elevations {
+                beams.push(LidarBeam {
+                    azimuth: core::f64::consts::TAU * fraction,
+                    elevation,
+                    time_offset: scan_period * fraction,
+                });
+            }
+        }
+        BeamPattern { beams }
+    }
+
+    /// Spinning lidar with `channels` elevations evenly spaced in the given range, e.g. 16, 32 or
+    /// 64 channels
+    pub fn spinning_uniform(
+        channels: usize,
+        min_elevation: f64,
+        max_elevation: f64,
+        azimuth_steps: usize,
+        scan_period: f64,
+    ) -> Self {
+        let elevations: Vec<f64> = (0..channels)
+            .map(|i| {
+                let fraction = if channels > 1 {
+                    i as f64 / (channels - 1) as f64
+                } else {
+                    0.5
+                };
+                min_elevation + fraction * (max_elevation - min_elevation)
+            })
+            .collect();
+        Self::spinning(&elevations, azimuth_steps, scan_period)
+    }
+
+    /// Solid-state lidar, scanning a forward facing field of view row by row
+    ///
+    /// Rows are scanned from top to bottom, each from left to right, within `scan_period`.
+    pub fn solid_state(
+        horizontal_fov: f64,
+        vertical_fov: f64,
+        columns: usize,
+        rows: usize,
+        scan_period: f64,
+    ) -> Self {
+        let angle = |i: usize, n: usize, fov: f64| {
+            if n > 1 {
+                fov * (0.5 - i as f64 / (n - 1) as f64)
+            } else {
+                0.0
+            }
+        };
+        let mut beams = Vec::with_capacity(columns * rows);
+        for row in 0..rows {
+            for column in 0..columns {
+                beams.push(LidarBeam {
+                    azimuth: angle(column, columns, horizontal_fov),
+                    elevation: angle(row, rows, vertical_fov),
+                    time_offset: scan_period * beams.len() as f64 / (columns * rows) as f64,
+                });
+            }
+        }
+        BeamPattern { beams }
+    }
+}
+
+/// Lidar sensor properties
+#[derive(Clone, Copy, Debug)]
+pub struct LidarProperties {
+    /// minimal range, closer surfaces are not seen
+    pub min_range: f64,
+    /// maximal range, further surfaces are not seen
+    pub max_range: f64,
+    /// width and height of each rendered cube-map face, the angular resolution is about
+    /// `PI / (2 * face_size)` radians
+    pub face_size: usize,
+}
+
+impl Default for LidarProperties {
+    fn default() -> Self {
+        Self {
+            min_range: 0.1,
+            max_range: 100.0,
+            face_size: 512,
+        }
+    }
+}
+
+/// Lidar return
+#[derive(Clone, Copy, Debug)]
+pub struct LidarPoint {
+    /// index of the beam in the beam pattern
+    pub beam_index: usize,
+    /// point in the lidar frame
+    pub point_in_lidar: VecF64<3>,
+    /// distance to the lidar origin
+    pub range: f64,
+    /// return intensity in [0, 1], the surface brightness times the cosine of the incidence
+    /// angle
+    pub intensity: f32,
+    /// time of the return
+    pub timestamp: f64,
+}
+
+/// Lidar scan, beams without return are omitted
+#[derive(Clone, Debug)]
+pub struct LidarScan {
+    /// pose of the lidar during the scan
+    pub world_from_lidar: Isometry3F64,
+    /// returns, in firing order
+    pub points: Vec<LidarPoint>,
+}
+
+struct CubeFace {
+    lidar_from_face: Isometry3F64,
+    simulator: CameraSimulator,
+}
+
+struct BeamLookup {
+    face: usize,
+    u: usize,
+    v: usize,
+    // beam direction in the face camera frame, scaled to z = 1
+    direction_in_face: VecF64<3>,
+}
+
+/// Lidar simulator
+///
+/// Depth is rendered into the faces of a cube-map around the lidar, which are then sampled
+/// along the beams. Only faces hit by a beam are rendered. The whole scan is taken from a
+/// single pose, i.e. motion during the scan is not simulated.
+pub struct LidarSimulator {
+    beam_pattern: BeamPattern,
+    properties: LidarProperties,
+    faces: Vec<CubeFace>,
+    lookup: Vec<BeamLookup>,
+}
+
+impl LidarSimulator {
+    /// new lidar simulator
+    pub fn new(
+        render_context: &RenderContext,
+        beam_pattern: BeamPattern,
+        properties: LidarProperties,
+    ) -> Self {
+        // face camera axes x, y and z in the lidar frame, looking along +x, -x, +y, -y, +z, -z
+        let face_axes: [[[f64; 3]; 3]; 6] = [
+            [[0.0, -1.0, 0.0], [0.0, 0.0, -1.0], [1.0, 0.0, 0.0]],
+            [[0.0, 1.0, 0.0], [0.0, 0.0, -1.0], [-1.0, 0.0, 0.0]],
+            [[1.0, 0.0, 0.0], [0.0, 0.0, -1.0], [0.0, 1.0, 0.0]],
+            [[-1.0, 0.0, 0.0], [0.0, 0.0, -1.0], [0.0, -1.0, 0.0]],
+            [[0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]],
+            [[0.0, -1.0, 0.0], [-1.0, 0.0, 0.0], [0.0, 0.0, -1.0]],
+        ];
+        let size = properties.face_size;
+        let focal_length = 0.5 * size as f64;
+        let principal_point = 0.5 * (size as f64 - 1.0);
+
+        let mut face_of_axes: [Option<usize>; 6] = [None; 6];
+        let mut faces = Vec::new();
+        let mut lookup = Vec::with_capacity(beam_pattern.beams.len());
+        for beam in &beam_pattern.beams {
+            let direction = beam.direction();
+            let axes_index = (0..6)
+                .max_by(|&a, &b| {
+                    let along = |i: usize| VecF64::<3>::from(face_axes[i][2]).dot(&direction);
+                    along(a).total_cmp(&along(b))
+                })
+                .unwrap();
+            let [x, y, z] = face_axes[axes_index].map(VecF64::<3>::from);
+
+            let face = *face_of_axes[axes_index].get_or_insert_with(|| {
+                let camera_properties = RenderCameraProperties::new(
+                    DynCameraF64::new_pinhole(
+                        &VecF64::<4>::new(
+                            focal_length,
+                            focal_length,
+                            principal_point,
+                            principal_point,
+                        ),
+                        ImageSize::new(size, size),
+                    ),
+                    ClippingPlanesF64 {
+                        // the corners of a face are sqrt(3) times further away than its center
+                        near: properties.min_range / 3.0_f64.sqrt(),
+                        far: properties.max_range,
+                    },
+                );
+                let mut simulator = CameraSimulator::new(render_context, &camera_properties);
+                // the plain colors are used as surface brightness
+                simulator.set_lighting(SceneLighting::unlit());
+                simulator.set_ground_truth(true);
+                faces.push(CubeFace {
+                    lidar_from_face: Isometry3F64::from_rotation(
+                        &Rotation3F64::from_rotation_matrix(&MatF64::<3, 3>::from_columns(&[
+                            x, y, z,
+                        ])),
+                    ),
+                    simulator,
+                });
+                faces.len() - 1
+            });
+
+            let direction_in_face =
+                VecF64::<3>::new(x.dot(&direction), y.dot(&direction), 1.0) / z.dot(&direction);
+            let pixel = |coordinate: f64| {
+                ((focal_length * coordinate + principal_point).round() as usize).min(size - 1)
+            };
+            lookup.push(BeamLookup {
+                face,
+                u: pixel(direction_in_face[0]),
+                v: pixel(direction_in_face[1]),
+                direction_in_face,
+            });
+        }
+
+        LidarSimulator {
+            beam_pattern,
+            properties,
+            faces,
+            lookup,
+        }
+    }
+
+    /// beam pattern
+    pub fn beam_pattern(&self) -> &BeamPattern {
+        &self.beam_pattern
+    }
+
+    /// update scene renderables
+    pub fn update_3d_renderables(&mut self, renderables: Vec<SceneRenderable>) {
+        for face in &mut self.faces {
+            face.simulator.update_3d_renderables(renderables.clone());
+        }
+    }
+
+    /// delete scene renderables by name
+    pub fn delete_3d_renderables(&mut self, names: &[String]) {
+        for face in &mut self.faces {
+            face.simulator.delete_3d_renderables(names);
+        }
+    }
+
+    /// clear all scene renderables
+    pub fn clear_3d_renderables(&mut self) {
+        for face in &mut self.faces {
+            face.simulator.clear_3d_renderables();
+        }
+    }
+
+    /// simulate a scan, starting at `start_time`
+    pub fn scan(&mut self, world_from_lidar: Isometry3F64, start_time: f64) -> LidarScan {
+        let images: Vec<_> = self
+            .faces
+            .iter_mut()
+            .map(|face| {
+                face.simulator
+                    .render(world_from_lidar.group_mul(&face.lidar_from_face))
+            })
+            .collect();
+        let metric_depths: Vec<_> = images
+            .iter()
+            .map(|image| image.depth_image.metric_depth())
+            .collect();
+
+        let mut points = Vec::new();
+        for (beam_index, (beam, lookup)) in
+            self.beam_pattern.beams.iter().zip(&self.lookup).enumerate()
+        {
+            let image = &images[lookup.face];
+            if image.depth_image.ndc_z_image.pixel(lookup.u, lookup.v) >= 1.0 {
+                continue;
+            }
+            // the depth is the distance along the face camera axis
+            let z = metric_depths[lookup.face].pixel(lookup.u, lookup.v) as f64;
+            let point_in_face = lookup.direction_in_face * z;
+            let range = point_in_face.norm();
+            if range < self.properties.min_range || range > self.properties.max_range {
+                continue;
+            }
+
+            let rgba = image.rgba_image.pixel(lookup.u, lookup.v);
+            let brightness =
+                (0.299 * rgba[0] as f32 + 0.587 * rgba[1] as f32 + 0.114 * rgba[2] as f32) / 255.0;
+            let normal = image
+                .normal_image
+                .as_ref()
+                .unwrap()
+                .pixel(lookup.u, lookup.v);
+            // points and lines have no normal, they reflect as if hit head-on
+            let cos_incidence = if normal[3] > 0.0 {
+                -(normal[0] as f64 * point_in_face[0]
+                    + normal[1] as f64 * point_in_face[1]
+                    + normal[2] as f64 * point_in_face[2])
+                    / range
+            } else {
+                1.0
+            };
+
+            points.push(LidarPoint {
+                beam_index,
+                point_in_lidar: beam.direction() * range,
+                range,
+                intensity: brightness * cos_incidence.clamp(0.0, 1.0) as f32,
+                timestamp: start_time + beam.time_offset,
+            });
+        }
+
+        LidarScan {
+            world_from_lidar,
+            points,
+        }
+    }
+}
@@ -0,0 +1,356 @@
+use dog_tv_renderer::textures::depth_image::DepthImage;
+use sophus::core::linalg::SVec;
+use sophus::image::arc_image::ArcImage4U8;
+use sophus::image::arc_image::ArcImageF32;
+use sophus::image::mut_image::MutImage4U8;
+use sophus::image::mut_image::MutImageF32;
+use sophus::image::prelude::IsImageView;
+use sophus::image::prelude::IsMutImageView;
+
+/// Small seedable random number generator, xoshiro256++ seeded by splitmix64
+///
+/// The sequence only depends on the seed, on all platforms.
+#[derive(Clone, Debug)]
+pub struct SimRng {
+    state: [u64; 4],
+}
+
+impl SimRng {
+    /// new generator from seed
+    pub fn new(seed: u64) -> Self {
+        let mut x = seed;
+        let mut splitmix64 = || {
+            x = x.wrapping_add(0x9e3779b97f4a7c15);
+            let mut z = x;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+            z ^ (z >> 31)
+        };
+        SimRng {
+            state: [splitmix64(), splitmix64(), splitmix64(), splitmix64()],
+        }
+    }
+
+    /// next 64 random bits
+    pub fn next_u64(&mut self) -> u64 {
+        let s = &mut self.state;
+        let result = s[0].wrapping_add(s[3]).rotate_left(23).wrapping_add(s[0]);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        result
+    }
+
+    /// uniform sample in [0, 1)
+    pub fn uniform(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    /// standard normal sample
+    pub fn gaussian(&mut self) -> f64 {
+        // Box-Muller, 1 - u is in (0, 1] and hence the log is finite
+        let u = 1.0 - self.uniform();
+        let v = self.uniform();
+        (-2.0 * u.ln()).sqrt() * (core::f64::consts::TAU * v).cos()
+    }
+
+    /// Poisson sample with the given mean
+    pub fn poisson(&mut self, mean: f64) -> f64 {
+        if mean <= 0.0 {
+            return 0.0;
+        }
+        if mean > 30.0 {
+            // normal approximation, exact enough for shot noise
+            return (mean + mean.sqrt() * self.gaussian()).round().max(0.0);
+        }
+        // Knuth's algorithm
+        let limit = (-mean).exp();
+        let mut count = 0.0;
+        let mut product = self.uniform();
+        while product > limit {
+            count += 1.0;
+            product *= self.uniform();
+        }
+        count
+    }
+}
+
+/// Photometric camera model, applied to the clean rendered colors
+#[derive(Clone, Copy, Debug)]
+pub struct PhotometricModel {
+    /// exposure gain, applied in linear space
+    pub exposure: f64,
+    /// per channel gain of red, green and blue
+    pub white_balance: [f64; 3],
+    /// vignetting strength, the gain falls off as `1 - vignetting * r^2` with `r` the distance to
+    /// the image center normalized by the half diagonal
+    pub vignetting: f64,
+    /// display gamma, the output is `linear^(1/gamma)`
+    pub gamma: f64,
+}
+
+impl Default for PhotometricModel {
+    fn default() -> Self {
+        Self {
+            exposure: 1.0,
+            white_balance: [1.0; 3],
+            vignetting: 0.0,
+            gamma: 1.0,
+        }
+    }
+}
+
+/// Pixel noise
+///
+/// The shot noise is applied to the linear intensity, before the gamma of the photometric model,
+/// the read noise to the final 8-bit intensity.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PixelNoiseModel {
+    /// standard deviation of the additive Gaussian read noise, in 8-bit intensity units
+    pub gaussian_sigma: f64,
+    /// number of photons at full linear intensity for Poisson shot noise, zero disables it
+    ///
+    /// The smaller, the noisier.
+    pub full_well_capacity: f64,
+}
+
+/// Depth sensor model
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DepthNoiseModel {
+    /// constant part of the depth standard deviation, in meters
+    pub sigma_constant: f64,
+    /// quadratic part of the depth standard deviation, i.e. `sigma = c + q * z^2`
+    pub sigma_quadratic: f64,
+    /// depth quantization step in meters, zero disables quantization
+    pub quantization: f64,
+    /// probability of a pixel to have no depth
+    pub dropout_probability: f64,
+}
+
+/// Seedable sensor noise of the camera simulator
+///
+/// The default adds no noise at all.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SensorNoise {
+    /// seed of the random number generator
+    pub seed: u64,
+    /// photometric model
+    pub photometric: PhotometricModel,
+    /// pixel noise
+    pub pixel: PixelNoiseModel,
+    /// depth noise
+    pub depth: DepthNoiseModel,
+}
+
+impl SensorNoise {
+    /// apply photometric model and pixel noise to rgba image, alpha is kept as is
+    pub fn apply_to_rgba(&self, rng: &mut SimRng, image: &ArcImage4U8) -> ArcImage4U8 {
+        let size = image.image_size();
+        let half_width = 0.5 * size.width as f64;
+        let half_height = 0.5 * size.height as f64;
+        let half_diagonal_squared = half_width * half_width + half_height * half_height;
+        let photometric = &self.photometric;
+
+        let mut noisy = MutImage4U8::from_image_size(size);
+        for v in 0..size.height {
+            for u in 0..size.width {
+                let pixel = image.pixel(u, v);
+
+                let du = u as f64 + 0.5 - half_width;
+                let dv = v as f64 + 0.5 - half_height;
+                let vignetting = (1.0
+                    - photometric.vignetting * (du * du + dv * dv) / half_diagonal_squared)
+                    .max(0.0);
+
+                let mut rgba = SVec::<u8, 4>::new(0, 0, 0, pixel[3]);
+                for c in 0..3 {
+                    let linear = pixel[c] as f64 / 255.0
+                        * photometric.exposure
+                        * photometric.white_balance[c]
+                        * vignetting;
+                    let mut linear = linear.clamp(0.0, 1.0);
+                    if self.pixel.full_well_capacity > 0.0 {
+                        // photons are counted before the gamma, saturating at the full well
+                        linear = (rng.poisson(linear * self.pixel.full_well_capacity)
+                            / self.pixel.full_well_capacity)
+                            .min(1.0);
+                    }
+                    let mut value = 255.0 * linear.powf(1.0 / photometric.gamma);
+                    if self.pixel.gaussian_sigma > 0.0 {
+                        value += self.pixel.gaussian_sigma * rng.gaussian();
+                    }
+                    rgba[c] = value.round().clamp(0.0, 255.0) as u8;
+                }
+                *noisy.mut_pixel(u, v) = rgba;
+            }
+        }
+        ArcImage4U8::from(noisy)
+    }
+
+    /// apply depth noise, quantization and dropouts
+    ///
+    /// Dropped out pixels are set to the far plane, i.e. they look like background.
+    pub fn apply_to_depth(&self, rng: &mut SimRng, depth_image: &DepthImage) -> DepthImage {
+        let clipping_planes = depth_image.clipping_planes.cast::<f64>();
+        let size = depth_image.ndc_z_image.image_size();
+        let depth = &self.depth;
+
+        let mut noisy = MutImageF32::from_image_size(size);
+        for v in 0..size.height {
+            for u in 0..size.width {
+                let ndc_z = depth_image.ndc_z_image.pixel(u, v);
+                *noisy.mut_pixel(u, v) = if ndc_z >= 1.0 {
+                    ndc_z
+                } else if depth.dropout_probability > 0.0
+                    && rng.uniform() < depth.dropout_probability
+                {
+                    1.0
+                } else {
+                    let mut z = clipping_planes.metric_z_from_ndc_z(ndc_z as f64);
+                    let sigma = depth.sigma_constant + depth.sigma_quadratic * z * z;
+                    if sigma > 0.0 {
+                        z += sigma * rng.gaussian();
+                    }
+                    if depth.quantization > 0.0 {
+                        z = (z / depth.quantization).round() * depth.quantization;
+                    }
+                    let z = z.clamp(clipping_planes.near, clipping_planes.far);
+                    clipping_planes.ndc_z_from_metric_z(z) as f32
+                };
+            }
+        }
+        DepthImage::new(ArcImageF32::from(noisy), depth_image.clipping_planes)
+    }
+}
+
+#[test]
+fn sensor_noise_is_reproducible() {
+    let noise = SensorNoise {
+        seed: 42,
+        pixel: PixelNoiseModel {
+            gaussian_sigma: 3.0,
+            full_well_capacity: 100.0,
+        },
+        ..Default::default()
+    };
+    let mut clean = MutImage4U8::from_image_size(sophus::image::ImageSize::new(8, 4));
+    *clean.mut_pixel(3, 2) = SVec::<u8, 4>::new(100, 150, 200, 255);
+    let clean = ArcImage4U8::from(clean);
+
+    let mut rng = SimRng::new(noise.seed);
+    let first = noise.apply_to_rgba(&mut rng, &clean);
+    let mut rng = SimRng::new(noise.seed);
+    let second = noise.apply_to_rgba(&mut rng, &clean);
+
+    for v in 0..4 {
+        for u in 0..8 {
+            assert_eq!(first.pixel(u, v), second.pixel(u, v));
+        }
+    }
+}
+
+#[cfg(test)]
+fn uniform_image(value: u8) -> ArcImage4U8 {
+    ArcImage4U8::from(MutImage4U8::from_image_size_and_val(
+        sophus::image::ImageSize::new(64, 64),
+        SVec::<u8, 4>::new(value, value, value, 255),
+    ))
+}
+
+#[test]
+fn sensor_noise_depends_on_seed() {
+    let noise = SensorNoise {
+        seed: 42,
+        pixel: PixelNoiseModel {
+            gaussian_sigma: 3.0,
+            full_well_capacity: 100.0,
+        },
+        ..Default::default()
+    };
+    let clean = uniform_image(128);
+    let first = noise.apply_to_rgba(&mut SimRng::new(42), &clean);
+    let other_seed = noise.apply_to_rgba(&mut SimRng::new(43), &clean);
+
+    let mut changed = 0;
+    let mut different = 0;
+    for v in 0..64 {
+        for u in 0..64 {
+            changed += (first.pixel(u, v)[0] != 128) as usize;
+            different += (first.pixel(u, v) != other_seed.pixel(u, v)) as usize;
+            assert_eq!(first.pixel(u, v)[3], 255);
+        }
+    }
+    // the shot noise of about 50 photons alone changes almost every pixel
+    assert!(changed > 64 * 64 / 2);
+    assert!(different > 64 * 64 / 2);
+}
+
+#[test]
+fn shot_noise_is_applied_before_gamma() {
+    let gamma = 2.2;
+    let full_well_capacity = 1000.0;
+    let noise = SensorNoise {
+        photometric: PhotometricModel {
+            gamma,
+            ..Default::default()
+        },
+        pixel: PixelNoiseModel {
+            gaussian_sigma: 0.0,
+            full_well_capacity,
+        },
+        ..Default::default()
+    };
+    let noisy = noise.apply_to_rgba(&mut SimRng::new(7), &uniform_image(64));
+    let values = || (0..64 * 64).map(|i| noisy.pixel(i % 64, i / 64)[0] as f64);
+    let mean = values().sum::<f64>() / (64.0 * 64.0);
+    let std = (values().map(|x| (x - mean).powi(2)).sum::<f64>() / (64.0 * 64.0)).sqrt();
+
+    // The photon count has the mean and variance of the linear intensity times the full well
+    // capacity, the gamma scales its standard deviation by the derivative of the gamma curve.
+    let linear = 64.0_f64 / 255.0;
+    let expected_mean = 255.0 * linear.powf(1.0 / gamma);
+    let expected_std =
+        255.0 * linear.powf(1.0 / gamma - 1.0) / gamma * (linear / full_well_capacity).sqrt();
+    assert!((mean - expected_mean).abs() < 0.5);
+    assert!((std / expected_std - 1.0).abs() < 0.1);
+}
+
+#[test]
+fn depth_dropouts_follow_probability() {
+    use dog_tv_renderer::camera::clipping_planes::ClippingPlanesF32;
+
+    let noise = SensorNoise {
+        depth: DepthNoiseModel {
+            dropout_probability: 0.2,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let size = sophus::image::ImageSize::new(100, 100);
+    let depth_image = DepthImage::new(
+        ArcImageF32::from(MutImageF32::from_image_size_and_val(size, 0.5)),
+        ClippingPlanesF32 {
+            near: 1.0,
+            far: 100.0,
+        },
+    );
+    let noisy = noise.apply_to_depth(&mut SimRng::new(1), &depth_image);
+
+    let mut dropouts = 0;
+    for v in 0..size.height {
+        for u in 0..size.width {
+            let ndc_z = noisy.ndc_z_image.pixel(u, v);
+            if ndc_z >= 1.0 {
+                dropouts += 1;
+            } else {
+                assert!((ndc_z - 0.5).abs() < 1e-6);
+            }
+        }
+    }
+    let rate = dropouts as f64 / (size.width * size.height) as f64;
+    assert!((rate - 0.2).abs() < 0.02);
+}
@@ -0,0 +1,323 @@
+use crate::camera_simulator::CameraSimulator;
+use crate::camera_simulator::SimulatedImage;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use dog_tv_renderer::background::Background;
+use dog_tv_renderer::camera::properties::RenderCameraProperties;
+use dog_tv_renderer::lighting::SceneLighting;
+use dog_tv_renderer::offscreen_renderer::OffscreenCamera;
+use dog_tv_renderer::renderables::scene_renderable::SceneRenderable;
+use dog_tv_renderer::RenderContext;
+use sophus::core::linalg::MatF64;
+use sophus::core::linalg::VecF64;
+use sophus::image::arc_image::ArcImageF32;
+use sophus::image::mut_image::MutImageF32;
+use sophus::image::prelude::IsImageView;
+use sophus::image::prelude::IsMutImageView;
+use sophus::image::ImageSize;
+use sophus::lie::traits::IsTranslationProductGroup;
+use sophus::lie::Isometry3F64;
+use sophus::lie::Rotation3F64;
+use sophus::sensor::dyn_camera::DynCameraF64;
+
+extern crate alloc;
+
+/// Camera of a rig
+#[derive(Clone, Debug)]
+pub struct RigCamera {
+    /// unique name
+    pub name: String,
+    /// intrinsics and clipping planes
+    pub camera_properties: RenderCameraProperties,
+    /// pose of the camera in the rig frame
+    pub rig_from_camera: Isometry3F64,
+}
+
+/// Rectified stereo pair, rendered by two virtual cameras
+///
+/// Both virtual cameras share the same undistorted pinhole intrinsics and orientation, with the
+/// x axis along the baseline. Hence, a point at depth `z` appears at the same row in both images,
+/// shifted by the disparity `fx * baseline / z`.
+pub struct RectifiedStereo {
+    /// name of the left camera
+    pub left_name: String,
+    /// name of the right camera
+    pub right_name: String,
+    /// rectified intrinsics, shared by both images
+    pub camera_properties: RenderCameraProperties,
+    /// distance between the two camera centers
+    pub baseline: f64,
+    /// pose of the rectified left camera in the rig frame
+    pub rig_from_rectified_left: Isometry3F64,
+    /// rectified left image
+    pub left: SimulatedImage,
+    /// rectified right image
+    pub right: SimulatedImage,
+    /// ground-truth disparity of the left image in pixels, zero where there is no depth
+    pub disparity: ArcImageF32,
+}
+
+/// Images of all rig cameras, in the order the cameras were given
+pub struct RigImages {
+    /// per camera images, by camera name
+    pub images: Vec<(String, SimulatedImage)>,
+    /// rectified stereo pairs, see `RigSimulator::add_stereo_pair`
+    pub stereo: Vec<RectifiedStereo>,
+}
+
+impl RigImages {
+    /// image of the named camera
+    pub fn image(&self, name: &str) -> Option<&SimulatedImage> {
+        self.images
+            .iter()
+            .find(|(camera_name, _)| camera_name == name)
+            .map(|(_, image)| image)
+    }
+}
+
+/// Error when creating a rig simulator
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RigError {
+    /// Two rig cameras share the given name
+    DuplicateCameraName(String),
+}
+
+impl fmt::Display for RigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RigError::DuplicateCameraName(name) => write!(f, "duplicate rig camera name: {}", name),
+        }
+    }
+}
+
+/// Error when adding a stereo pair
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StereoPairError {
+    /// There is no rig camera of the given name
+    UnknownCamera(String),
+    /// Both cameras share the same center, hence there is no baseline
+    CoincidingCenters,
+}
+
+impl fmt::Display for StereoPairError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StereoPairError::UnknownCamera(name) => write!(f, "unknown rig camera: {}", name),
+            StereoPairError::CoincidingCenters => {
+                write!(f, "stereo cameras must not share their center")
+            }
+        }
+    }
+}
+
+struct StereoPair {
+    left_name: String,
+    right_name: String,
+    camera_properties: RenderCameraProperties,
+    baseline: f64,
+    rig_from_rectified_left: Isometry3F64,
+    left: OffscreenCamera,
+    right: OffscreenCamera,
+}
+
+/// Simulator of a multi-camera rig
+///
+/// All cameras are rendered from a single `world_from_rig` pose. The renderables are uploaded
+/// once and shared by all cameras, as are the lighting and background, hence all cameras see the
+/// same scene.
+pub struct RigSimulator {
+    render_context: RenderContext,
+    // renders the shared scene into the textures of each camera
+    simulator: CameraSimulator,
+    cameras: Vec<(RigCamera, OffscreenCamera)>,
+    stereo: Vec<StereoPair>,
+}
+
+impl RigSimulator {
+    /// new rig simulator, camera names must be unique
+    pub fn new(render_context: &RenderContext, cameras: Vec<RigCamera>) -> Result<Self, RigError> {
+        for (i, camera) in cameras.iter().enumerate() {
+            if cameras[..i].iter().any(|other| other.name == camera.name) {
+                return Err(RigError::DuplicateCameraName(camera.name.clone()));
+            }
+        }
+        Ok(RigSimulator {
+            render_context: render_context.clone(),
+            // the own render targets are not used, hence they are kept minimal
+            simulator: CameraSimulator::new(
+                render_context,
+                &RenderCameraProperties::default_from(ImageSize::new(1, 1)),
+            ),
+            cameras: cameras
+                .into_iter()
+                .map(|camera| {
+                    let target = OffscreenCamera::new(render_context, &camera.camera_properties);
+                    (camera, target)
+                })
+                .collect(),
+            stereo: Vec::new(),
+        })
+    }
+
+    /// rig cameras
+    pub fn cameras(&self) -> impl Iterator<Item = &RigCamera> {
+        self.cameras.iter().map(|(camera, _)| camera)
+    }
+
+    /// Also render the two named cameras as rectified stereo pair
+    ///
+    /// The rectified intrinsics are the pinhole part of the left camera. The pair sees the same
+    /// renderables, lighting and background as the rig cameras.
+    pub fn add_stereo_pair(
+        &mut self,
+        left_name: &str,
+        right_name: &str,
+    ) -> Result<(), StereoPairError> {
+        let find = |name: &str| {
+            self.cameras
+                .iter()
+                .map(|(camera, _)| camera)
+                .find(|camera| camera.name == name)
+                .ok_or_else(|| StereoPairError::UnknownCamera(name.into()))
+        };
+        let left = find(left_name)?;
+        let right = find(right_name)?;
+
+        let left_center = left.rig_from_camera.translation();
+        let baseline_vector = right.rig_from_camera.translation() - left_center;
+        let baseline = baseline_vector.norm();
+        if baseline <= 0.0 {
+            return Err(StereoPairError::CoincidingCenters);
+        }
+
+        // x along the baseline, z as close as possible to the mean optical axis
+        let x = baseline_vector / baseline;
+        let optical_axis = |camera: &RigCamera| {
+            camera
+                .rig_from_camera
+                .rotation()
+                .matrix()
+                .column(2)
+                .into_owned()
+        };
+        let mean_z = optical_axis(left) + optical_axis(right);
+        let z = (mean_z - x * x.dot(&mean_z)).normalize();
+        let y = z.cross(&x);
+        let rig_from_rectified =
+            Rotation3F64::from_rotation_matrix(&MatF64::<3, 3>::from_columns(&[x, y, z]));
+
+        let pinhole = left.camera_properties.intrinsics.pinhole_model();
+        let params = pinhole.params();
+        let camera_properties = RenderCameraProperties::new(
+            DynCameraF64::new_pinhole(
+                &VecF64::<4>::new(params[0], params[1], params[2], params[3]),
+                left.camera_properties.intrinsics.image_size(),
+            ),
+            left.camera_properties.clipping_planes,
+        );
+
+        self.stereo.push(StereoPair {
+            left_name: left.name.clone(),
+            right_name: right.name.clone(),
+            camera_properties: camera_properties.clone(),
+            baseline,
+            rig_from_rectified_left: Isometry3F64::from_translation_and_rotation(
+                &left_center,
+                &rig_from_rectified,
+            ),
+            left: OffscreenCamera::new(&self.render_context, &camera_properties),
+            right: OffscreenCamera::new(&self.render_context, &camera_properties),
+        });
+        Ok(())
+    }
+
+    /// update scene renderables of all cameras
+    pub fn update_3d_renderables(&mut self, renderables: Vec<SceneRenderable>) {
+        self.simulator.update_3d_renderables(renderables);
+    }
+
+    /// delete scene renderables by name
+    pub fn delete_3d_renderables(&mut self, names: &[String]) {
+        self.simulator.delete_3d_renderables(names);
+    }
+
+    /// clear all scene renderables
+    pub fn clear_3d_renderables(&mut self) {
+        self.simulator.clear_3d_renderables();
+    }
+
+    /// set the lighting of all cameras
+    pub fn set_lighting(&mut self, lighting: SceneLighting) {
+        self.simulator.set_lighting(lighting);
+    }
+
+    /// set the background of all cameras
+    pub fn set_background(&mut self, background: Background) {
+        self.simulator.set_background(background);
+    }
+
+    /// also render ground-truth normal and entity id images of all cameras
+    pub fn set_ground_truth(&mut self, ground_truth: bool) {
+        self.simulator.set_ground_truth(ground_truth);
+    }
+
+    /// render all cameras and stereo pairs
+    pub fn render(&mut self, world_from_rig: Isometry3F64) -> RigImages {
+        let images = self
+            .cameras
+            .iter_mut()
+            .map(|(camera, target)| {
+                (
+                    camera.name.clone(),
+                    self.simulator
+                        .render_camera(target, world_from_rig.group_mul(&camera.rig_from_camera)),
+                )
+            })
+            .collect();
+
+        let stereo = self
+            .stereo
+            .iter_mut()
+            .map(|stereo| {
+                let world_from_left = world_from_rig.group_mul(&stereo.rig_from_rectified_left);
+                let world_from_right = world_from_left.group_mul(&Isometry3F64::from_translation(
+                    &VecF64::<3>::new(stereo.baseline, 0.0, 0.0),
+                ));
+                let left = self
+                    .simulator
+                    .render_camera(&mut stereo.left, world_from_left);
+                let right = self
+                    .simulator
+                    .render_camera(&mut stereo.right, world_from_right);
+
+                let fx = stereo.camera_properties.intrinsics.pinhole_model().params()[0];
+                let ndc_z_image = &left.depth_image.ndc_z_image;
+                let metric_depth = left.depth_image.metric_depth();
+                let mut disparity = MutImageF32::from_image_size(metric_depth.image_size());
+                for v in 0..metric_depth.image_size().height {
+                    for u in 0..metric_depth.image_size().width {
+                        *disparity.mut_pixel(u, v) = if ndc_z_image.pixel(u, v) < 1.0 {
+                            (fx * stereo.baseline) as f32 / metric_depth.pixel(u, v)
+                        } else {
+                            0.0
+                        };
+                    }
+                }
+
+                RectifiedStereo {
+                    left_name: stereo.left_name.clone(),
+                    right_name: stereo.right_name.clone(),
+                    camera_properties: stereo.camera_properties.clone(),
+                    baseline: stereo.baseline,
+                    rig_from_rectified_left: stereo.rig_from_rectified_left,
+                    left,
+                    right,
+                    disparity: ArcImageF32::from(disparity),
+                }
+            })
+            .collect();
+
+        RigImages { images, stereo }
+    }
+}
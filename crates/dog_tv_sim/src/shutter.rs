@@ -0,0 +1,43 @@
+use sophus::lie::Isometry3F64;
+
+/// Shutter model of a moving camera, see `CameraSimulator::render_trajectory`
+#[derive(Clone, Copy, Debug)]
+pub struct ShutterModel {
+    /// exposure time of each row, in seconds
+    pub exposure_time: f64,
+    /// delay between the exposure start of two consecutive rows, zero for a global shutter
+    pub line_delay: f64,
+    /// number of row bands rendered with their own pose, if the line delay is non-zero
+    ///
+    /// Rendering each row separately is exact but slow, bands approximate the rolling shutter
+    /// piecewise. Each band is a full frame render, times `blur_samples`: the default of 32 bands
+    /// costs 32 renders per image without motion blur, and 32 * `blur_samples` renders with it.
+    pub row_bands: usize,
+    /// number of sub-frames averaged over the exposure for motion blur, 1 disables it
+    pub blur_samples: usize,
+}
+
+impl Default for ShutterModel {
+    fn default() -> Self {
+        Self {
+            exposure_time: 0.0,
+            line_delay: 0.0,
+            row_bands: 32,
+            blur_samples: 1,
+        }
+    }
+}
+
+impl ShutterModel {
+    /// time from the exposure start of the first row to the exposure end of the last row
+    pub fn readout_duration(&self, image_height: usize) -> f64 {
+        image_height.saturating_sub(1) as f64 * self.line_delay + self.exposure_time
+    }
+}
+
+/// Pose at `fraction` between `start` (0.0) and `end` (1.0), at constant velocity
+pub fn interpolate_pose(start: &Isometry3F64, end: &Isometry3F64, fraction: f64) -> Isometry3F64 {
+    start.group_mul(&Isometry3F64::exp(
+        &(start.inverse().group_mul(end).log() * fraction),
+    ))
+}
@@ -0,0 +1,296 @@
+use crate::preludes::*;
+use dog_tv_renderer::types::RenderResult;
+use log::warn;
+use sophus::image::arc_image::ArcImage4U8;
+use sophus::image::arc_image::ArcImageF32;
+use sophus::image::image_view::IsImageView;
+use sophus::image::io::png::save_as_png;
+use sophus::image::io::tiff::save_as_tiff;
+use std::format;
+use std::io;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::SystemTime;
+
+extern crate std;
+
+/// File format of exported frames
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FrameExportFormat {
+    /// rgba image as png
+    #[default]
+    Png,
+    /// rgba image as png, plus metric depth image as f32 tiff
+    PngAndDepthTiff,
+}
+
+impl FrameExportFormat {
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            FrameExportFormat::Png => "png",
+            FrameExportFormat::PngAndDepthTiff => "png + depth tiff",
+        }
+    }
+}
+
+struct FrameSequence {
+    view_label: String,
+    format: FrameExportFormat,
+    next_index: usize,
+}
+
+/// Number of frames queued for writing, before rendering waits for the writer thread
+const MAX_QUEUED_FRAMES: usize = 8;
+
+// Frame to be written by the writer thread
+struct FrameJob {
+    view_label: String,
+    is_sequence: bool,
+    rgba_path: String,
+    rgba_image: ArcImage4U8,
+    // metric depth, for `FrameExportFormat::PngAndDepthTiff`
+    maybe_depth: Option<(String, ArcImageF32)>,
+}
+
+impl FrameJob {
+    fn write(&self) -> io::Result<()> {
+        save_as_png(&self.rgba_image.image_view(), &self.rgba_path)?;
+        if let Some((path, depth_image)) = &self.maybe_depth {
+            save_as_tiff(&depth_image.image_view(), path)?;
+        }
+        Ok(())
+    }
+}
+
+struct FrameWriteError {
+    view_label: String,
+    is_sequence: bool,
+    err: io::Error,
+}
+
+// Thread encoding and writing the frames, so that rendering does not wait for it
+struct FrameWriter {
+    jobs: Option<mpsc::SyncSender<FrameJob>>,
+    errors: mpsc::Receiver<FrameWriteError>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl FrameWriter {
+    fn spawn() -> Self {
+        let (job_send, job_recv) = mpsc::sync_channel::<FrameJob>(MAX_QUEUED_FRAMES);
+        let (error_send, error_recv) = mpsc::channel();
+        let thread = thread::spawn(move || {
+            for job in job_recv {
+                if let Err(err) = job.write() {
+                    let _ = error_send.send(FrameWriteError {
+                        view_label: job.view_label,
+                        is_sequence: job.is_sequence,
+                        err,
+                    });
+                }
+            }
+        });
+        FrameWriter {
+            jobs: Some(job_send),
+            errors: error_recv,
+            thread: Some(thread),
+        }
+    }
+}
+
+impl Drop for FrameWriter {
+    fn drop(&mut self) {
+        // closes the channel, the thread finishes the queued frames first
+        self.jobs = None;
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Writes screenshots and frame sequences of views into a directory
+///
+/// File names are derived from the view label, e.g. `scene_000042.png` and
+/// `scene_000042_depth.tiff` for a frame sequence of the view "scene". The images are encoded
+/// and written on a separate thread, write errors are reported by `begin_frame`.
+pub(crate) struct FrameExporter {
+    pub(crate) directory: PathBuf,
+    pub(crate) format: FrameExportFormat,
+    pending_screenshots: Vec<String>,
+    sequence: Option<FrameSequence>,
+    writer: Option<FrameWriter>,
+}
+
+impl FrameExporter {
+    pub(crate) fn new() -> Self {
+        FrameExporter {
+            directory: PathBuf::from("."),
+            format: FrameExportFormat::default(),
+            pending_screenshots: Vec::new(),
+            sequence: None,
+            writer: None,
+        }
+    }
+
+    /// Reports write errors of previous frames and drops screenshot requests and the frame
+    /// sequence of views which are not rendered, e.g. because they were removed
+    ///
+    /// The frame sequence is stopped on the first write error.
+    pub(crate) fn begin_frame(&mut self, is_rendered_view: impl Fn(&str) -> bool) {
+        self.pending_screenshots.retain(|view_label| {
+            let is_rendered = is_rendered_view(view_label);
+            if !is_rendered {
+                warn!("Dropped screenshot of {}: no such view", view_label);
+            }
+            is_rendered
+        });
+        if let Some(view_label) = self.sequence_view_label() {
+            if !is_rendered_view(view_label) {
+                warn!("Stopped frame export of {}: no such view", view_label);
+                self.sequence = None;
+            }
+        }
+
+        let Some(writer) = self.writer.as_ref() else {
+            return;
+        };
+        for error in writer.errors.try_iter().collect::<Vec<_>>() {
+            if !error.is_sequence {
+                warn!(
+                    "Failed to save screenshot of {}: {}",
+                    error.view_label, error.err
+                );
+            } else if self.sequence_view_label() == Some(error.view_label.as_str()) {
+                warn!(
+                    "Stopped frame export of {}: {}",
+                    error.view_label, error.err
+                );
+                self.sequence = None;
+            }
+        }
+    }
+
+    /// Request a png screenshot of the next rendered frame of the view
+    pub(crate) fn request_screenshot(&mut self, view_label: &str) {
+        self.pending_screenshots.push(view_label.to_string());
+    }
+
+    pub(crate) fn start_sequence(&mut self, view_label: &str) {
+        self.sequence = Some(FrameSequence {
+            view_label: view_label.to_string(),
+            format: self.format,
+            next_index: 0,
+        });
+    }
+
+    pub(crate) fn stop_sequence(&mut self) {
+        self.sequence = None;
+    }
+
+    pub(crate) fn sequence_view_label(&self) -> Option<&str> {
+        self.sequence
+            .as_ref()
+            .map(|sequence| sequence.view_label.as_str())
+    }
+
+    /// Does the next rendered frame of the view need to be downloaded
+    pub(crate) fn wants_frame(&self, view_label: &str) -> bool {
+        self.sequence_view_label() == Some(view_label)
+            || self.pending_screenshots.iter().any(|l| l == view_label)
+    }
+
+    /// Queue the rendered frame of the view for writing, if requested
+    ///
+    /// The frame sequence is stopped if the frame could not be queued.
+    pub(crate) fn export(&mut self, view_label: &str, render_result: &RenderResult) {
+        if let Some(index) = self
+            .pending_screenshots
+            .iter()
+            .position(|l| l == view_label)
+        {
+            self.pending_screenshots.remove(index);
+            let millis = SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map_or(0, |duration| duration.as_millis());
+            let stem = format!("{}_{}", file_stem(view_label), millis);
+            if let Err(err) = self.queue(
+                view_label,
+                false,
+                &stem,
+                FrameExportFormat::Png,
+                render_result,
+            ) {
+                warn!("Failed to save screenshot of {}: {}", view_label, err);
+            }
+        }
+
+        let Some(sequence) = self.sequence.as_mut() else {
+            return;
+        };
+        if sequence.view_label != view_label {
+            return;
+        }
+        let stem = format!("{}_{:06}", file_stem(view_label), sequence.next_index);
+        let format = sequence.format;
+        sequence.next_index += 1;
+        if let Err(err) = self.queue(view_label, true, &stem, format, render_result) {
+            warn!("Stopped frame export of {}: {}", view_label, err);
+            self.sequence = None;
+        }
+    }
+
+    fn queue(
+        &mut self,
+        view_label: &str,
+        is_sequence: bool,
+        stem: &str,
+        format: FrameExportFormat,
+        render_result: &RenderResult,
+    ) -> io::Result<()> {
+        let rgba_image = render_result
+            .rgba_image
+            .clone()
+            .ok_or_else(|| io::Error::other("rgba image was not downloaded"))?;
+        let job = FrameJob {
+            view_label: view_label.to_string(),
+            is_sequence,
+            rgba_path: self.file_path(&format!("{}.png", stem)),
+            rgba_image,
+            maybe_depth: (format == FrameExportFormat::PngAndDepthTiff).then(|| {
+                (
+                    self.file_path(&format!("{}_depth.tiff", stem)),
+                    render_result.depth_image.metric_depth(),
+                )
+            }),
+        };
+        self.writer
+            .get_or_insert_with(FrameWriter::spawn)
+            .jobs
+            .as_ref()
+            .and_then(|jobs| jobs.send(job).ok())
+            .ok_or_else(|| io::Error::other("frame writer thread stopped"))
+    }
+
+    fn file_path(&self, file_name: &str) -> String {
+        self.directory
+            .join(file_name)
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+/// View label with all characters, which might be problematic in file names, replaced by '_'
+fn file_stem(view_label: &str) -> String {
+    view_label
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
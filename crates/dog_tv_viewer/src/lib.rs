@@ -4,18 +4,35 @@
 
 //! Simple viewer for 2D and 3D visualizations.
 
+/// Screenshots and frame sequences of views
+#[cfg(all(feature = "std", feature = "viewer"))]
+pub mod frame_export;
 /// Interactions
+#[cfg(feature = "viewer")]
 pub mod interactions;
 /// The view packets.
 pub mod packets;
+/// Scene entities under the cursor
+#[cfg(feature = "viewer")]
+pub mod picking;
+/// Recording and replay of packets
+#[cfg(feature = "recording")]
+pub mod recording;
+/// Packet server and client, to stream packets from other processes
+#[cfg(feature = "remote")]
+pub mod remote;
 /// eframea app impl
+#[cfg(feature = "viewer")]
 pub mod simple_viewer;
 /// Viewer base
+#[cfg(feature = "viewer")]
 pub mod viewer_base;
 /// The view struct.
+#[cfg(feature = "viewer")]
 pub mod views;
 
 /// eframe native options - recommended for use with the dog-tv
+#[cfg(feature = "viewer")]
 pub fn recommened_eframe_native_options() -> eframe::NativeOptions {
     eframe::NativeOptions {
         viewport: eframe::egui::ViewportBuilder::default().with_inner_size([850.0, 480.0]),
@@ -27,7 +44,9 @@ pub fn recommened_eframe_native_options() -> eframe::NativeOptions {
 
 /// preludes
 pub mod preludes {
+    #[cfg(feature = "viewer")]
     pub(crate) use alloc::boxed::Box;
+    #[cfg(feature = "viewer")]
     pub(crate) use alloc::collections::btree_map::BTreeMap;
     pub(crate) use alloc::collections::vec_deque::VecDeque;
     pub(crate) use alloc::string::String;
@@ -5,6 +5,7 @@ use crate::packets::scene_view_packet::SceneViewPacket;
 use crate::packets::scene_view_packet::SceneViewPacketContent;
 use crate::preludes::*;
 use dog_tv_renderer::camera::RenderCamera;
+use dog_tv_renderer::lighting::SceneLighting;
 use dog_tv_renderer::renderables::frame::ImageFrame;
 use dog_tv_renderer::renderables::pixel_renderable::PixelRenderable;
 use dog_tv_renderer::renderables::scene_renderable::SceneRenderable;
@@ -16,6 +17,9 @@ pub mod image_view_packet;
 pub mod plot_view_packet;
 /// scene packet
 pub mod scene_view_packet;
+/// binary wire format of packets
+#[cfg(feature = "wire")]
+pub mod wire;
 
 /// Packet of renderables
 #[derive(Clone, Debug)]
@@ -40,6 +44,30 @@ pub fn make_image_packet(
         pixel_renderables,
         scene_renderables,
         view_label: view_label.to_string(),
+        ..Default::default()
+    })
+}
+
+/// Create a image packet which deletes named renderables
+pub fn delete_from_image_packet(
+    view_label: &str,
+    pixel_renderables_to_delete: Vec<String>,
+    scene_renderables_to_delete: Vec<String>,
+) -> Packet {
+    Packet::Image(ImageViewPacket {
+        pixel_renderables_to_delete,
+        scene_renderables_to_delete,
+        view_label: view_label.to_string(),
+        ..Default::default()
+    })
+}
+
+/// Create a image packet which deletes all renderables, but keeps the frame
+pub fn clear_image_packet(view_label: &str) -> Packet {
+    Packet::Image(ImageViewPacket {
+        clear_all: true,
+        view_label: view_label.to_string(),
+        ..Default::default()
     })
 }
 
@@ -66,6 +94,30 @@ pub fn append_to_scene_packet(view_label: &str, scene_renderables: Vec<SceneRend
     })
 }
 
+/// Delete named renderables from scene packet
+pub fn delete_from_scene_packet(view_label: &str, names: Vec<String>) -> Packet {
+    Packet::Scene(SceneViewPacket {
+        view_label: view_label.to_string(),
+        content: SceneViewPacketContent::Delete(names),
+    })
+}
+
+/// Delete all renderables from scene packet
+pub fn clear_scene_packet(view_label: &str) -> Packet {
+    Packet::Scene(SceneViewPacket {
+        view_label: view_label.to_string(),
+        content: SceneViewPacketContent::ClearAll,
+    })
+}
+
+/// Set the lighting of a scene view, scene packet
+pub fn scene_lighting_packet(view_label: &str, lighting: SceneLighting) -> Packet {
+    Packet::Scene(SceneViewPacket {
+        view_label: view_label.to_string(),
+        content: SceneViewPacketContent::Lighting(lighting),
+    })
+}
+
 /// Create world-from-scene update, scene packet
 pub fn world_from_scene_update_packet(
     view_label: &str,
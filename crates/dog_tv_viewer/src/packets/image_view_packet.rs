@@ -4,7 +4,7 @@ use dog_tv_renderer::renderables::pixel_renderable::PixelRenderable;
 use dog_tv_renderer::renderables::scene_renderable::SceneRenderable;
 
 /// Packet to populate an image view
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct ImageViewPacket {
     /// Frame to hold content
     ///
@@ -18,6 +18,18 @@ pub struct ImageViewPacket {
     pub pixel_renderables: Vec<PixelRenderable>,
     /// List of scene renderables
     pub scene_renderables: Vec<SceneRenderable>,
+    /// Names of 2d renderables to delete
+    ///
+    /// Deletions are applied before the content of this packet is added.
+    pub pixel_renderables_to_delete: Vec<String>,
+    /// Names of scene renderables to delete
+    ///
+    /// Deletions are applied before the content of this packet is added.
+    pub scene_renderables_to_delete: Vec<String>,
+    /// If true, all 2d and scene renderables are deleted, but the frame is kept
+    ///
+    /// Clearing is applied before the content of this packet is added.
+    pub clear_all: bool,
     /// Name of the view
     pub view_label: String,
 }
@@ -1,5 +1,6 @@
 use crate::preludes::*;
 use dog_tv_renderer::camera::RenderCamera;
+use dog_tv_renderer::lighting::SceneLighting;
 use dog_tv_renderer::renderables::scene_renderable::SceneRenderable;
 use sophus::lie::Isometry3F64;
 
@@ -12,6 +13,12 @@ pub enum SceneViewPacketContent {
     Creation(SceneViewCreation),
     /// world-from-scene pose update
     WorldFromSceneUpdate(Isometry3F64),
+    /// delete 3d renderables by name
+    Delete(Vec<String>),
+    /// delete all 3d renderables
+    ClearAll,
+    /// lighting of triangle meshes
+    Lighting(SceneLighting),
 }
 
 /// Creation of a scene view
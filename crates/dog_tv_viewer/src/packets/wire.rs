@@ -0,0 +1,805 @@
+use crate::packets::image_view_packet::ImageViewPacket;
+use crate::packets::scene_view_packet::SceneViewCreation;
+use crate::packets::scene_view_packet::SceneViewPacket;
+use crate::packets::scene_view_packet::SceneViewPacketContent;
+use crate::packets::Packet;
+use crate::preludes::*;
+use core::fmt;
+
+extern crate alloc;
+
+mod plot;
+mod renderables;
+
+/// Magic bytes at the start of each message
+pub const WIRE_MAGIC: [u8; 4] = *b"DOGT";
+
+/// Version of the wire format
+///
+/// Must be bumped whenever the encoding of any packet changes. Messages with a different version
+/// are rejected by the decoder.
+pub const WIRE_FORMAT_VERSION: u16 = 10;
+
+/// Length of the message header in bytes
+///
+/// The header consists of the magic bytes, the format version (u16), two reserved bytes and the
+/// payload length (u32). All integers and floats are little-endian.
+pub const WIRE_HEADER_LEN: usize = 12;
+
+/// Maximal payload length in bytes
+///
+/// Messages announcing a longer payload are rejected before the payload is read.
+pub const MAX_WIRE_PAYLOAD_LEN: usize = 1 << 30;
+
+/// Error when encoding or decoding a message
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WireError {
+    /// The message ended before decoding was complete
+    UnexpectedEnd,
+    /// The message does not start with the magic bytes
+    BadMagic,
+    /// The message was encoded with a different wire format version
+    UnsupportedVersion(u16),
+    /// Unknown tag for an enum
+    InvalidTag {
+        /// name of the enum
+        what: &'static str,
+        /// the unknown tag
+        tag: u8,
+    },
+    /// A string is not valid utf-8
+    InvalidUtf8,
+    /// A decoded value is out of range, e.g. an image too large to be addressed
+    InvalidValue(&'static str),
+    /// The payload was decoded, but there are bytes left over
+    TrailingBytes(usize),
+    /// The payload is longer than `MAX_WIRE_PAYLOAD_LEN`
+    PayloadTooLarge(usize),
+    /// A length to encode does not fit into the u32 of the wire format
+    LengthOverflow(usize),
+}
+
+impl fmt::Display for WireError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WireError::UnexpectedEnd => write!(f, "unexpected end of message"),
+            WireError::BadMagic => write!(f, "not a dog-tv message"),
+            WireError::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported wire format version {}, expected {}",
+                version, WIRE_FORMAT_VERSION
+            ),
+            WireError::InvalidTag { what, tag } => write!(f, "invalid {} tag: {}", what, tag),
+            WireError::InvalidUtf8 => write!(f, "invalid utf-8 string"),
+            WireError::InvalidValue(what) => write!(f, "invalid {}", what),
+            WireError::TrailingBytes(n) => write!(f, "{} trailing bytes after payload", n),
+            WireError::PayloadTooLarge(n) => write!(
+                f,
+                "payload of {} bytes exceeds the maximum of {} bytes",
+                n, MAX_WIRE_PAYLOAD_LEN
+            ),
+            WireError::LengthOverflow(n) => write!(f, "length {} exceeds u32", n),
+        }
+    }
+}
+
+/// Encode packets into a single message, including the header
+///
+/// Fails if a length does not fit into a u32 or if the payload exceeds `MAX_WIRE_PAYLOAD_LEN`,
+/// since the receiver would reject it.
+pub fn encode_packets(packets: &[Packet]) -> Result<Vec<u8>, WireError> {
+    let mut writer = WireWriter::default();
+    writer.len(packets.len());
+    for packet in packets {
+        packet.encode(&mut writer);
+    }
+    if let Some(len) = writer.overflowing_len {
+        return Err(WireError::LengthOverflow(len));
+    }
+    let payload = writer.buf;
+    if payload.len() > MAX_WIRE_PAYLOAD_LEN {
+        return Err(WireError::PayloadTooLarge(payload.len()));
+    }
+
+    let mut message = Vec::with_capacity(WIRE_HEADER_LEN + payload.len());
+    message.extend_from_slice(&WIRE_MAGIC);
+    message.extend_from_slice(&WIRE_FORMAT_VERSION.to_le_bytes());
+    message.extend_from_slice(&[0, 0]);
+    // fits, since MAX_WIRE_PAYLOAD_LEN is below u32::MAX
+    message.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    message.extend_from_slice(&payload);
+    Ok(message)
+}
+
+/// Decode the message header and return the payload length
+///
+/// Useful to read messages from a byte stream: First read `WIRE_HEADER_LEN` bytes, then the
+/// payload.
+pub fn decode_header(header: &[u8]) -> Result<usize, WireError> {
+    if header.len() < WIRE_HEADER_LEN {
+        return Err(WireError::UnexpectedEnd);
+    }
+    if header[0..4] != WIRE_MAGIC {
+        return Err(WireError::BadMagic);
+    }
+    let version = u16::from_le_bytes([header[4], header[5]]);
+    if version != WIRE_FORMAT_VERSION {
+        return Err(WireError::UnsupportedVersion(version));
+    }
+    let payload_len = u32::from_le_bytes([header[8], header[9], header[10], header[11]]) as usize;
+    if payload_len > MAX_WIRE_PAYLOAD_LEN {
+        return Err(WireError::PayloadTooLarge(payload_len));
+    }
+    Ok(payload_len)
+}
+
+/// Decode a message, including the header, into packets
+pub fn decode_packets(message: &[u8]) -> Result<Vec<Packet>, WireError> {
+    let payload_len = decode_header(message)?;
+    let payload = &message[WIRE_HEADER_LEN..];
+    if payload.len() < payload_len {
+        return Err(WireError::UnexpectedEnd);
+    }
+    if payload.len() > payload_len {
+        return Err(WireError::TrailingBytes(payload.len() - payload_len));
+    }
+    decode_payload(payload)
+}
+
+/// Decode a message payload, i.e. the bytes following the header, into packets
+pub fn decode_payload(payload: &[u8]) -> Result<Vec<Packet>, WireError> {
+    let mut reader = WireReader::new(payload);
+    let packets = Vec::<Packet>::decode(&mut reader)?;
+    if !reader.bytes.is_empty() {
+        return Err(WireError::TrailingBytes(reader.bytes.len()));
+    }
+    Ok(packets)
+}
+
+#[derive(Default)]
+pub(crate) struct WireWriter {
+    pub(crate) buf: Vec<u8>,
+    // first length which did not fit into a u32, reported by `encode_packets`
+    pub(crate) overflowing_len: Option<usize>,
+}
+
+impl WireWriter {
+    pub(crate) fn u8(&mut self, v: u8) {
+        self.buf.push(v);
+    }
+
+    pub(crate) fn u32(&mut self, v: u32) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub(crate) fn f32(&mut self, v: f32) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub(crate) fn f64(&mut self, v: f64) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub(crate) fn len(&mut self, len: usize) {
+        match u32::try_from(len) {
+            Ok(len) => self.u32(len),
+            Err(_) => {
+                self.overflowing_len.get_or_insert(len);
+                self.u32(u32::MAX);
+            }
+        }
+    }
+
+    pub(crate) fn bytes(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
+}
+
+pub(crate) struct WireReader<'a> {
+    pub(crate) bytes: &'a [u8],
+}
+
+impl<'a> WireReader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        WireReader { bytes }
+    }
+
+    pub(crate) fn take(&mut self, n: usize) -> Result<&'a [u8], WireError> {
+        if self.bytes.len() < n {
+            return Err(WireError::UnexpectedEnd);
+        }
+        let (head, tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, WireError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, WireError> {
+        let b = self.take(4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    pub(crate) fn f32(&mut self) -> Result<f32, WireError> {
+        Ok(f32::from_bits(self.u32()?))
+    }
+
+    pub(crate) fn f64(&mut self) -> Result<f64, WireError> {
+        let b = self.take(8)?;
+        Ok(f64::from_le_bytes([
+            b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7],
+        ]))
+    }
+
+    pub(crate) fn len(&mut self) -> Result<usize, WireError> {
+        Ok(self.u32()? as usize)
+    }
+}
+
+pub(crate) trait WireEncode {
+    fn encode(&self, w: &mut WireWriter);
+}
+
+pub(crate) trait WireDecode: Sized {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError>;
+}
+
+impl WireEncode for bool {
+    fn encode(&self, w: &mut WireWriter) {
+        w.u8(*self as u8);
+    }
+}
+
+impl WireDecode for bool {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        match r.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            tag => Err(WireError::InvalidTag { what: "bool", tag }),
+        }
+    }
+}
+
+impl WireEncode for f32 {
+    fn encode(&self, w: &mut WireWriter) {
+        w.f32(*self);
+    }
+}
+
+impl WireDecode for f32 {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        r.f32()
+    }
+}
+
+impl WireEncode for f64 {
+    fn encode(&self, w: &mut WireWriter) {
+        w.f64(*self);
+    }
+}
+
+impl WireDecode for f64 {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        r.f64()
+    }
+}
+
+impl WireEncode for String {
+    fn encode(&self, w: &mut WireWriter) {
+        w.len(self.len());
+        w.bytes(self.as_bytes());
+    }
+}
+
+impl WireDecode for String {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        let len = r.len()?;
+        let bytes = r.take(len)?;
+        core::str::from_utf8(bytes)
+            .map(|s| s.to_string())
+            .map_err(|_| WireError::InvalidUtf8)
+    }
+}
+
+impl<T: WireEncode> WireEncode for Option<T> {
+    fn encode(&self, w: &mut WireWriter) {
+        match self {
+            None => w.u8(0),
+            Some(v) => {
+                w.u8(1);
+                v.encode(w);
+            }
+        }
+    }
+}
+
+impl<T: WireDecode> WireDecode for Option<T> {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        match r.u8()? {
+            0 => Ok(None),
+            1 => Ok(Some(T::decode(r)?)),
+            tag => Err(WireError::InvalidTag {
+                what: "option",
+                tag,
+            }),
+        }
+    }
+}
+
+impl<T: WireEncode> WireEncode for Vec<T> {
+    fn encode(&self, w: &mut WireWriter) {
+        w.len(self.len());
+        for v in self {
+            v.encode(w);
+        }
+    }
+}
+
+impl<T: WireDecode> WireDecode for Vec<T> {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        let len = r.len()?;
+        // do not trust the length for the allocation: reserve at most as much memory as there are
+        // bytes left, and grow beyond that as elements are decoded
+        let max_reserved = r.bytes.len() / core::mem::size_of::<T>().max(1);
+        let mut v = Vec::with_capacity(len.min(max_reserved));
+        for _ in 0..len {
+            v.push(T::decode(r)?);
+        }
+        Ok(v)
+    }
+}
+
+impl<T: WireEncode> WireEncode for VecDeque<T> {
+    fn encode(&self, w: &mut WireWriter) {
+        w.len(self.len());
+        for v in self {
+            v.encode(w);
+        }
+    }
+}
+
+impl<T: WireDecode> WireDecode for VecDeque<T> {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        Ok(Vec::<T>::decode(r)?.into())
+    }
+}
+
+impl<T: WireEncode, const N: usize> WireEncode for [T; N] {
+    fn encode(&self, w: &mut WireWriter) {
+        for v in self {
+            v.encode(w);
+        }
+    }
+}
+
+impl<T: WireDecode, const N: usize> WireDecode for [T; N] {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        let mut v = Vec::with_capacity(N);
+        for _ in 0..N {
+            v.push(T::decode(r)?);
+        }
+        match v.try_into() {
+            Ok(arr) => Ok(arr),
+            Err(_) => unreachable!(),
+        }
+    }
+}
+
+impl<A: WireEncode, B: WireEncode> WireEncode for (A, B) {
+    fn encode(&self, w: &mut WireWriter) {
+        self.0.encode(w);
+        self.1.encode(w);
+    }
+}
+
+impl<A: WireDecode, B: WireDecode> WireDecode for (A, B) {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        Ok((A::decode(r)?, B::decode(r)?))
+    }
+}
+
+impl WireEncode for Packet {
+    fn encode(&self, w: &mut WireWriter) {
+        match self {
+            Packet::Scene(packet) => {
+                w.u8(0);
+                packet.encode(w);
+            }
+            Packet::Image(packet) => {
+                w.u8(1);
+                packet.encode(w);
+            }
+            Packet::Plot(packets) => {
+                w.u8(2);
+                packets.encode(w);
+            }
+        }
+    }
+}
+
+impl WireDecode for Packet {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        match r.u8()? {
+            0 => Ok(Packet::Scene(SceneViewPacket::decode(r)?)),
+            1 => Ok(Packet::Image(ImageViewPacket::decode(r)?)),
+            2 => Ok(Packet::Plot(Vec::decode(r)?)),
+            tag => Err(WireError::InvalidTag {
+                what: "packet",
+                tag,
+            }),
+        }
+    }
+}
+
+impl WireEncode for SceneViewPacket {
+    fn encode(&self, w: &mut WireWriter) {
+        self.view_label.encode(w);
+        match &self.content {
+            SceneViewPacketContent::Renderables(renderables) => {
+                w.u8(0);
+                renderables.encode(w);
+            }
+            SceneViewPacketContent::Creation(creation) => {
+                w.u8(1);
+                creation.initial_camera.encode(w);
+                creation.locked_to_birds_eye_orientation.encode(w);
+            }
+            SceneViewPacketContent::WorldFromSceneUpdate(world_from_scene) => {
+                w.u8(2);
+                world_from_scene.encode(w);
+            }
+            SceneViewPacketContent::Delete(names) => {
+                w.u8(3);
+                names.encode(w);
+            }
+            SceneViewPacketContent::ClearAll => {
+                w.u8(4);
+            }
+            SceneViewPacketContent::Lighting(lighting) => {
+                w.u8(5);
+                lighting.encode(w);
+            }
+        }
+    }
+}
+
+impl WireDecode for SceneViewPacket {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        let view_label = String::decode(r)?;
+        let content = match r.u8()? {
+            0 => SceneViewPacketContent::Renderables(Vec::decode(r)?),
+            1 => SceneViewPacketContent::Creation(SceneViewCreation {
+                initial_camera: WireDecode::decode(r)?,
+                locked_to_birds_eye_orientation: bool::decode(r)?,
+            }),
+            2 => SceneViewPacketContent::WorldFromSceneUpdate(WireDecode::decode(r)?),
+            3 => SceneViewPacketContent::Delete(Vec::decode(r)?),
+            4 => SceneViewPacketContent::ClearAll,
+            5 => SceneViewPacketContent::Lighting(WireDecode::decode(r)?),
+            tag => {
+                return Err(WireError::InvalidTag {
+                    what: "scene view packet",
+                    tag,
+                })
+            }
+        };
+        Ok(SceneViewPacket {
+            view_label,
+            content,
+        })
+    }
+}
+
+impl WireEncode for ImageViewPacket {
+    fn encode(&self, w: &mut WireWriter) {
+        self.view_label.encode(w);
+        self.frame.encode(w);
+        self.pixel_renderables.encode(w);
+        self.scene_renderables.encode(w);
+        self.pixel_renderables_to_delete.encode(w);
+        self.scene_renderables_to_delete.encode(w);
+        self.clear_all.encode(w);
+    }
+}
+
+impl WireDecode for ImageViewPacket {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        Ok(ImageViewPacket {
+            view_label: String::decode(r)?,
+            frame: Option::decode(r)?,
+            pixel_renderables: Vec::decode(r)?,
+            scene_renderables: Vec::decode(r)?,
+            pixel_renderables_to_delete: Vec::decode(r)?,
+            scene_renderables_to_delete: Vec::decode(r)?,
+            clear_all: bool::decode(r)?,
+        })
+    }
+}
+
+#[test]
+fn wire_roundtrip_tests() {
+    use crate::packets::plot_view_packet::scalar_curve::ScalarCurveStyle;
+    use crate::packets::plot_view_packet::ClearCondition;
+    use crate::packets::plot_view_packet::LineType;
+    use crate::packets::plot_view_packet::PlotViewPacket;
+    use alloc::vec;
+    use dog_tv_renderer::camera::RenderCamera;
+    use dog_tv_renderer::renderables::color::Color;
+    use dog_tv_renderer::renderables::color::Colormap;
+    use dog_tv_renderer::renderables::frame::ImageFrame;
+    use dog_tv_renderer::renderables::pixel_renderable::make_arrow2;
+    use dog_tv_renderer::renderables::pixel_renderable::make_box2;
+    use dog_tv_renderer::renderables::pixel_renderable::make_point2;
+    use dog_tv_renderer::renderables::pixel_renderable::named_ellipse2;
+    use dog_tv_renderer::renderables::pixel_renderable::named_polygon2;
+    use dog_tv_renderer::renderables::pixel_renderable::named_text2;
+    use dog_tv_renderer::renderables::pixel_renderable::Ellipse2;
+    use dog_tv_renderer::renderables::pixel_renderable::Polygon2;
+    use dog_tv_renderer::renderables::pixel_renderable::Text2;
+    use dog_tv_renderer::renderables::pixel_renderable::TextAnchor;
+    use dog_tv_renderer::renderables::scene_renderable::axes::make_axis3;
+    use dog_tv_renderer::renderables::scene_renderable::named_camera_frustum3;
+    use dog_tv_renderer::renderables::scene_renderable::named_gaussian3;
+    use dog_tv_renderer::renderables::scene_renderable::named_instances3;
+    use dog_tv_renderer::renderables::scene_renderable::named_line3;
+    use dog_tv_renderer::renderables::scene_renderable::named_point_stream3;
+    use dog_tv_renderer::renderables::scene_renderable::named_polyline3;
+    use dog_tv_renderer::renderables::scene_renderable::named_pose_covariance3;
+    use dog_tv_renderer::renderables::scene_renderable::uncertainty::Gaussian3;
+    use dog_tv_renderer::renderables::scene_renderable::uncertainty::PoseCovariance3;
+    use dog_tv_renderer::renderables::scene_renderable::uncertainty::UncertaintyStyle;
+    use dog_tv_renderer::renderables::scene_renderable::Instance3;
+    use dog_tv_renderer::renderables::scene_renderable::InstanceTemplate3;
+    use dog_tv_renderer::renderables::scene_renderable::Point3;
+    use dog_tv_renderer::renderables::scene_renderable::PointBudget;
+    use dog_tv_renderer::renderables::scene_renderable::PointColoring;
+    use dog_tv_renderer::renderables::scene_renderable::PolylineVertex3;
+    use dog_tv_renderer::renderables::scene_renderable::ScalarField3;
+    use dog_tv_renderer::renderables::scene_renderable::ScalarPointCloud3;
+    use dog_tv_renderer::renderables::scene_renderable::SceneRenderable;
+    use dog_tv_renderer::renderables::scene_renderable::Triangle3;
+    use sophus::core::linalg::MatF64;
+    use sophus::core::linalg::SMat;
+    use sophus::core::linalg::SVec;
+    use sophus::image::arc_image::ArcImage4U8;
+    use sophus::image::mut_image::MutImage4U8;
+    use sophus::image::ImageSize;
+    use sophus::lie::Isometry3F64;
+
+    let packets = vec![
+        crate::packets::create_scene_packet("scene", RenderCamera::default(), false),
+        crate::packets::append_to_scene_packet(
+            "scene",
+            vec![named_line3("axes", make_axis3(Isometry3F64::identity()))],
+        ),
+        crate::packets::append_to_scene_packet(
+            "scene",
+            vec![
+                named_gaussian3(
+                    "landmarks",
+                    vec![Gaussian3 {
+                        mean: SVec::<f32, 3>::new(1.0, 2.0, 3.0),
+                        covariance: SMat::<f32, 3, 3>::from_diagonal_element(0.1),
+                        color: Color::red(),
+                    }],
+                    3.0,
+                    UncertaintyStyle::PrincipalEllipses,
+                ),
+                named_pose_covariance3(
+                    "poses",
+                    vec![PoseCovariance3 {
+                        world_from_pose: Isometry3F64::identity(),
+                        covariance: MatF64::<6, 6>::from_diagonal_element(0.01),
+                    }],
+                    2.0,
+                    0.5,
+                ),
+            ],
+        ),
+        crate::packets::append_to_scene_packet(
+            "scene",
+            vec![
+                named_instances3(
+                    "markers",
+                    InstanceTemplate3::Lines(make_axis3(Isometry3F64::identity())),
+                    vec![
+                        Instance3::new(Isometry3F64::identity()),
+                        Instance3 {
+                            entity_from_instance: Isometry3F64::trans_x(2.0),
+                            color: Color::blue(),
+                            scale: 0.5,
+                        },
+                    ],
+                ),
+                named_instances3(
+                    "tiles",
+                    InstanceTemplate3::Mesh(vec![Triangle3::new(
+                        SVec::<f32, 3>::new(0.0, 0.0, 0.0),
+                        SVec::<f32, 3>::new(1.0, 0.0, 0.0),
+                        SVec::<f32, 3>::new(0.0, 1.0, 0.0),
+                        Color::green(),
+                    )]),
+                    vec![Instance3::new(Isometry3F64::trans_z(1.0))],
+                ),
+            ],
+        ),
+        crate::packets::append_to_scene_packet(
+            "scene",
+            vec![named_point_stream3(
+                "scan",
+                vec![Point3 {
+                    p: SVec::<f32, 3>::new(1.0, 2.0, 3.0),
+                    color: Color::red(),
+                    point_size: 2.0,
+                }],
+                PointBudget {
+                    max_points: 1000,
+                    voxel_size: 0.05,
+                },
+            )],
+        ),
+        crate::packets::append_to_scene_packet(
+            "scene",
+            vec![SceneRenderable::ScalarPoint(ScalarPointCloud3 {
+                name: "lidar".to_string(),
+                positions: vec![
+                    SVec::<f32, 3>::new(1.0, 2.0, 3.0),
+                    SVec::<f32, 3>::new(4.0, 5.0, 6.0),
+                ],
+                fields: vec![
+                    ScalarField3 {
+                        name: "intensity".to_string(),
+                        values: vec![0.5, 1.0],
+                    },
+                    ScalarField3 {
+                        name: "range".to_string(),
+                        values: vec![3.7, 8.8],
+                    },
+                ],
+                point_size: 2.0,
+                coloring: PointColoring {
+                    field: 1,
+                    colormap: Colormap::Viridis,
+                    range: Some([0.0, 10.0]),
+                },
+                world_from_entity: Isometry3F64::identity(),
+            })],
+        ),
+        crate::packets::append_to_scene_packet(
+            "scene",
+            vec![
+                named_camera_frustum3(
+                    "camera",
+                    &ImageFrame::from_size(&ImageSize::new(4, 3)),
+                    1.0,
+                    Isometry3F64::identity(),
+                ),
+                named_camera_frustum3(
+                    "camera with image",
+                    &ImageFrame::from_image(&ArcImage4U8::from(
+                        MutImage4U8::from_image_size_and_val(
+                            ImageSize::new(4, 3),
+                            SVec::<u8, 4>::new(255, 0, 0, 255),
+                        ),
+                    )),
+                    0.5,
+                    Isometry3F64::trans_z(1.0),
+                ),
+            ],
+        ),
+        crate::packets::append_to_scene_packet(
+            "scene",
+            vec![
+                named_polyline3(
+                    "trajectory",
+                    vec![
+                        PolylineVertex3 {
+                            p: SVec::<f32, 3>::new(0.0, 0.0, 0.0),
+                            color: Color::red(),
+                            t: 0.0,
+                        },
+                        PolylineVertex3 {
+                            p: SVec::<f32, 3>::new(1.0, 0.0, 0.0),
+                            color: Color::blue(),
+                            t: 0.1,
+                        },
+                    ],
+                    2.0,
+                    Some(5.0),
+                ),
+                named_polyline3("path", vec![], 1.0, None),
+            ],
+        ),
+        crate::packets::delete_from_scene_packet("scene", vec!["axes".to_string()]),
+        crate::packets::make_image_packet(
+            "image",
+            Some(ImageFrame::from_size(&ImageSize::new(4, 3))),
+            vec![
+                make_point2("points", &[[1.0, 2.0]], &Color::red(), 5.0),
+                named_text2(
+                    "labels",
+                    vec![Text2 {
+                        text: "id 7".to_string(),
+                        p: [1.0, 2.0].into(),
+                        anchor: TextAnchor::BottomCenter,
+                        color: Color::red(),
+                        font_size: 12.0,
+                    }],
+                ),
+                named_ellipse2(
+                    "ellipses",
+                    vec![Ellipse2 {
+                        center: [2.0, 1.5].into(),
+                        radii: [2.0, 1.0].into(),
+                        angle: 0.5,
+                        color: Color::red(),
+                        line_width: 1.0,
+                        fill_color: Some(Color::blue()),
+                    }],
+                ),
+                make_box2("boxes", &[[[0.0, 0.0], [2.0, 1.0]]], &Color::green(), 2.0),
+                named_polygon2(
+                    "polygons",
+                    vec![Polygon2 {
+                        points: vec![
+                            [0.0, 0.0].into(),
+                            [3.0, 0.0].into(),
+                            [1.0, 1.0].into(),
+                            [0.0, 3.0].into(),
+                        ],
+                        color: Color::green(),
+                        line_width: 1.5,
+                        fill_color: Some(Color::red()),
+                    }],
+                ),
+                make_arrow2("arrows", &[[[0.0, 0.0], [3.0, 2.0]]], &Color::blue(), 1.0),
+            ],
+            vec![],
+        ),
+        Packet::Plot(vec![PlotViewPacket::append_to_curve(
+            ("plot", "curve"),
+            vec![(0.0, 1.0), (1.0, 2.0)].into(),
+            ScalarCurveStyle {
+                color: Color::blue(),
+                line_type: LineType::Points,
+            },
+            ClearCondition { max_x_range: 10.0 },
+            Some(0.5),
+        )]),
+    ];
+
+    let message = encode_packets(&packets).unwrap();
+    let decoded = decode_packets(&message).unwrap();
+    assert_eq!(decoded.len(), packets.len());
+    assert_eq!(encode_packets(&decoded).unwrap(), message);
+
+    assert_eq!(
+        decode_packets(&message[..message.len() - 1]).unwrap_err(),
+        WireError::UnexpectedEnd
+    );
+    let mut wrong_version = message.clone();
+    wrong_version[4] = wrong_version[4].wrapping_add(1);
+    assert!(matches!(
+        decode_packets(&wrong_version),
+        Err(WireError::UnsupportedVersion(_))
+    ));
+    let mut too_large = message.clone();
+    too_large[8..12].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(matches!(
+        decode_header(&too_large),
+        Err(WireError::PayloadTooLarge(_))
+    ));
+}
+
+#[cfg(target_pointer_width = "64")]
+#[test]
+fn wire_writer_reports_overflowing_len() {
+    let mut w = WireWriter::default();
+    w.len(u32::MAX as usize);
+    assert_eq!(w.overflowing_len, None);
+    w.len(u32::MAX as usize + 1);
+    w.len(u32::MAX as usize + 2);
+    assert_eq!(w.overflowing_len, Some(u32::MAX as usize + 1));
+}
@@ -0,0 +1,189 @@
+use crate::packets::plot_view_packet::curve_vec_with_conf::CurveVecWithConf;
+use crate::packets::plot_view_packet::curve_vec_with_conf::CurveVecWithConfStyle;
+use crate::packets::plot_view_packet::curve_vec_with_conf::NamedVecConfCurve;
+use crate::packets::plot_view_packet::scalar_curve::NamedScalarCurve;
+use crate::packets::plot_view_packet::scalar_curve::ScalarCurve;
+use crate::packets::plot_view_packet::scalar_curve::ScalarCurveStyle;
+use crate::packets::plot_view_packet::vec_curve::CurveVec;
+use crate::packets::plot_view_packet::vec_curve::CurveVecStyle;
+use crate::packets::plot_view_packet::vec_curve::NamedCurveVec;
+use crate::packets::plot_view_packet::ClearCondition;
+use crate::packets::plot_view_packet::LineType;
+use crate::packets::plot_view_packet::PlotViewPacket;
+use crate::packets::wire::WireDecode;
+use crate::packets::wire::WireEncode;
+use crate::packets::wire::WireError;
+use crate::packets::wire::WireReader;
+use crate::packets::wire::WireWriter;
+use crate::preludes::*;
+use dog_tv_renderer::renderables::color::Color;
+
+impl WireEncode for PlotViewPacket {
+    fn encode(&self, w: &mut WireWriter) {
+        match self {
+            PlotViewPacket::Scalar(curve) => {
+                w.u8(0);
+                curve.encode(w);
+            }
+            PlotViewPacket::Vec2(curve) => {
+                w.u8(1);
+                curve.encode(w);
+            }
+            PlotViewPacket::Vec2Conf(curve) => {
+                w.u8(2);
+                curve.encode(w);
+            }
+            PlotViewPacket::Vec3(curve) => {
+                w.u8(3);
+                curve.encode(w);
+            }
+            PlotViewPacket::Vec3Conf(curve) => {
+                w.u8(4);
+                curve.encode(w);
+            }
+        }
+    }
+}
+
+impl WireDecode for PlotViewPacket {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        match r.u8()? {
+            0 => Ok(PlotViewPacket::Scalar(NamedScalarCurve::decode(r)?)),
+            1 => Ok(PlotViewPacket::Vec2(NamedCurveVec::decode(r)?)),
+            2 => Ok(PlotViewPacket::Vec2Conf(NamedVecConfCurve::decode(r)?)),
+            3 => Ok(PlotViewPacket::Vec3(NamedCurveVec::decode(r)?)),
+            4 => Ok(PlotViewPacket::Vec3Conf(NamedVecConfCurve::decode(r)?)),
+            tag => Err(WireError::InvalidTag {
+                what: "plot view packet",
+                tag,
+            }),
+        }
+    }
+}
+
+impl WireEncode for ClearCondition {
+    fn encode(&self, w: &mut WireWriter) {
+        w.f64(self.max_x_range);
+    }
+}
+
+impl WireDecode for ClearCondition {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        Ok(ClearCondition {
+            max_x_range: r.f64()?,
+        })
+    }
+}
+
+impl WireEncode for LineType {
+    fn encode(&self, w: &mut WireWriter) {
+        match self {
+            LineType::LineStrip => w.u8(0),
+            LineType::Points => w.u8(1),
+        }
+    }
+}
+
+impl WireDecode for LineType {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        match r.u8()? {
+            0 => Ok(LineType::LineStrip),
+            1 => Ok(LineType::Points),
+            tag => Err(WireError::InvalidTag {
+                what: "line type",
+                tag,
+            }),
+        }
+    }
+}
+
+impl WireEncode for NamedScalarCurve {
+    fn encode(&self, w: &mut WireWriter) {
+        self.plot_name.encode(w);
+        self.graph_name.encode(w);
+        let curve = &self.scalar_curve;
+        curve.data.encode(w);
+        curve.style.color.encode(w);
+        curve.style.line_type.encode(w);
+        curve.clear_cond.encode(w);
+        curve.v_line.encode(w);
+    }
+}
+
+impl WireDecode for NamedScalarCurve {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        Ok(NamedScalarCurve {
+            plot_name: String::decode(r)?,
+            graph_name: String::decode(r)?,
+            scalar_curve: ScalarCurve {
+                data: VecDeque::decode(r)?,
+                style: ScalarCurveStyle {
+                    color: Color::decode(r)?,
+                    line_type: LineType::decode(r)?,
+                },
+                clear_cond: ClearCondition::decode(r)?,
+                v_line: Option::decode(r)?,
+            },
+        })
+    }
+}
+
+impl<const N: usize> WireEncode for NamedCurveVec<N> {
+    fn encode(&self, w: &mut WireWriter) {
+        self.plot_name.encode(w);
+        self.curve_name.encode(w);
+        let curve = &self.scalar_curve;
+        curve.data.encode(w);
+        curve.style.colors.encode(w);
+        curve.style.line_type.encode(w);
+        curve.clear_cond.encode(w);
+        curve.v_line.encode(w);
+    }
+}
+
+impl<const N: usize> WireDecode for NamedCurveVec<N> {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        Ok(NamedCurveVec {
+            plot_name: String::decode(r)?,
+            curve_name: String::decode(r)?,
+            scalar_curve: CurveVec {
+                data: VecDeque::decode(r)?,
+                style: CurveVecStyle {
+                    colors: WireDecode::decode(r)?,
+                    line_type: LineType::decode(r)?,
+                },
+                clear_cond: ClearCondition::decode(r)?,
+                v_line: Option::decode(r)?,
+            },
+        })
+    }
+}
+
+impl<const N: usize> WireEncode for NamedVecConfCurve<N> {
+    fn encode(&self, w: &mut WireWriter) {
+        self.plot_name.encode(w);
+        self.curve_name.encode(w);
+        let curve = &self.scalar_curve;
+        curve.data.encode(w);
+        curve.style.colors.encode(w);
+        curve.clear_cond.encode(w);
+        curve.v_line.encode(w);
+    }
+}
+
+impl<const N: usize> WireDecode for NamedVecConfCurve<N> {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        Ok(NamedVecConfCurve {
+            plot_name: String::decode(r)?,
+            curve_name: String::decode(r)?,
+            scalar_curve: CurveVecWithConf {
+                data: VecDeque::decode(r)?,
+                style: CurveVecWithConfStyle {
+                    colors: WireDecode::decode(r)?,
+                },
+                clear_cond: ClearCondition::decode(r)?,
+                v_line: Option::decode(r)?,
+            },
+        })
+    }
+}
@@ -0,0 +1,1167 @@
+use crate::packets::wire::WireDecode;
+use crate::packets::wire::WireEncode;
+use crate::packets::wire::WireError;
+use crate::packets::wire::WireReader;
+use crate::packets::wire::WireWriter;
+use crate::preludes::*;
+use dog_tv_renderer::camera::clipping_planes::ClippingPlanesF64;
+use dog_tv_renderer::camera::intrinsics::RenderIntrinsics;
+use dog_tv_renderer::camera::properties::RenderCameraProperties;
+use dog_tv_renderer::camera::RenderCamera;
+use dog_tv_renderer::lighting::LightSource;
+use dog_tv_renderer::lighting::SceneLighting;
+use dog_tv_renderer::lighting::ShadingModel;
+use dog_tv_renderer::renderables::color::Color;
+use dog_tv_renderer::renderables::color::Colormap;
+use dog_tv_renderer::renderables::frame::ImageFrame;
+use dog_tv_renderer::renderables::pixel_renderable::Arrow2;
+use dog_tv_renderer::renderables::pixel_renderable::Arrows2;
+use dog_tv_renderer::renderables::pixel_renderable::Box2;
+use dog_tv_renderer::renderables::pixel_renderable::Boxes2;
+use dog_tv_renderer::renderables::pixel_renderable::Ellipse2;
+use dog_tv_renderer::renderables::pixel_renderable::Ellipses2;
+use dog_tv_renderer::renderables::pixel_renderable::LineSegment2;
+use dog_tv_renderer::renderables::pixel_renderable::LineSegments2;
+use dog_tv_renderer::renderables::pixel_renderable::PixelRenderable;
+use dog_tv_renderer::renderables::pixel_renderable::Point2;
+use dog_tv_renderer::renderables::pixel_renderable::PointCloud2;
+use dog_tv_renderer::renderables::pixel_renderable::Polygon2;
+use dog_tv_renderer::renderables::pixel_renderable::Polygons2;
+use dog_tv_renderer::renderables::pixel_renderable::Text2;
+use dog_tv_renderer::renderables::pixel_renderable::TextAnchor;
+use dog_tv_renderer::renderables::pixel_renderable::Texts2;
+use dog_tv_renderer::renderables::scene_renderable::frustum::CameraFrustum3;
+use dog_tv_renderer::renderables::scene_renderable::uncertainty::Gaussian3;
+use dog_tv_renderer::renderables::scene_renderable::uncertainty::Gaussians3;
+use dog_tv_renderer::renderables::scene_renderable::uncertainty::PoseCovariance3;
+use dog_tv_renderer::renderables::scene_renderable::uncertainty::PoseCovariances3;
+use dog_tv_renderer::renderables::scene_renderable::uncertainty::UncertaintyStyle;
+use dog_tv_renderer::renderables::scene_renderable::Instance3;
+use dog_tv_renderer::renderables::scene_renderable::InstanceTemplate3;
+use dog_tv_renderer::renderables::scene_renderable::Instances3;
+use dog_tv_renderer::renderables::scene_renderable::LineSegment3;
+use dog_tv_renderer::renderables::scene_renderable::LineSegments3;
+use dog_tv_renderer::renderables::scene_renderable::Point3;
+use dog_tv_renderer::renderables::scene_renderable::PointBudget;
+use dog_tv_renderer::renderables::scene_renderable::PointCloud3;
+use dog_tv_renderer::renderables::scene_renderable::PointColoring;
+use dog_tv_renderer::renderables::scene_renderable::PointStream3;
+use dog_tv_renderer::renderables::scene_renderable::Polyline3;
+use dog_tv_renderer::renderables::scene_renderable::PolylineVertex3;
+use dog_tv_renderer::renderables::scene_renderable::ScalarField3;
+use dog_tv_renderer::renderables::scene_renderable::ScalarPointCloud3;
+use dog_tv_renderer::renderables::scene_renderable::SceneRenderable;
+use dog_tv_renderer::renderables::scene_renderable::Text3;
+use dog_tv_renderer::renderables::scene_renderable::Texts3;
+use dog_tv_renderer::renderables::scene_renderable::TexturedTriangle3;
+use dog_tv_renderer::renderables::scene_renderable::TexturedTriangleMesh3;
+use dog_tv_renderer::renderables::scene_renderable::Triangle3;
+use dog_tv_renderer::renderables::scene_renderable::TriangleMesh3;
+use sophus::core::linalg::SMat;
+use sophus::core::linalg::VecF64;
+use sophus::core::IsTensorLike;
+use sophus::image::arc_image::ArcImage4U8;
+use sophus::image::image_view::ImageView4U8;
+use sophus::image::image_view::IsImageView;
+use sophus::image::ImageSize;
+use sophus::lie::Isometry3F64;
+use sophus::prelude::HasParams;
+use sophus::sensor::camera_enum::perspective_camera::BrownConradyCameraF64;
+use sophus::sensor::camera_enum::perspective_camera::KannalaBrandtCameraF64;
+use sophus::sensor::camera_enum::perspective_camera::PinholeCameraF64;
+use sophus::sensor::camera_enum::perspective_camera::UnifiedCameraF64;
+
+// Vectors and matrices are encoded in column-major order.
+impl<const R: usize, const C: usize> WireEncode for SMat<f32, R, C> {
+    fn encode(&self, w: &mut WireWriter) {
+        for v in self.iter() {
+            w.f32(*v);
+        }
+    }
+}
+
+impl<const R: usize, const C: usize> WireDecode for SMat<f32, R, C> {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        let mut m = SMat::<f32, R, C>::zeros();
+        for v in m.iter_mut() {
+            *v = r.f32()?;
+        }
+        Ok(m)
+    }
+}
+
+impl<const R: usize, const C: usize> WireEncode for SMat<f64, R, C> {
+    fn encode(&self, w: &mut WireWriter) {
+        for v in self.iter() {
+            w.f64(*v);
+        }
+    }
+}
+
+impl<const R: usize, const C: usize> WireDecode for SMat<f64, R, C> {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        let mut m = SMat::<f64, R, C>::zeros();
+        for v in m.iter_mut() {
+            *v = r.f64()?;
+        }
+        Ok(m)
+    }
+}
+
+// Poses are encoded using the sophus parameter layout, i.e. the translation followed by the unit
+// quaternion.
+impl WireEncode for Isometry3F64 {
+    fn encode(&self, w: &mut WireWriter) {
+        self.params().encode(w);
+    }
+}
+
+impl WireDecode for Isometry3F64 {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        let mut params = VecF64::<7>::decode(r)?;
+        let norm = params.fixed_rows::<4>(3).norm();
+        if !(norm.is_finite() && norm > 0.0) || params.iter().any(|x| !x.is_finite()) {
+            return Err(WireError::InvalidValue("pose"));
+        }
+        // sophus asserts unit quaternions, the sender may not provide one
+        params.fixed_rows_mut::<4>(3).unscale_mut(norm);
+        Ok(Isometry3F64::from_params(&params))
+    }
+}
+
+impl WireEncode for ImageSize {
+    fn encode(&self, w: &mut WireWriter) {
+        w.u32(self.width as u32);
+        w.u32(self.height as u32);
+    }
+}
+
+impl WireDecode for ImageSize {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        let width = r.u32()? as usize;
+        let height = r.u32()? as usize;
+        // images and cameras are backed by textures, which must not be empty
+        if width == 0 || height == 0 {
+            return Err(WireError::InvalidValue("image size"));
+        }
+        Ok(ImageSize::new(width, height))
+    }
+}
+
+// Images are encoded as image size followed by the tightly packed rgba pixels.
+impl WireEncode for ArcImage4U8 {
+    fn encode(&self, w: &mut WireWriter) {
+        self.image_size().encode(w);
+        w.bytes(self.tensor.scalar_view().as_slice().unwrap());
+    }
+}
+
+impl WireDecode for ArcImage4U8 {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        let image_size = ImageSize::decode(r)?;
+        let len = image_size
+            .width
+            .checked_mul(image_size.height)
+            .and_then(|area| area.checked_mul(4))
+            .ok_or(WireError::InvalidValue("image size"))?;
+        let bytes = r.take(len)?;
+        let view = ImageView4U8::from_stride_and_slice(
+            image_size,
+            image_size.width,
+            bytemuck::cast_slice(bytes),
+        );
+        Ok(ArcImage4U8::make_copy_from(&view))
+    }
+}
+
+impl WireEncode for RenderIntrinsics {
+    fn encode(&self, w: &mut WireWriter) {
+        match self {
+            RenderIntrinsics::Pinhole(camera) => {
+                w.u8(0);
+                camera.image_size().encode(w);
+                camera.params().encode(w);
+            }
+            RenderIntrinsics::UnifiedExtended(camera) => {
+                w.u8(1);
+                camera.image_size().encode(w);
+                camera.params().encode(w);
+            }
+            RenderIntrinsics::KannalaBrandt(camera) => {
+                w.u8(2);
+                camera.image_size().encode(w);
+                camera.params().encode(w);
+            }
+            RenderIntrinsics::BrownConrady(camera) => {
+                w.u8(3);
+                camera.image_size().encode(w);
+                camera.params().encode(w);
+            }
+        }
+    }
+}
+
+/// Decodes camera parameters, which start with the focal lengths fx and fy
+fn decode_camera_params<const N: usize>(r: &mut WireReader) -> Result<VecF64<N>, WireError> {
+    let params = VecF64::<N>::decode(r)?;
+    if params.iter().any(|x| !x.is_finite()) || params[0] <= 0.0 || params[1] <= 0.0 {
+        return Err(WireError::InvalidValue("camera parameters"));
+    }
+    Ok(params)
+}
+
+impl WireDecode for RenderIntrinsics {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        let tag = r.u8()?;
+        let image_size = ImageSize::decode(r)?;
+        match tag {
+            0 => Ok(RenderIntrinsics::Pinhole(PinholeCameraF64::new(
+                &decode_camera_params::<4>(r)?,
+                image_size,
+            ))),
+            1 => Ok(RenderIntrinsics::UnifiedExtended(UnifiedCameraF64::new(
+                &decode_camera_params::<6>(r)?,
+                image_size,
+            ))),
+            2 => Ok(RenderIntrinsics::KannalaBrandt(
+                KannalaBrandtCameraF64::new(&decode_camera_params::<8>(r)?, image_size),
+            )),
+            3 => Ok(RenderIntrinsics::BrownConrady(BrownConradyCameraF64::new(
+                &decode_camera_params::<12>(r)?,
+                image_size,
+            ))),
+            tag => Err(WireError::InvalidTag {
+                what: "intrinsics",
+                tag,
+            }),
+        }
+    }
+}
+
+impl WireEncode for RenderCameraProperties {
+    fn encode(&self, w: &mut WireWriter) {
+        self.intrinsics.encode(w);
+        w.f64(self.clipping_planes.near);
+        w.f64(self.clipping_planes.far);
+    }
+}
+
+impl WireDecode for RenderCameraProperties {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        Ok(RenderCameraProperties {
+            intrinsics: RenderIntrinsics::decode(r)?,
+            clipping_planes: ClippingPlanesF64 {
+                near: r.f64()?,
+                far: r.f64()?,
+            },
+        })
+    }
+}
+
+impl WireEncode for RenderCamera {
+    fn encode(&self, w: &mut WireWriter) {
+        self.scene_from_camera.encode(w);
+        self.properties.encode(w);
+    }
+}
+
+impl WireDecode for RenderCamera {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        Ok(RenderCamera {
+            scene_from_camera: Isometry3F64::decode(r)?,
+            properties: RenderCameraProperties::decode(r)?,
+        })
+    }
+}
+
+impl WireEncode for ImageFrame {
+    fn encode(&self, w: &mut WireWriter) {
+        self.image.encode(w);
+        self.camera_properties.encode(w);
+    }
+}
+
+impl WireDecode for ImageFrame {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        Ok(ImageFrame {
+            image: Option::decode(r)?,
+            camera_properties: RenderCameraProperties::decode(r)?,
+        })
+    }
+}
+
+impl WireEncode for Color {
+    fn encode(&self, w: &mut WireWriter) {
+        w.f32(self.r);
+        w.f32(self.g);
+        w.f32(self.b);
+        w.f32(self.a);
+    }
+}
+
+impl WireDecode for Color {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        Ok(Color {
+            r: r.f32()?,
+            g: r.f32()?,
+            b: r.f32()?,
+            a: r.f32()?,
+        })
+    }
+}
+
+impl WireEncode for SceneLighting {
+    fn encode(&self, w: &mut WireWriter) {
+        w.u8(match self.shading {
+            ShadingModel::Unlit => 0,
+            ShadingModel::Lambertian => 1,
+            ShadingModel::Phong => 2,
+        });
+        match self.light {
+            LightSource::Headlight => w.u8(0),
+            LightSource::Directional(direction) => {
+                w.u8(1);
+                direction.encode(w);
+            }
+        }
+        w.f32(self.ambient);
+        w.f32(self.specular);
+        w.f32(self.shininess);
+    }
+}
+
+impl WireDecode for SceneLighting {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        let shading = match r.u8()? {
+            0 => ShadingModel::Unlit,
+            1 => ShadingModel::Lambertian,
+            2 => ShadingModel::Phong,
+            tag => {
+                return Err(WireError::InvalidTag {
+                    what: "shading model",
+                    tag,
+                })
+            }
+        };
+        let light = match r.u8()? {
+            0 => LightSource::Headlight,
+            1 => LightSource::Directional(VecF64::<3>::decode(r)?),
+            tag => {
+                return Err(WireError::InvalidTag {
+                    what: "light source",
+                    tag,
+                })
+            }
+        };
+        Ok(SceneLighting {
+            shading,
+            light,
+            ambient: r.f32()?,
+            specular: r.f32()?,
+            shininess: r.f32()?,
+        })
+    }
+}
+
+impl WireEncode for PixelRenderable {
+    fn encode(&self, w: &mut WireWriter) {
+        match self {
+            PixelRenderable::Line(lines) => {
+                w.u8(0);
+                lines.name.encode(w);
+                lines.segments.encode(w);
+            }
+            PixelRenderable::Point(points) => {
+                w.u8(1);
+                points.name.encode(w);
+                points.points.encode(w);
+            }
+            PixelRenderable::Text(texts) => {
+                w.u8(2);
+                texts.name.encode(w);
+                texts.texts.encode(w);
+            }
+            PixelRenderable::Ellipse(ellipses) => {
+                w.u8(3);
+                ellipses.name.encode(w);
+                ellipses.ellipses.encode(w);
+            }
+            PixelRenderable::Box(boxes) => {
+                w.u8(4);
+                boxes.name.encode(w);
+                boxes.boxes.encode(w);
+            }
+            PixelRenderable::Polygon(polygons) => {
+                w.u8(5);
+                polygons.name.encode(w);
+                polygons.polygons.encode(w);
+            }
+            PixelRenderable::Arrow(arrows) => {
+                w.u8(6);
+                arrows.name.encode(w);
+                arrows.arrows.encode(w);
+            }
+        }
+    }
+}
+
+impl WireDecode for PixelRenderable {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        match r.u8()? {
+            0 => Ok(PixelRenderable::Line(LineSegments2 {
+                name: String::decode(r)?,
+                segments: Vec::decode(r)?,
+            })),
+            1 => Ok(PixelRenderable::Point(PointCloud2 {
+                name: String::decode(r)?,
+                points: Vec::decode(r)?,
+            })),
+            2 => Ok(PixelRenderable::Text(Texts2 {
+                name: String::decode(r)?,
+                texts: Vec::decode(r)?,
+            })),
+            3 => Ok(PixelRenderable::Ellipse(Ellipses2 {
+                name: String::decode(r)?,
+                ellipses: Vec::decode(r)?,
+            })),
+            4 => Ok(PixelRenderable::Box(Boxes2 {
+                name: String::decode(r)?,
+                boxes: Vec::decode(r)?,
+            })),
+            5 => Ok(PixelRenderable::Polygon(Polygons2 {
+                name: String::decode(r)?,
+                polygons: Vec::decode(r)?,
+            })),
+            6 => Ok(PixelRenderable::Arrow(Arrows2 {
+                name: String::decode(r)?,
+                arrows: Vec::decode(r)?,
+            })),
+            tag => Err(WireError::InvalidTag {
+                what: "pixel renderable",
+                tag,
+            }),
+        }
+    }
+}
+
+impl WireEncode for LineSegment2 {
+    fn encode(&self, w: &mut WireWriter) {
+        self.p0.encode(w);
+        self.p1.encode(w);
+        self.color.encode(w);
+        w.f32(self.line_width);
+    }
+}
+
+impl WireDecode for LineSegment2 {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        Ok(LineSegment2 {
+            p0: WireDecode::decode(r)?,
+            p1: WireDecode::decode(r)?,
+            color: Color::decode(r)?,
+            line_width: r.f32()?,
+        })
+    }
+}
+
+impl WireEncode for Point2 {
+    fn encode(&self, w: &mut WireWriter) {
+        self.p.encode(w);
+        self.color.encode(w);
+        w.f32(self.point_size);
+    }
+}
+
+impl WireDecode for Point2 {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        Ok(Point2 {
+            p: WireDecode::decode(r)?,
+            color: Color::decode(r)?,
+            point_size: r.f32()?,
+        })
+    }
+}
+
+impl WireEncode for Ellipse2 {
+    fn encode(&self, w: &mut WireWriter) {
+        self.center.encode(w);
+        self.radii.encode(w);
+        w.f32(self.angle);
+        self.color.encode(w);
+        w.f32(self.line_width);
+        self.fill_color.encode(w);
+    }
+}
+
+impl WireDecode for Ellipse2 {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        Ok(Ellipse2 {
+            center: WireDecode::decode(r)?,
+            radii: WireDecode::decode(r)?,
+            angle: r.f32()?,
+            color: Color::decode(r)?,
+            line_width: r.f32()?,
+            fill_color: Option::decode(r)?,
+        })
+    }
+}
+
+impl WireEncode for Box2 {
+    fn encode(&self, w: &mut WireWriter) {
+        self.min.encode(w);
+        self.max.encode(w);
+        self.color.encode(w);
+        w.f32(self.line_width);
+        self.fill_color.encode(w);
+    }
+}
+
+impl WireDecode for Box2 {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        Ok(Box2 {
+            min: WireDecode::decode(r)?,
+            max: WireDecode::decode(r)?,
+            color: Color::decode(r)?,
+            line_width: r.f32()?,
+            fill_color: Option::decode(r)?,
+        })
+    }
+}
+
+impl WireEncode for Polygon2 {
+    fn encode(&self, w: &mut WireWriter) {
+        self.points.encode(w);
+        self.color.encode(w);
+        w.f32(self.line_width);
+        self.fill_color.encode(w);
+    }
+}
+
+impl WireDecode for Polygon2 {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        Ok(Polygon2 {
+            points: Vec::decode(r)?,
+            color: Color::decode(r)?,
+            line_width: r.f32()?,
+            fill_color: Option::decode(r)?,
+        })
+    }
+}
+
+impl WireEncode for Arrow2 {
+    fn encode(&self, w: &mut WireWriter) {
+        self.tail.encode(w);
+        self.tip.encode(w);
+        self.color.encode(w);
+        w.f32(self.line_width);
+        w.f32(self.head_size);
+    }
+}
+
+impl WireDecode for Arrow2 {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        Ok(Arrow2 {
+            tail: WireDecode::decode(r)?,
+            tip: WireDecode::decode(r)?,
+            color: Color::decode(r)?,
+            line_width: r.f32()?,
+            head_size: r.f32()?,
+        })
+    }
+}
+
+const TEXT_ANCHORS: [TextAnchor; 9] = [
+    TextAnchor::TopLeft,
+    TextAnchor::TopCenter,
+    TextAnchor::TopRight,
+    TextAnchor::CenterLeft,
+    TextAnchor::Center,
+    TextAnchor::CenterRight,
+    TextAnchor::BottomLeft,
+    TextAnchor::BottomCenter,
+    TextAnchor::BottomRight,
+];
+
+impl WireEncode for TextAnchor {
+    fn encode(&self, w: &mut WireWriter) {
+        // the anchor is always present in the table
+        w.u8(TEXT_ANCHORS.iter().position(|a| a == self).unwrap_or(0) as u8);
+    }
+}
+
+impl WireDecode for TextAnchor {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        let tag = r.u8()?;
+        TEXT_ANCHORS
+            .get(tag as usize)
+            .copied()
+            .ok_or(WireError::InvalidTag {
+                what: "text anchor",
+                tag,
+            })
+    }
+}
+
+impl WireEncode for Text2 {
+    fn encode(&self, w: &mut WireWriter) {
+        self.text.encode(w);
+        self.p.encode(w);
+        self.anchor.encode(w);
+        self.color.encode(w);
+        w.f32(self.font_size);
+    }
+}
+
+impl WireDecode for Text2 {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        Ok(Text2 {
+            text: String::decode(r)?,
+            p: WireDecode::decode(r)?,
+            anchor: TextAnchor::decode(r)?,
+            color: Color::decode(r)?,
+            font_size: r.f32()?,
+        })
+    }
+}
+
+impl WireEncode for SceneRenderable {
+    fn encode(&self, w: &mut WireWriter) {
+        match self {
+            SceneRenderable::Line(lines) => {
+                w.u8(0);
+                lines.name.encode(w);
+                lines.segments.encode(w);
+                lines.world_from_entity.encode(w);
+            }
+            SceneRenderable::Point(points) => {
+                w.u8(1);
+                points.name.encode(w);
+                points.points.encode(w);
+                points.world_from_entity.encode(w);
+            }
+            SceneRenderable::Mesh3(mesh) => {
+                w.u8(2);
+                mesh.name.encode(w);
+                mesh.triangles.encode(w);
+                mesh.world_from_entity.encode(w);
+            }
+            SceneRenderable::TexturedMesh3(mesh) => {
+                w.u8(3);
+                mesh.name.encode(w);
+                mesh.triangles.encode(w);
+                mesh.texture.encode(w);
+                mesh.world_from_entity.encode(w);
+            }
+            SceneRenderable::Text(texts) => {
+                w.u8(4);
+                texts.name.encode(w);
+                texts.texts.encode(w);
+                texts.world_from_entity.encode(w);
+            }
+            SceneRenderable::Gaussian(gaussians) => {
+                w.u8(5);
+                gaussians.name.encode(w);
+                gaussians.gaussians.encode(w);
+                w.f32(gaussians.sigma);
+                gaussians.style.encode(w);
+                w.f32(gaussians.line_width);
+                gaussians.world_from_entity.encode(w);
+            }
+            SceneRenderable::PoseCovariance(poses) => {
+                w.u8(6);
+                poses.name.encode(w);
+                poses.poses.encode(w);
+                w.f32(poses.sigma);
+                w.f32(poses.axis_length);
+                w.f32(poses.line_width);
+                poses.color.encode(w);
+                poses.world_from_entity.encode(w);
+            }
+            SceneRenderable::Instanced(instances) => {
+                w.u8(7);
+                instances.name.encode(w);
+                instances.template.encode(w);
+                instances.instances.encode(w);
+                instances.world_from_entity.encode(w);
+            }
+            SceneRenderable::PointStream(stream) => {
+                w.u8(8);
+                stream.name.encode(w);
+                stream.points.encode(w);
+                stream.budget.encode(w);
+                stream.world_from_entity.encode(w);
+            }
+            SceneRenderable::ScalarPoint(points) => {
+                w.u8(9);
+                points.name.encode(w);
+                points.positions.encode(w);
+                points.fields.encode(w);
+                w.f32(points.point_size);
+                points.coloring.encode(w);
+                points.world_from_entity.encode(w);
+            }
+            SceneRenderable::CameraFrustum(frustum) => {
+                w.u8(10);
+                frustum.name.encode(w);
+                frustum.camera_properties.encode(w);
+                w.f64(frustum.depth);
+                frustum.image.encode(w);
+                frustum.color.encode(w);
+                w.f32(frustum.line_width);
+                frustum.world_from_entity.encode(w);
+            }
+            SceneRenderable::Polyline(polyline) => {
+                w.u8(11);
+                polyline.name.encode(w);
+                polyline.vertices.encode(w);
+                w.f32(polyline.line_width);
+                polyline.time_window.encode(w);
+                polyline.world_from_entity.encode(w);
+            }
+        }
+    }
+}
+
+impl WireDecode for SceneRenderable {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        match r.u8()? {
+            0 => Ok(SceneRenderable::Line(LineSegments3 {
+                name: String::decode(r)?,
+                segments: Vec::decode(r)?,
+                world_from_entity: Isometry3F64::decode(r)?,
+            })),
+            1 => Ok(SceneRenderable::Point(PointCloud3 {
+                name: String::decode(r)?,
+                points: Vec::decode(r)?,
+                world_from_entity: Isometry3F64::decode(r)?,
+            })),
+            2 => Ok(SceneRenderable::Mesh3(TriangleMesh3 {
+                name: String::decode(r)?,
+                triangles: Vec::decode(r)?,
+                world_from_entity: Isometry3F64::decode(r)?,
+            })),
+            3 => Ok(SceneRenderable::TexturedMesh3(TexturedTriangleMesh3 {
+                name: String::decode(r)?,
+                triangles: Vec::decode(r)?,
+                texture: ArcImage4U8::decode(r)?,
+                world_from_entity: Isometry3F64::decode(r)?,
+            })),
+            4 => Ok(SceneRenderable::Text(Texts3 {
+                name: String::decode(r)?,
+                texts: Vec::decode(r)?,
+                world_from_entity: Isometry3F64::decode(r)?,
+            })),
+            5 => Ok(SceneRenderable::Gaussian(Gaussians3 {
+                name: String::decode(r)?,
+                gaussians: Vec::decode(r)?,
+                sigma: r.f32()?,
+                style: UncertaintyStyle::decode(r)?,
+                line_width: r.f32()?,
+                world_from_entity: Isometry3F64::decode(r)?,
+            })),
+            6 => Ok(SceneRenderable::PoseCovariance(PoseCovariances3 {
+                name: String::decode(r)?,
+                poses: Vec::decode(r)?,
+                sigma: r.f32()?,
+                axis_length: r.f32()?,
+                line_width: r.f32()?,
+                color: Color::decode(r)?,
+                world_from_entity: Isometry3F64::decode(r)?,
+            })),
+            7 => Ok(SceneRenderable::Instanced(Instances3 {
+                name: String::decode(r)?,
+                template: InstanceTemplate3::decode(r)?,
+                instances: Vec::decode(r)?,
+                world_from_entity: Isometry3F64::decode(r)?,
+            })),
+            8 => Ok(SceneRenderable::PointStream(PointStream3 {
+                name: String::decode(r)?,
+                points: Vec::decode(r)?,
+                budget: PointBudget::decode(r)?,
+                world_from_entity: Isometry3F64::decode(r)?,
+            })),
+            9 => Ok(SceneRenderable::ScalarPoint(ScalarPointCloud3 {
+                name: String::decode(r)?,
+                positions: Vec::decode(r)?,
+                fields: Vec::decode(r)?,
+                point_size: r.f32()?,
+                coloring: PointColoring::decode(r)?,
+                world_from_entity: Isometry3F64::decode(r)?,
+            })),
+            10 => Ok(SceneRenderable::CameraFrustum(CameraFrustum3 {
+                name: String::decode(r)?,
+                camera_properties: RenderCameraProperties::decode(r)?,
+                depth: r.f64()?,
+                image: Option::decode(r)?,
+                color: Color::decode(r)?,
+                line_width: r.f32()?,
+                world_from_entity: Isometry3F64::decode(r)?,
+            })),
+            11 => Ok(SceneRenderable::Polyline(Polyline3 {
+                name: String::decode(r)?,
+                vertices: Vec::decode(r)?,
+                line_width: r.f32()?,
+                time_window: Option::decode(r)?,
+                world_from_entity: Isometry3F64::decode(r)?,
+            })),
+            tag => Err(WireError::InvalidTag {
+                what: "scene renderable",
+                tag,
+            }),
+        }
+    }
+}
+
+impl WireEncode for Text3 {
+    fn encode(&self, w: &mut WireWriter) {
+        self.text.encode(w);
+        self.p.encode(w);
+        self.anchor.encode(w);
+        self.color.encode(w);
+        w.f32(self.font_size);
+    }
+}
+
+impl WireDecode for Text3 {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        Ok(Text3 {
+            text: String::decode(r)?,
+            p: WireDecode::decode(r)?,
+            anchor: TextAnchor::decode(r)?,
+            color: Color::decode(r)?,
+            font_size: r.f32()?,
+        })
+    }
+}
+
+impl WireEncode for LineSegment3 {
+    fn encode(&self, w: &mut WireWriter) {
+        self.p0.encode(w);
+        self.p1.encode(w);
+        self.color.encode(w);
+        w.f32(self.line_width);
+    }
+}
+
+impl WireDecode for LineSegment3 {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        Ok(LineSegment3 {
+            p0: WireDecode::decode(r)?,
+            p1: WireDecode::decode(r)?,
+            color: Color::decode(r)?,
+            line_width: r.f32()?,
+        })
+    }
+}
+
+impl WireEncode for Point3 {
+    fn encode(&self, w: &mut WireWriter) {
+        self.p.encode(w);
+        self.color.encode(w);
+        w.f32(self.point_size);
+    }
+}
+
+impl WireDecode for Point3 {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        Ok(Point3 {
+            p: WireDecode::decode(r)?,
+            color: Color::decode(r)?,
+            point_size: r.f32()?,
+        })
+    }
+}
+
+impl WireEncode for PolylineVertex3 {
+    fn encode(&self, w: &mut WireWriter) {
+        self.p.encode(w);
+        self.color.encode(w);
+        w.f64(self.t);
+    }
+}
+
+impl WireDecode for PolylineVertex3 {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        Ok(PolylineVertex3 {
+            p: WireDecode::decode(r)?,
+            color: Color::decode(r)?,
+            t: r.f64()?,
+        })
+    }
+}
+
+impl WireEncode for Triangle3 {
+    fn encode(&self, w: &mut WireWriter) {
+        self.p0.encode(w);
+        self.p1.encode(w);
+        self.p2.encode(w);
+        self.color0.encode(w);
+        self.color1.encode(w);
+        self.color2.encode(w);
+        self.normals.encode(w);
+    }
+}
+
+impl WireDecode for Triangle3 {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        Ok(Triangle3 {
+            p0: WireDecode::decode(r)?,
+            p1: WireDecode::decode(r)?,
+            p2: WireDecode::decode(r)?,
+            color0: Color::decode(r)?,
+            color1: Color::decode(r)?,
+            color2: Color::decode(r)?,
+            normals: WireDecode::decode(r)?,
+        })
+    }
+}
+
+impl WireEncode for TexturedTriangle3 {
+    fn encode(&self, w: &mut WireWriter) {
+        self.p0.encode(w);
+        self.p1.encode(w);
+        self.p2.encode(w);
+        self.tex0.encode(w);
+        self.tex1.encode(w);
+        self.tex2.encode(w);
+    }
+}
+
+impl WireDecode for TexturedTriangle3 {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        Ok(TexturedTriangle3 {
+            p0: WireDecode::decode(r)?,
+            p1: WireDecode::decode(r)?,
+            p2: WireDecode::decode(r)?,
+            tex0: WireDecode::decode(r)?,
+            tex1: WireDecode::decode(r)?,
+            tex2: WireDecode::decode(r)?,
+        })
+    }
+}
+
+impl WireEncode for ScalarField3 {
+    fn encode(&self, w: &mut WireWriter) {
+        self.name.encode(w);
+        self.values.encode(w);
+    }
+}
+
+impl WireDecode for ScalarField3 {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        Ok(ScalarField3 {
+            name: String::decode(r)?,
+            values: Vec::decode(r)?,
+        })
+    }
+}
+
+impl WireEncode for Colormap {
+    fn encode(&self, w: &mut WireWriter) {
+        w.u8(match self {
+            Colormap::BlueWhiteRedBlack => 0,
+            Colormap::Grayscale => 1,
+            Colormap::Viridis => 2,
+            Colormap::Turbo => 3,
+        });
+    }
+}
+
+impl WireDecode for Colormap {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        match r.u8()? {
+            0 => Ok(Colormap::BlueWhiteRedBlack),
+            1 => Ok(Colormap::Grayscale),
+            2 => Ok(Colormap::Viridis),
+            3 => Ok(Colormap::Turbo),
+            tag => Err(WireError::InvalidTag {
+                what: "colormap",
+                tag,
+            }),
+        }
+    }
+}
+
+impl WireEncode for PointColoring {
+    fn encode(&self, w: &mut WireWriter) {
+        w.len(self.field);
+        self.colormap.encode(w);
+        self.range.encode(w);
+    }
+}
+
+impl WireDecode for PointColoring {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        Ok(PointColoring {
+            field: r.len()?,
+            colormap: Colormap::decode(r)?,
+            range: Option::decode(r)?,
+        })
+    }
+}
+
+impl WireEncode for PointBudget {
+    fn encode(&self, w: &mut WireWriter) {
+        // an unbounded budget is sent as u32::MAX
+        w.u32(self.max_points.min(u32::MAX as usize) as u32);
+        w.f32(self.voxel_size);
+    }
+}
+
+impl WireDecode for PointBudget {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        Ok(PointBudget {
+            max_points: match r.u32()? {
+                u32::MAX => usize::MAX,
+                max_points => max_points as usize,
+            },
+            voxel_size: r.f32()?,
+        })
+    }
+}
+
+impl WireEncode for UncertaintyStyle {
+    fn encode(&self, w: &mut WireWriter) {
+        w.u8(match self {
+            UncertaintyStyle::Ellipsoid => 0,
+            UncertaintyStyle::PrincipalEllipses => 1,
+        });
+    }
+}
+
+impl WireDecode for UncertaintyStyle {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        match r.u8()? {
+            0 => Ok(UncertaintyStyle::Ellipsoid),
+            1 => Ok(UncertaintyStyle::PrincipalEllipses),
+            tag => Err(WireError::InvalidTag {
+                what: "uncertainty style",
+                tag,
+            }),
+        }
+    }
+}
+
+impl WireEncode for Gaussian3 {
+    fn encode(&self, w: &mut WireWriter) {
+        self.mean.encode(w);
+        self.covariance.encode(w);
+        self.color.encode(w);
+    }
+}
+
+impl WireDecode for Gaussian3 {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        Ok(Gaussian3 {
+            mean: WireDecode::decode(r)?,
+            covariance: WireDecode::decode(r)?,
+            color: Color::decode(r)?,
+        })
+    }
+}
+
+impl WireEncode for PoseCovariance3 {
+    fn encode(&self, w: &mut WireWriter) {
+        self.world_from_pose.encode(w);
+        self.covariance.encode(w);
+    }
+}
+
+impl WireDecode for PoseCovariance3 {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        Ok(PoseCovariance3 {
+            world_from_pose: Isometry3F64::decode(r)?,
+            covariance: WireDecode::decode(r)?,
+        })
+    }
+}
+
+impl WireEncode for InstanceTemplate3 {
+    fn encode(&self, w: &mut WireWriter) {
+        match self {
+            InstanceTemplate3::Mesh(triangles) => {
+                w.u8(0);
+                triangles.encode(w);
+            }
+            InstanceTemplate3::Lines(segments) => {
+                w.u8(1);
+                segments.encode(w);
+            }
+        }
+    }
+}
+
+impl WireDecode for InstanceTemplate3 {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        match r.u8()? {
+            0 => Ok(InstanceTemplate3::Mesh(Vec::decode(r)?)),
+            1 => Ok(InstanceTemplate3::Lines(Vec::decode(r)?)),
+            tag => Err(WireError::InvalidTag {
+                what: "instance template",
+                tag,
+            }),
+        }
+    }
+}
+
+impl WireEncode for Instance3 {
+    fn encode(&self, w: &mut WireWriter) {
+        self.entity_from_instance.encode(w);
+        self.color.encode(w);
+        w.f32(self.scale);
+    }
+}
+
+impl WireDecode for Instance3 {
+    fn decode(r: &mut WireReader) -> Result<Self, WireError> {
+        Ok(Instance3 {
+            entity_from_instance: Isometry3F64::decode(r)?,
+            color: Color::decode(r)?,
+            scale: r.f32()?,
+        })
+    }
+}
+
+#[test]
+fn wire_rejects_invalid_sizes_and_intrinsics() {
+    let decode_intrinsics = |width: u32, height: u32, params: [f64; 4]| {
+        let mut w = WireWriter::default();
+        w.u8(0);
+        w.u32(width);
+        w.u32(height);
+        for param in params {
+            w.f64(param);
+        }
+        RenderIntrinsics::decode(&mut WireReader::new(&w.buf))
+    };
+    let invalid = |what| Some(WireError::InvalidValue(what));
+
+    assert!(decode_intrinsics(640, 480, [500.0, 500.0, 320.0, 240.0]).is_ok());
+    assert_eq!(
+        decode_intrinsics(0, 0, [500.0, 500.0, 320.0, 240.0]).err(),
+        invalid("image size")
+    );
+    assert_eq!(
+        decode_intrinsics(640, 0, [500.0, 500.0, 320.0, 240.0]).err(),
+        invalid("image size")
+    );
+    for params in [
+        [0.0, 500.0, 320.0, 240.0],
+        [500.0, 0.0, 320.0, 240.0],
+        [f64::NAN, 500.0, 320.0, 240.0],
+        [500.0, 500.0, f64::INFINITY, 240.0],
+    ] {
+        assert_eq!(
+            decode_intrinsics(640, 480, params).err(),
+            invalid("camera parameters")
+        );
+    }
+
+    let mut w = WireWriter::default();
+    w.u32(0);
+    w.u32(0);
+    assert_eq!(
+        ArcImage4U8::decode(&mut WireReader::new(&w.buf)).err(),
+        invalid("image size")
+    );
+}
@@ -0,0 +1,25 @@
+use crate::preludes::*;
+use dog_tv_renderer::types::ScenePick;
+
+/// What triggered a pick event
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PickTrigger {
+    /// the cursor moved onto a different scene entity or primitive
+    #[default]
+    Hover,
+    /// the scene entity was clicked
+    Click,
+}
+
+/// Scene entity and primitive under the cursor, sent back to the application
+///
+/// See `ViewerBase::set_pick_sender`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PickEvent {
+    /// label of the view the cursor is in
+    pub view_label: String,
+    /// hover or click
+    pub trigger: PickTrigger,
+    /// the picked entity and primitive
+    pub pick: ScenePick,
+}
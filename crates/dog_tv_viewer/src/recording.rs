@@ -0,0 +1,352 @@
+use crate::packets::wire::decode_header;
+use crate::packets::wire::decode_payload;
+use crate::packets::wire::encode_packets;
+use crate::packets::wire::WIRE_HEADER_LEN;
+use crate::packets::Packet;
+use crate::preludes::*;
+use std::format;
+use std::fs::File;
+use std::io;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+use std::path::Path;
+use std::time::Instant;
+use std::vec;
+
+extern crate std;
+
+/// Magic bytes at the start of a packet log file
+pub const LOG_MAGIC: [u8; 4] = *b"DOGL";
+
+/// Version of the packet log format
+///
+/// The log file starts with the magic bytes, the log format version (u16) and two reserved bytes.
+/// It is followed by one record per batch: the timestamp in nanoseconds since the start of the
+/// recording (u64), followed by the batch encoded as a wire format message.
+pub const LOG_FORMAT_VERSION: u16 = 1;
+
+const LOG_HEADER_LEN: usize = 8;
+
+/// Records packet batches, with timestamps, into a log file
+pub struct PacketRecorder {
+    writer: BufWriter<File>,
+    start: Instant,
+}
+
+impl PacketRecorder {
+    /// Create a new log file, an existing file is overwritten
+    pub fn create(path: impl AsRef<Path>) -> io::Result<PacketRecorder> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(&LOG_MAGIC)?;
+        writer.write_all(&LOG_FORMAT_VERSION.to_le_bytes())?;
+        writer.write_all(&[0, 0])?;
+        writer.flush()?;
+        Ok(PacketRecorder {
+            writer,
+            start: Instant::now(),
+        })
+    }
+
+    /// Append a batch of packets, timestamped relative to the creation of the recorder
+    ///
+    /// Each batch is flushed, so that the log is usable even if the process crashes.
+    pub fn record(&mut self, packets: &[Packet]) -> io::Result<()> {
+        let message = encode_packets(packets)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err.to_string()))?;
+        let timestamp_ns = self.start.elapsed().as_nanos() as u64;
+        self.writer.write_all(&timestamp_ns.to_le_bytes())?;
+        self.writer.write_all(&message)?;
+        self.writer.flush()
+    }
+}
+
+/// Batch of packets from a log file
+#[derive(Clone, Debug)]
+pub struct RecordedBatch {
+    /// Time since the start of the recording, in seconds
+    pub timestamp: f64,
+    /// The packets
+    pub packets: Vec<Packet>,
+}
+
+/// Read all batches of a log file
+///
+/// A truncated last record, e.g. from a crashed process, is ignored. To replay large logs, use
+/// `PacketReplay::open`, which decodes the batches as they are released.
+pub fn read_packet_log(path: impl AsRef<Path>) -> io::Result<Vec<RecordedBatch>> {
+    let mut replay = PacketReplay::open(path)?;
+    (0..replay.timestamps.len())
+        .map(|index| replay.batch(index))
+        .collect()
+}
+
+// Location of the payload of a batch in a log file
+#[derive(Clone, Copy)]
+struct PayloadLocation {
+    offset: u64,
+    len: usize,
+}
+
+/// Reads the timestamp and payload location of each record of a log file, without decoding
+fn index_packet_log(file: &mut File) -> io::Result<(Vec<f64>, Vec<PayloadLocation>)> {
+    let file_len = file.metadata()?.len();
+    let mut reader = BufReader::new(file);
+
+    let mut header = [0u8; LOG_HEADER_LEN];
+    reader.read_exact(&mut header)?;
+    if header[0..4] != LOG_MAGIC {
+        return Err(invalid_data("not a dog-tv packet log"));
+    }
+    let version = u16::from_le_bytes([header[4], header[5]]);
+    if version != LOG_FORMAT_VERSION {
+        return Err(invalid_data(&format!(
+            "unsupported packet log version {}, expected {}",
+            version, LOG_FORMAT_VERSION
+        )));
+    }
+
+    let mut timestamps = vec![];
+    let mut payloads = vec![];
+    let mut offset = LOG_HEADER_LEN as u64;
+    let mut timestamp_ns = [0u8; 8];
+    let mut message_header = [0u8; WIRE_HEADER_LEN];
+    loop {
+        if reader.read_exact(&mut timestamp_ns).is_err()
+            || reader.read_exact(&mut message_header).is_err()
+        {
+            break;
+        }
+        let payload_len = decode_header(&message_header).map_err(|e| invalid_data(&e))?;
+        offset += (timestamp_ns.len() + WIRE_HEADER_LEN) as u64;
+        if offset + payload_len as u64 > file_len {
+            break;
+        }
+        timestamps.push(u64::from_le_bytes(timestamp_ns) as f64 * 1e-9);
+        payloads.push(PayloadLocation {
+            offset,
+            len: payload_len,
+        });
+        offset += payload_len as u64;
+        reader.seek_relative(payload_len as i64)?;
+    }
+    Ok((timestamps, payloads))
+}
+
+fn invalid_data(err: &(impl ToString + ?Sized)) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err.to_string())
+}
+
+// Packets of the batches of a replay
+enum ReplaySource {
+    // log file, with the payload location of each batch
+    Log {
+        file: File,
+        payloads: Vec<PayloadLocation>,
+    },
+    Decoded(Vec<Vec<Packet>>),
+}
+
+/// Replay of recorded packet batches
+///
+/// Batches are released according to their timestamps, at real-time or scaled speed. The replay
+/// starts playing immediately.
+pub struct PacketReplay {
+    timestamps: Vec<f64>,
+    source: ReplaySource,
+    next: usize,
+    time: f64,
+    playing: bool,
+    speed: f64,
+    last_poll: Option<Instant>,
+}
+
+impl PacketReplay {
+    /// Open a log file for replay
+    ///
+    /// Only the timestamps and locations of the batches are read up front, each batch is decoded
+    /// when it is released.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<PacketReplay> {
+        let mut file = File::open(path)?;
+        let (timestamps, payloads) = index_packet_log(&mut file)?;
+        Ok(PacketReplay::from_source(
+            timestamps,
+            ReplaySource::Log { file, payloads },
+        ))
+    }
+
+    /// Create a replay from recorded batches, sorted by timestamp
+    pub fn new(batches: Vec<RecordedBatch>) -> PacketReplay {
+        let (timestamps, packets) = batches
+            .into_iter()
+            .map(|batch| (batch.timestamp, batch.packets))
+            .unzip();
+        PacketReplay::from_source(timestamps, ReplaySource::Decoded(packets))
+    }
+
+    fn from_source(timestamps: Vec<f64>, source: ReplaySource) -> PacketReplay {
+        PacketReplay {
+            timestamps,
+            source,
+            next: 0,
+            time: 0.0,
+            playing: true,
+            speed: 1.0,
+            last_poll: None,
+        }
+    }
+
+    /// Duration of the recording in seconds
+    pub fn duration(&self) -> f64 {
+        self.timestamps.last().copied().unwrap_or(0.0)
+    }
+
+    /// Current replay time in seconds
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    /// Is the replay playing, i.e. not paused
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// Play or pause the replay
+    pub fn set_playing(&mut self, playing: bool) {
+        self.playing = playing;
+        self.last_poll = None;
+    }
+
+    /// Replay speed, 1.0 is real-time
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    /// Set the replay speed, 1.0 is real-time
+    pub fn set_speed(&mut self, speed: f64) {
+        self.speed = speed.max(0.0);
+    }
+
+    /// Have all batches been released
+    pub fn is_finished(&self) -> bool {
+        self.next == self.timestamps.len()
+    }
+
+    /// Advance the replay time by the wall-clock time since the last poll, and return all batches
+    /// which became due
+    #[cfg(feature = "viewer")]
+    pub(crate) fn poll(&mut self) -> io::Result<Vec<RecordedBatch>> {
+        let now = Instant::now();
+        if self.playing {
+            if let Some(last_poll) = self.last_poll {
+                self.time += (now - last_poll).as_secs_f64() * self.speed;
+            }
+            self.last_poll = Some(now);
+        }
+        self.release_until(self.time)
+    }
+
+    /// Pause and release the next batch
+    #[cfg(feature = "viewer")]
+    pub(crate) fn step(&mut self) -> io::Result<Vec<RecordedBatch>> {
+        self.set_playing(false);
+        if self.is_finished() {
+            return Ok(vec![]);
+        }
+        self.time = self.timestamps[self.next];
+        let batch = self.batch(self.next)?;
+        self.next += 1;
+        Ok(vec![batch])
+    }
+
+    /// Move forward to the given time, and return all batches released on the way
+    ///
+    /// Seeking backwards is done by `rewind` followed by `seek`.
+    #[cfg(feature = "viewer")]
+    pub(crate) fn seek(&mut self, time: f64) -> io::Result<Vec<RecordedBatch>> {
+        self.time = time;
+        self.last_poll = None;
+        self.release_until(time)
+    }
+
+    /// Rewind to the start of the recording, no batches are released
+    #[cfg(feature = "viewer")]
+    pub(crate) fn rewind(&mut self) {
+        self.next = 0;
+        self.time = 0.0;
+        self.last_poll = None;
+    }
+
+    #[cfg(feature = "viewer")]
+    fn release_until(&mut self, time: f64) -> io::Result<Vec<RecordedBatch>> {
+        let mut batches = vec![];
+        while self.next < self.timestamps.len() && self.timestamps[self.next] <= time {
+            batches.push(self.batch(self.next)?);
+            self.next += 1;
+        }
+        Ok(batches)
+    }
+
+    /// Decodes the batch of the given index
+    fn batch(&mut self, index: usize) -> io::Result<RecordedBatch> {
+        let packets = match &mut self.source {
+            ReplaySource::Log { file, payloads } => {
+                let location = payloads[index];
+                let mut payload = vec![0u8; location.len];
+                file.seek(SeekFrom::Start(location.offset))?;
+                file.read_exact(&mut payload)?;
+                decode_payload(&payload).map_err(|e| invalid_data(&e))?
+            }
+            ReplaySource::Decoded(packets) => packets[index].clone(),
+        };
+        Ok(RecordedBatch {
+            timestamp: self.timestamps[index],
+            packets,
+        })
+    }
+}
+
+#[test]
+fn replay_decodes_indexed_batches() {
+    use std::string::ToString;
+
+    let path = std::env::temp_dir().join(format!("dog_tv_replay_{}.log", std::process::id()));
+    let batch = |name: &str| {
+        vec![crate::packets::delete_from_scene_packet(
+            "scene",
+            vec![name.to_string()],
+        )]
+    };
+    let mut recorder = PacketRecorder::create(&path).unwrap();
+    for name in ["a", "b", "c"] {
+        recorder.record(&batch(name)).unwrap();
+    }
+    drop(recorder);
+
+    let batches = read_packet_log(&path).unwrap();
+    assert_eq!(batches.len(), 3);
+    assert_eq!(
+        encode_packets(&batches[1].packets).unwrap(),
+        encode_packets(&batch("b")).unwrap()
+    );
+
+    // a truncated last record, e.g. from a crashed process, is ignored
+    let len = std::fs::metadata(&path).unwrap().len();
+    std::fs::OpenOptions::new()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_len(len - 1)
+        .unwrap();
+    let mut replay = PacketReplay::open(&path).unwrap();
+    assert_eq!(replay.timestamps.len(), 2);
+    assert_eq!(replay.duration(), batches[1].timestamp);
+    assert_eq!(
+        encode_packets(&replay.batch(0).unwrap().packets).unwrap(),
+        encode_packets(&batch("a")).unwrap()
+    );
+    std::fs::remove_file(&path).unwrap();
+}
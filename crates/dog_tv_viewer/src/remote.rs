@@ -0,0 +1,326 @@
+use crate::packets::wire::decode_header;
+use crate::packets::wire::decode_payload;
+use crate::packets::wire::encode_packets;
+use crate::packets::wire::WIRE_HEADER_LEN;
+use crate::packets::Packet;
+use crate::preludes::*;
+use log::warn;
+use std::io;
+use std::io::Read;
+use std::io::Write;
+use std::net::Ipv4Addr;
+use std::net::Ipv6Addr;
+use std::net::SocketAddr;
+use std::net::TcpListener;
+use std::net::TcpStream;
+#[cfg(unix)]
+use std::os::unix::fs::FileTypeExt;
+#[cfg(unix)]
+use std::os::unix::net::UnixListener;
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+#[cfg(unix)]
+use std::path::Path;
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::thread;
+use std::thread::JoinHandle;
+use thingbuf::mpsc::blocking::Sender;
+
+extern crate std;
+
+/// Address of a packet server
+#[derive(Clone, Debug)]
+pub enum PacketAddress {
+    /// TCP socket address, e.g. "127.0.0.1:7878"
+    Tcp(String),
+    /// Path of a Unix domain socket
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl Default for PacketAddress {
+    fn default() -> Self {
+        PacketAddress::Tcp(PacketAddress::DEFAULT_TCP.to_string())
+    }
+}
+
+impl PacketAddress {
+    /// default local TCP address
+    pub const DEFAULT_TCP: &'static str = "127.0.0.1:7878";
+}
+
+enum Stream {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(stream) => stream.read(buf),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(stream) => stream.write(buf),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Stream::Tcp(stream) => stream.flush(),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.flush(),
+        }
+    }
+}
+
+/// Packet server
+///
+/// Accepts connections on a local TCP port or Unix socket. Each message received is decoded into
+/// a `Vec<Packet>` batch and forwarded to the viewer through the same channel used for
+/// in-process packets, see `ViewerBaseConfig`. Each connection is served on its own thread, and
+/// batches of a single connection are forwarded in order.
+///
+/// When the server is dropped, it stops listening and the Unix socket file is removed.
+/// Connections already accepted are served until the client disconnects.
+pub struct PacketServer {
+    address: PacketAddress,
+    shutdown: Arc<AtomicBool>,
+    listener_thread: Option<JoinHandle<()>>,
+}
+
+impl PacketServer {
+    /// Start listening on the given address and forward received packets to `message_send`
+    ///
+    /// A connection is closed if it sends a malformed message, or once the receiver of
+    /// `message_send` is dropped. A stale Unix socket file, which no server listens on, is
+    /// replaced.
+    pub fn spawn(
+        address: PacketAddress,
+        message_send: Sender<Vec<Packet>>,
+    ) -> io::Result<PacketServer> {
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let listener_shutdown = shutdown.clone();
+        match address {
+            PacketAddress::Tcp(address) => {
+                let listener = TcpListener::bind(&address)?;
+                // resolves port 0 to the actual port
+                let local_address = listener.local_addr()?.to_string();
+                let listener_thread = thread::spawn(move || {
+                    accept_connections(
+                        listener.incoming(),
+                        Stream::Tcp,
+                        message_send,
+                        &listener_shutdown,
+                    )
+                });
+                Ok(PacketServer {
+                    address: PacketAddress::Tcp(local_address),
+                    shutdown,
+                    listener_thread: Some(listener_thread),
+                })
+            }
+            #[cfg(unix)]
+            PacketAddress::Unix(path) => {
+                remove_stale_socket(&path)?;
+                let listener = UnixListener::bind(&path)?;
+                let listener_thread = thread::spawn(move || {
+                    accept_connections(
+                        listener.incoming(),
+                        Stream::Unix,
+                        message_send,
+                        &listener_shutdown,
+                    )
+                });
+                Ok(PacketServer {
+                    address: PacketAddress::Unix(path),
+                    shutdown,
+                    listener_thread: Some(listener_thread),
+                })
+            }
+        }
+    }
+
+    /// Address the server is listening on
+    pub fn address(&self) -> &PacketAddress {
+        &self.address
+    }
+}
+
+impl Drop for PacketServer {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        if let Some(listener_thread) = self.listener_thread.take() {
+            // the listener checks the flag once it accepted the connection
+            match connect_to_self(&self.address) {
+                Ok(()) => {
+                    let _ = listener_thread.join();
+                }
+                Err(err) => warn!("Failed to stop the packet server: {}", err),
+            }
+        }
+        #[cfg(unix)]
+        if let PacketAddress::Unix(path) = &self.address {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Connects to the address of the server and closes the connection right away
+fn connect_to_self(address: &PacketAddress) -> io::Result<()> {
+    match address {
+        PacketAddress::Tcp(address) => {
+            let mut address: SocketAddr = address
+                .parse()
+                .map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))?;
+            // a server listening on all interfaces is reachable through the loopback interface
+            if address.ip().is_unspecified() {
+                address.set_ip(match address {
+                    SocketAddr::V4(_) => Ipv4Addr::LOCALHOST.into(),
+                    SocketAddr::V6(_) => Ipv6Addr::LOCALHOST.into(),
+                });
+            }
+            TcpStream::connect(address).map(drop)
+        }
+        #[cfg(unix)]
+        PacketAddress::Unix(path) => UnixStream::connect(path).map(drop),
+    }
+}
+
+/// Removes the socket file left behind by a server which did not shut down cleanly
+#[cfg(unix)]
+fn remove_stale_socket(path: &Path) -> io::Result<()> {
+    let is_socket = std::fs::symlink_metadata(path)
+        .map(|metadata| metadata.file_type().is_socket())
+        .unwrap_or(false);
+    if is_socket && UnixStream::connect(path).is_err() {
+        std::fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+fn accept_connections<S: Send + 'static>(
+    incoming: impl Iterator<Item = io::Result<S>>,
+    to_stream: fn(S) -> Stream,
+    message_send: Sender<Vec<Packet>>,
+    shutdown: &AtomicBool,
+) {
+    for connection in incoming {
+        if shutdown.load(Ordering::SeqCst) {
+            return;
+        }
+        match connection {
+            Ok(connection) => {
+                let message_send = message_send.clone();
+                thread::spawn(move || serve_connection(to_stream(connection), message_send));
+            }
+            Err(err) => {
+                warn!("Failed to accept connection: {}", err);
+            }
+        }
+    }
+}
+
+fn serve_connection(mut stream: Stream, message_send: Sender<Vec<Packet>>) {
+    let mut header = [0u8; WIRE_HEADER_LEN];
+    loop {
+        if stream.read_exact(&mut header).is_err() {
+            // disconnected
+            return;
+        }
+        let payload_len = match decode_header(&header) {
+            Ok(payload_len) => payload_len,
+            Err(err) => {
+                warn!("Closing connection, invalid header: {}", err);
+                return;
+            }
+        };
+        // grows with the bytes actually received, not with the announced length
+        let mut payload = Vec::new();
+        match (&mut stream)
+            .take(payload_len as u64)
+            .read_to_end(&mut payload)
+        {
+            Ok(n) if n == payload_len => {}
+            Ok(n) => {
+                warn!(
+                    "Closing connection, incomplete message: {} of {} bytes",
+                    n, payload_len
+                );
+                return;
+            }
+            Err(err) => {
+                warn!("Closing connection, incomplete message: {}", err);
+                return;
+            }
+        }
+        match decode_payload(&payload) {
+            Ok(packets) => {
+                if message_send.send(packets).is_err() {
+                    // viewer was closed
+                    return;
+                }
+            }
+            Err(err) => {
+                warn!("Closing connection, invalid message: {}", err);
+                return;
+            }
+        }
+    }
+}
+
+/// Packet client, sends packets to a `PacketServer`
+pub struct PacketClient {
+    stream: Stream,
+}
+
+impl PacketClient {
+    /// Connect to a packet server
+    pub fn connect(address: &PacketAddress) -> io::Result<PacketClient> {
+        let stream = match address {
+            PacketAddress::Tcp(address) => {
+                let stream = TcpStream::connect(address)?;
+                stream.set_nodelay(true)?;
+                Stream::Tcp(stream)
+            }
+            #[cfg(unix)]
+            PacketAddress::Unix(path) => Stream::Unix(UnixStream::connect(path)?),
+        };
+        Ok(PacketClient { stream })
+    }
+
+    /// Send a batch of packets
+    ///
+    /// The batch is forwarded to the viewer as a whole, just like a `Vec<Packet>` sent through the
+    /// in-process channel.
+    pub fn send(&mut self, packets: &[Packet]) -> io::Result<()> {
+        let message = encode_packets(packets)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err.to_string()))?;
+        self.stream.write_all(&message)
+    }
+}
+
+#[test]
+fn dropped_server_stops_listening() {
+    let (message_send, _message_recv) = thingbuf::mpsc::blocking::channel(1);
+    let server =
+        PacketServer::spawn(PacketAddress::Tcp("127.0.0.1:0".into()), message_send).unwrap();
+    let address = server.address().clone();
+    assert!(PacketClient::connect(&address).is_ok());
+
+    drop(server);
+    assert!(PacketClient::connect(&address).is_err());
+}
@@ -1,10 +1,17 @@
 use crate::packets::Packet;
+use crate::picking::PickEvent;
 use crate::preludes::*;
+#[cfg(feature = "recording")]
+use crate::recording::PacketReplay;
 use crate::viewer_base::ViewerBase;
 use crate::viewer_base::ViewerBaseConfig;
 use dog_tv_renderer::RenderContext;
 use eframe::egui;
 use thingbuf::mpsc::blocking::Receiver;
+use thingbuf::mpsc::blocking::Sender;
+
+#[cfg(feature = "recording")]
+extern crate std;
 
 /// Simple viewer
 pub struct SimpleViewer {
@@ -21,6 +28,31 @@ impl SimpleViewer {
             base: ViewerBase::new(render_state, ViewerBaseConfig { message_recv }),
         })
     }
+
+    /// Create a new simple viewer from a config, e.g. `ViewerBaseConfig::listen`
+    pub fn from_config(render_state: RenderContext, config: ViewerBaseConfig) -> Box<SimpleViewer> {
+        Box::new(SimpleViewer {
+            base: ViewerBase::new(render_state, config),
+        })
+    }
+
+    /// Send pick events to the application, see `ViewerBase::set_pick_sender`
+    pub fn set_pick_sender(&mut self, pick_send: Sender<PickEvent>) {
+        self.base.set_pick_sender(pick_send);
+    }
+}
+
+#[cfg(feature = "recording")]
+impl SimpleViewer {
+    /// Record all packets received from now on into a log file, see `PacketRecorder`
+    pub fn start_recording(&mut self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        self.base.start_recording(path)
+    }
+
+    /// Replay recorded packets, see `ViewerBase::start_replay`
+    pub fn start_replay(&mut self, replay: PacketReplay) {
+        self.base.start_replay(replay);
+    }
 }
 
 impl eframe::App for SimpleViewer {
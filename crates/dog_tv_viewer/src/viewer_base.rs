@@ -1,8 +1,24 @@
+#[cfg(feature = "std")]
+use crate::frame_export::FrameExportFormat;
+#[cfg(feature = "std")]
+use crate::frame_export::FrameExporter;
 use crate::interactions::ViewportScale;
 use crate::packets::plot_view_packet::curve_vec_with_conf::CurveVecWithConf;
 use crate::packets::plot_view_packet::vec_curve::CurveVec;
 use crate::packets::plot_view_packet::LineType;
 use crate::packets::Packet;
+use crate::picking::PickEvent;
+use crate::picking::PickTrigger;
+#[cfg(feature = "recording")]
+use crate::recording::PacketRecorder;
+#[cfg(feature = "recording")]
+use crate::recording::PacketReplay;
+#[cfg(feature = "recording")]
+use crate::recording::RecordedBatch;
+#[cfg(feature = "remote")]
+use crate::remote::PacketAddress;
+#[cfg(feature = "remote")]
+use crate::remote::PacketServer;
 use crate::views::active_view_info::ActiveViewInfo;
 use crate::views::get_adjusted_view_size;
 use crate::views::get_max_size;
@@ -15,10 +31,17 @@ use crate::views::ViewportSize;
 use alloc::collections::BTreeMap;
 use alloc::format;
 use alloc::string::String;
+use alloc::string::ToString;
 use alloc::vec;
 use alloc::vec::Vec;
 use dog_tv_renderer::aspect_ratio::HasAspectRatio;
 use dog_tv_renderer::renderables::color::Color;
+use dog_tv_renderer::renderables::color::Colormap;
+use dog_tv_renderer::scene_helpers::GridPlane;
+use dog_tv_renderer::scene_helpers::SceneHelpers;
+use dog_tv_renderer::scene_renderer::scalar_point::ScalarPoint3dEntity;
+use dog_tv_renderer::textures::depth_image::DepthImage;
+use dog_tv_renderer::types::ScenePick;
 use dog_tv_renderer::RenderContext;
 use eframe::egui;
 use eframe::egui::Ui;
@@ -26,13 +49,20 @@ use egui_plot::LineStyle;
 use egui_plot::PlotUi;
 use egui_plot::VLine;
 use linked_hash_map::LinkedHashMap;
+#[cfg(feature = "recording")]
+use log::warn;
+use sophus::core::linalg::VecF64;
 use sophus::image::arc_image::ArcImageF32;
+use sophus::image::image_view::IsImageView;
 use sophus::image::ImageSize;
 use sophus::prelude::HasParams;
 use sophus::prelude::IsTranslationProductGroup;
 use thingbuf::mpsc::blocking::Receiver;
+use thingbuf::mpsc::blocking::Sender;
 
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 
 /// Viewer top-level struct.
 pub struct ViewerBase {
@@ -41,9 +71,29 @@ pub struct ViewerBase {
     message_recv: Receiver<Vec<Packet>>,
     show_depth: bool,
     backface_culling: bool,
+    scene_helpers: SceneHelpers,
+    show_scale_bar: bool,
     responses: BTreeMap<String, ResponseStruct>,
     active_view: String,
     active_view_info: Option<ActiveViewInfo>,
+    pick_request: Option<PickRequest>,
+    // view label and cursor position of the last pick
+    picked_pixel: Option<(String, VecF64<2>)>,
+    hovered_pick: Option<(String, ScenePick)>,
+    pick_send: Option<Sender<PickEvent>>,
+    #[cfg(feature = "recording")]
+    recorder: Option<PacketRecorder>,
+    #[cfg(feature = "recording")]
+    replay: Option<PacketReplay>,
+    #[cfg(feature = "std")]
+    exporter: FrameExporter,
+}
+
+// Pick to be done in the next rendered frame of the view
+struct PickRequest {
+    view_label: String,
+    pixel: VecF64<2>,
+    clicked: bool,
 }
 
 pub(crate) struct ResponseStruct {
@@ -59,6 +109,19 @@ pub struct ViewerBaseConfig {
     pub message_recv: Receiver<Vec<Packet>>,
 }
 
+#[cfg(feature = "remote")]
+impl ViewerBaseConfig {
+    /// Configuration which receives packets from other processes, see `PacketServer`.
+    ///
+    /// The returned server tells the address actually listened on, e.g. the port chosen for
+    /// port 0.
+    pub fn listen(address: PacketAddress) -> std::io::Result<(ViewerBaseConfig, PacketServer)> {
+        let (message_send, message_recv) = thingbuf::mpsc::blocking::channel(50);
+        let server = PacketServer::spawn(address, message_send)?;
+        Ok((ViewerBaseConfig { message_recv }, server))
+    }
+}
+
 impl ViewerBase {
     /// Create a new viewer.
     pub fn new(render_state: RenderContext, config: ViewerBaseConfig) -> ViewerBase {
@@ -68,21 +131,241 @@ impl ViewerBase {
             message_recv: config.message_recv,
             show_depth: false,
             backface_culling: false,
+            scene_helpers: SceneHelpers {
+                show_grid: true,
+                show_origin_axes: true,
+                ..Default::default()
+            },
+            show_scale_bar: false,
             responses: BTreeMap::new(),
             active_view_info: None,
             active_view: Default::default(),
+            pick_request: None,
+            picked_pixel: None,
+            hovered_pick: None,
+            pick_send: None,
+            #[cfg(feature = "recording")]
+            recorder: None,
+            #[cfg(feature = "recording")]
+            replay: None,
+            #[cfg(feature = "std")]
+            exporter: FrameExporter::new(),
         }
     }
 
     /// Update the data.
     pub fn update_data(&mut self) {
-        Self::process_simple_packets(&mut self.views, &self.state, &self.message_recv);
+        while let Ok(packets) = self.message_recv.try_recv() {
+            #[cfg(feature = "recording")]
+            {
+                if self.replay.is_some() {
+                    continue;
+                }
+                if let Some(recorder) = self.recorder.as_mut() {
+                    if let Err(err) = recorder.record(&packets) {
+                        warn!("Stopped recording: {}", err);
+                        self.recorder = None;
+                    }
+                }
+            }
+            Self::process_packets(&mut self.views, &self.state, packets);
+        }
+
+        #[cfg(feature = "recording")]
+        if let Some(replay) = self.replay.as_mut() {
+            let batches = replay.poll();
+            self.process_replayed(batches);
+        }
+    }
+
+    /// Record all packets received from now on into a log file, see `PacketRecorder`.
+    #[cfg(feature = "recording")]
+    pub fn start_recording(&mut self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        self.recorder = Some(PacketRecorder::create(path)?);
+        Ok(())
+    }
+
+    /// Stop recording.
+    #[cfg(feature = "recording")]
+    pub fn stop_recording(&mut self) {
+        self.recorder = None;
+    }
+
+    /// Replay recorded packets, all current views are removed.
+    ///
+    /// While replaying, packets received through the message channel are dropped.
+    #[cfg(feature = "recording")]
+    pub fn start_replay(&mut self, replay: PacketReplay) {
+        self.clear_views();
+        self.replay = Some(replay);
+    }
+
+    /// Stop the replay, the views are kept.
+    #[cfg(feature = "recording")]
+    pub fn stop_replay(&mut self) {
+        self.replay = None;
+    }
+
+    #[cfg(feature = "recording")]
+    fn step_replay(&mut self) {
+        if let Some(replay) = self.replay.as_mut() {
+            let batches = replay.step();
+            self.process_replayed(batches);
+        }
+    }
+
+    #[cfg(feature = "recording")]
+    fn seek_replay(&mut self, time: f64) {
+        if let Some(replay) = self.replay.as_mut() {
+            if time < replay.time() {
+                // packets are incremental, so we need to start from scratch
+                self.views.clear();
+                self.active_view_info = None;
+                replay.rewind();
+            }
+            let batches = replay.seek(time);
+            self.process_replayed(batches);
+        }
+    }
+
+    /// Processes replayed batches, the replay is stopped if a batch could not be read
+    #[cfg(feature = "recording")]
+    fn process_replayed(&mut self, batches: std::io::Result<Vec<RecordedBatch>>) {
+        match batches {
+            Ok(batches) => {
+                for batch in batches {
+                    Self::process_packets(&mut self.views, &self.state, batch.packets);
+                }
+            }
+            Err(err) => {
+                warn!("Stopped replay: {}", err);
+                self.replay = None;
+            }
+        }
+    }
+
+    #[cfg(feature = "recording")]
+    fn clear_views(&mut self) {
+        self.views.clear();
+        self.active_view_info = None;
+    }
+
+    #[cfg(feature = "recording")]
+    fn update_recording_controls(&mut self, ui: &mut egui::Ui) {
+        if self.recorder.is_some() {
+            ui.separator();
+            ui.label("recording");
+            if ui.button("stop recording").clicked() {
+                self.stop_recording();
+            }
+        }
+
+        let Some(replay) = self.replay.as_mut() else {
+            return;
+        };
+        ui.separator();
+        let play_label = if replay.is_playing() { "⏸" } else { "▶" };
+        if ui.button(play_label).clicked() {
+            replay.set_playing(!replay.is_playing());
+        }
+        let step = ui.button("⏭").on_hover_text("step").clicked();
+        let mut time = replay.time();
+        let seek = ui
+            .add(egui::Slider::new(&mut time, 0.0..=replay.duration()).suffix(" s"))
+            .changed();
+        let mut speed = replay.speed();
+        if ui
+            .add(
+                egui::DragValue::new(&mut speed)
+                    .speed(0.05)
+                    .range(0.05..=20.0)
+                    .suffix("x"),
+            )
+            .changed()
+        {
+            replay.set_speed(speed);
+        }
+        let stop = ui.button("stop replay").clicked();
+
+        if step {
+            self.step_replay();
+        } else if seek {
+            self.seek_replay(time);
+        }
+        if stop {
+            self.stop_replay();
+        }
+    }
+
+    /// Set the directory for screenshots and frame sequences, the current directory by default
+    #[cfg(feature = "std")]
+    pub fn set_export_directory(&mut self, directory: impl Into<std::path::PathBuf>) {
+        self.exporter.directory = directory.into();
+    }
+
+    /// Save the next rendered frame of a scene or image view as png
+    #[cfg(feature = "std")]
+    pub fn save_screenshot(&mut self, view_label: &str) {
+        self.exporter.request_screenshot(view_label);
+    }
+
+    /// Write every rendered frame of a scene or image view as numbered files, until
+    /// `stop_frame_export` is called
+    ///
+    /// Only one view at a time can be exported.
+    #[cfg(feature = "std")]
+    pub fn start_frame_export(&mut self, view_label: &str, format: FrameExportFormat) {
+        self.exporter.format = format;
+        self.exporter.start_sequence(view_label);
+    }
+
+    /// Stop writing the frame sequence
+    #[cfg(feature = "std")]
+    pub fn stop_frame_export(&mut self) {
+        self.exporter.stop_sequence();
+    }
+
+    /// Send pick events, i.e. the scene entity and primitive under the cursor, to the application
+    ///
+    /// A hover event is sent whenever the cursor moves onto a different entity or primitive, and a
+    /// click event when the entity under the cursor is clicked. Events are dropped if the channel
+    /// is full.
+    ///
+    /// The scene is only picked when the cursor moved or on a click, hence no hover event is sent
+    /// while the scene changes under a resting cursor.
+    pub fn set_pick_sender(&mut self, pick_send: Sender<PickEvent>) {
+        self.pick_send = Some(pick_send);
+    }
+
+    fn send_pick_event(&self, view_label: &str, trigger: PickTrigger, pick: &ScenePick) {
+        if let Some(pick_send) = self.pick_send.as_ref() {
+            let _ = pick_send.try_send(PickEvent {
+                view_label: view_label.to_string(),
+                trigger,
+                pick: pick.clone(),
+            });
+        }
     }
 
     /// Process events.
     pub fn process_events(&mut self) {
+        self.pick_request = None;
+        let mut hover_pixel = None;
+        let mut clicked_view = None;
         for (view_label, view) in self.views.iter_mut() {
             let mut view_port_size = ImageSize::default();
+            if let Some(response) = self.responses.get(view_label) {
+                if let Some(hover_pos) = response.ui_response.hover_pos() {
+                    let pixel = hover_pos - response.ui_response.rect.min;
+                    hover_pixel = Some((
+                        view_label.clone(),
+                        VecF64::<2>::new(pixel.x as f64, pixel.y as f64),
+                    ));
+                }
+                if response.ui_response.clicked() {
+                    clicked_view = Some(view_label.clone());
+                }
+            }
             match view {
                 View::Scene(view) => {
                     if let Some(response) = self.responses.get(view_label) {
@@ -129,6 +412,35 @@ impl ViewerBase {
             }
         }
         self.responses.clear();
+
+        // picking waits for the gpu, so only pick if the cursor moved or clicked
+        match hover_pixel {
+            Some((view_label, pixel)) => {
+                let clicked = clicked_view.as_ref() == Some(&view_label);
+                if clicked || self.picked_pixel.as_ref() != Some(&(view_label.clone(), pixel)) {
+                    self.pick_request = Some(PickRequest {
+                        view_label,
+                        pixel,
+                        clicked,
+                    });
+                }
+            }
+            None => {
+                self.picked_pixel = None;
+                self.hovered_pick = None;
+            }
+        }
+    }
+
+    fn update_hovered_pick(&mut self, view_label: &str, maybe_pick: Option<ScenePick>) {
+        let hovered_pick = maybe_pick.map(|pick| (view_label.to_string(), pick));
+        if hovered_pick == self.hovered_pick {
+            return;
+        }
+        if let Some((view_label, pick)) = hovered_pick.as_ref() {
+            self.send_pick_event(view_label, PickTrigger::Hover, pick);
+        }
+        self.hovered_pick = hovered_pick;
     }
 
     /// Update bottom status bar
@@ -139,6 +451,15 @@ impl ViewerBase {
                     ui.checkbox(&mut self.show_depth, "show depth");
                     ui.checkbox(&mut self.backface_culling, "backface culling");
                 });
+                self.update_helper_controls(ui);
+                #[cfg(feature = "std")]
+                egui::ComboBox::from_label("frame export")
+                    .selected_text(self.exporter.format.label())
+                    .show_ui(ui, |ui| {
+                        for format in [FrameExportFormat::Png, FrameExportFormat::PngAndDepthTiff] {
+                            ui.selectable_value(&mut self.exporter.format, format, format.label());
+                        }
+                    });
             });
 
             let help_button_response = ui.button("❓");
@@ -177,19 +498,213 @@ impl ViewerBase {
                     ui.label("** Does not work on all touchpads.");
                 },
             );
+
+            #[cfg(feature = "recording")]
+            self.update_recording_controls(ui);
         });
     }
 
+    /// Ground grid, origin axes and scale bar controls of the scene views
+    fn update_helper_controls(&mut self, ui: &mut egui::Ui) {
+        let helpers = &mut self.scene_helpers;
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut helpers.show_grid, "grid");
+            ui.checkbox(&mut helpers.show_origin_axes, "origin axes");
+            ui.checkbox(&mut self.show_scale_bar, "scale bar");
+        });
+        if helpers.show_grid {
+            let grid = &mut helpers.grid;
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_label("grid plane")
+                    .selected_text(grid.plane.label())
+                    .show_ui(ui, |ui| {
+                        for plane in GridPlane::ALL {
+                            ui.selectable_value(&mut grid.plane, plane, plane.label());
+                        }
+                    });
+                ui.add(
+                    egui::DragValue::new(&mut grid.spacing)
+                        .speed(0.01)
+                        .range(0.001..=1000.0)
+                        .prefix("spacing: "),
+                );
+                ui.add(
+                    egui::DragValue::new(&mut grid.fade_distance)
+                        .speed(0.1)
+                        .range(0.1..=10000.0)
+                        .prefix("fade: "),
+                );
+            });
+        }
+    }
+
     /// Update the left panel.
     pub fn update_left_panel(&mut self, ui: &mut egui::Ui, _ctx: &egui::Context) {
         for (view_label, view) in self.views.iter_mut() {
-            ui.checkbox(view.enabled_mut(), view_label);
+            ui.horizontal(|ui| {
+                ui.checkbox(view.enabled_mut(), view_label);
+                #[cfg(feature = "std")]
+                if !matches!(view, View::Plot(_)) {
+                    Self::update_export_buttons(&mut self.exporter, ui, view_label);
+                }
+            });
+            if let Some(renderer) = view.renderer_mut() {
+                for (name, points) in renderer.scene.scalar_point_renderer.entities_mut() {
+                    Self::update_coloring_controls(ui, view_label, name, points);
+                }
+            }
         }
         ui.separator();
     }
 
+    /// "Color by" controls of a point cloud with scalar fields
+    fn update_coloring_controls(
+        ui: &mut egui::Ui,
+        view_label: &str,
+        name: &str,
+        points: &mut ScalarPoint3dEntity,
+    ) {
+        let mut coloring = points.coloring();
+        egui::CollapsingHeader::new(name)
+            .id_source((view_label, name))
+            .show(ui, |ui| {
+                let field_name = |field: usize| {
+                    points
+                        .field_names()
+                        .get(field)
+                        .map_or("", |name| name.as_str())
+                };
+                egui::ComboBox::new((view_label, name, "color by"), "color by")
+                    .selected_text(field_name(coloring.field))
+                    .show_ui(ui, |ui| {
+                        for field in 0..points.field_names().len() {
+                            ui.selectable_value(&mut coloring.field, field, field_name(field));
+                        }
+                    });
+                egui::ComboBox::new((view_label, name, "colormap"), "colormap")
+                    .selected_text(coloring.colormap.label())
+                    .show_ui(ui, |ui| {
+                        for colormap in Colormap::ALL {
+                            ui.selectable_value(&mut coloring.colormap, colormap, colormap.label());
+                        }
+                    });
+
+                let field_range = points.field_range(coloring.field).unwrap_or([0.0, 1.0]);
+                let mut auto_range = coloring.range.is_none();
+                ui.checkbox(&mut auto_range, "auto range");
+                coloring.range = match auto_range {
+                    true => None,
+                    false => {
+                        let mut range = coloring.range.unwrap_or(field_range);
+                        let speed = 0.01 * (field_range[1] - field_range[0]).max(1e-6);
+                        ui.horizontal(|ui| {
+                            ui.add(egui::DragValue::new(&mut range[0]).speed(speed));
+                            ui.add(egui::DragValue::new(&mut range[1]).speed(speed));
+                        });
+                        Some(range)
+                    }
+                };
+            });
+        if coloring != points.coloring() {
+            points.set_coloring(coloring);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn update_export_buttons(exporter: &mut FrameExporter, ui: &mut egui::Ui, view_label: &str) {
+        if ui.small_button("📷").on_hover_text("save png").clicked() {
+            exporter.request_screenshot(view_label);
+        }
+        if exporter.sequence_view_label() == Some(view_label) {
+            if ui
+                .small_button("⏹")
+                .on_hover_text("stop frame export")
+                .clicked()
+            {
+                exporter.stop_sequence();
+            }
+        } else if ui
+            .small_button("⏺")
+            .on_hover_text("export every frame")
+            .clicked()
+        {
+            exporter.start_sequence(view_label);
+        }
+    }
+
+    /// Median metric depth of the rendered scene, from a subsampled depth image
+    fn median_depth(depth_image: &DepthImage) -> Option<f64> {
+        const STRIDE: usize = 8;
+        let ndc_z_image = &depth_image.ndc_z_image;
+        let image_size = ndc_z_image.image_size();
+        let mut ndc_z_values: Vec<f32> = (0..image_size.height)
+            .step_by(STRIDE)
+            .flat_map(|v| {
+                (0..image_size.width)
+                    .step_by(STRIDE)
+                    .map(move |u| ndc_z_image.pixel(u, v))
+            })
+            .filter(|ndc_z| *ndc_z < 1.0)
+            .collect();
+        if ndc_z_values.is_empty() {
+            return None;
+        }
+        ndc_z_values.sort_by(|a, b| a.total_cmp(b));
+        let ndc_z = ndc_z_values[ndc_z_values.len() / 2];
+        Some(depth_image.clipping_planes.metric_z_from_ndc_z(ndc_z) as f64)
+    }
+
+    /// Scale bar in the bottom-left corner of a scene view, with a length of 1, 2 or 5 times a
+    /// power of ten meters, valid at the median depth of the scene
+    fn paint_scale_bar(ui: &egui::Ui, rect: egui::Rect, meters_per_point: f64) {
+        const TARGET_POINTS: f64 = 100.0;
+        if !(meters_per_point.is_finite() && meters_per_point > 0.0) {
+            return;
+        }
+        let target_meters = TARGET_POINTS * meters_per_point;
+        let power = 10f64.powf(target_meters.log10().floor());
+        let meters = [5.0, 2.0, 1.0]
+            .into_iter()
+            .map(|factor| factor * power)
+            .find(|meters| *meters <= target_meters)
+            .unwrap_or(power);
+        let length = (meters / meters_per_point) as f32;
+
+        let color = egui::Color32::WHITE;
+        let stroke = egui::Stroke::new(2.0, color);
+        let left = rect.left_bottom() + egui::vec2(12.0, -12.0);
+        let right = left + egui::vec2(length, 0.0);
+        let painter = ui.painter_at(rect);
+        painter.line_segment([left, right], stroke);
+        for end in [left, right] {
+            painter.line_segment([end, end - egui::vec2(0.0, 6.0)], stroke);
+        }
+        let label = if meters >= 1000.0 {
+            format!("{} km", meters / 1000.0)
+        } else if meters >= 1.0 {
+            format!("{} m", meters)
+        } else if meters >= 0.01 {
+            format!("{} cm", (meters * 1e4).round() / 1e2)
+        } else {
+            format!("{} mm", (meters * 1e6).round() / 1e3)
+        };
+        painter.text(
+            left + egui::vec2(0.5 * length, -8.0),
+            egui::Align2::CENTER_BOTTOM,
+            label,
+            egui::FontId::proportional(12.0),
+            color,
+        );
+    }
+
     /// Update bottom status bar
     pub fn update_bottom_status_bar(&mut self, ui: &mut egui::Ui, _ctx: &egui::Context) {
+        if let Some((view_label, pick)) = self.hovered_pick.as_ref() {
+            ui.label(format!(
+                "{}: {:?} {} #{}",
+                view_label, pick.kind, pick.name, pick.primitive_index
+            ));
+        }
         match self.active_view_info.as_ref() {
             Some(view_info) => {
                 if let Some(camera_properties) = view_info.camera_properties.as_ref() {
@@ -247,6 +762,17 @@ impl ViewerBase {
 
     /// Update the central panel.
     pub fn update_central_panel(&mut self, ui: &mut egui::Ui, _ctx: &egui::Context) {
+        // view label and pick result of the view under the cursor
+        let mut hovered_view: Option<(String, Option<ScenePick>)> = None;
+
+        #[cfg(feature = "std")]
+        {
+            let views = &self.views;
+            self.exporter.begin_frame(|view_label| {
+                matches!(views.get(view_label), Some(View::Scene(_) | View::Image(_)))
+            });
+        }
+
         ui.scope(|ui0| {
             if self.views.is_empty() {
                 return;
@@ -270,6 +796,14 @@ impl ViewerBase {
                     let view_aspect_ratio = view.aspect_ratio();
                     let adjusted_size =
                         get_adjusted_view_size(view_aspect_ratio, max_width, max_height);
+                    #[cfg(feature = "std")]
+                    let export_frame = self.exporter.wants_frame(view_label);
+                    #[cfg(not(feature = "std"))]
+                    let export_frame = false;
+                    let pick_pixel = match self.pick_request.as_ref() {
+                        Some(request) if &request.view_label == view_label => Some(request.pixel),
+                        _ => None,
+                    };
                     match view {
                         View::Scene(view) => {
                             let render_result = view
@@ -281,9 +815,21 @@ impl ViewerBase {
                                 .zoom(view.interaction.zoom2d())
                                 .interaction(view.interaction.marker())
                                 .backface_culling(self.backface_culling)
+                                .helpers(self.scene_helpers)
                                 .compute_depth_texture(self.show_depth)
+                                .download_rgba(export_frame)
+                                .pick(pick_pixel)
                                 .render();
 
+                            #[cfg(feature = "std")]
+                            if export_frame {
+                                self.exporter.export(view_label, &render_result);
+                            }
+                            if pick_pixel.is_some() {
+                                hovered_view =
+                                    Some((view_label.clone(), render_result.scene_pick.clone()));
+                            }
+
                             let egui_texture = if self.show_depth {
                                 render_result.depth_egui_tex_id
                             } else {
@@ -305,6 +851,22 @@ impl ViewerBase {
                                 .sense(egui::Sense::click_and_drag()),
                             );
 
+                            if self.show_scale_bar {
+                                let intrinsics = view.intrinsics();
+                                // size of a viewport point in the camera image, in pixels
+                                let image_pixels_per_point = intrinsics.image_size().width as f64
+                                    / adjusted_size.width as f64
+                                    / view.interaction.zoom2d().scaling[0];
+                                if let Some(z) = Self::median_depth(&render_result.depth_image) {
+                                    let fx = intrinsics.pinhole_model().params()[0];
+                                    Self::paint_scale_bar(
+                                        ui,
+                                        ui_response.rect,
+                                        z * image_pixels_per_point / fx,
+                                    );
+                                }
+                            }
+
                             self.responses.insert(
                                 view_label.clone(),
                                 ResponseStruct {
@@ -328,8 +890,19 @@ impl ViewerBase {
                                 .zoom(view.interaction.zoom2d())
                                 .interaction(view.interaction.marker())
                                 .backface_culling(self.backface_culling)
+                                .download_rgba(export_frame)
+                                .pick(pick_pixel)
                                 .render();
 
+                            #[cfg(feature = "std")]
+                            if export_frame {
+                                self.exporter.export(view_label, &render_result);
+                            }
+                            if pick_pixel.is_some() {
+                                hovered_view =
+                                    Some((view_label.clone(), render_result.scene_pick.clone()));
+                            }
+
                             let ui_response = ui.add(
                                 egui::Image::new(egui::load::SizedTexture {
                                     size: egui::Vec2::new(
@@ -387,6 +960,18 @@ impl ViewerBase {
                 }
             });
         });
+
+        if let Some((view_label, maybe_pick)) = hovered_view {
+            self.update_hovered_pick(&view_label, maybe_pick);
+            if let Some(request) = self.pick_request.take() {
+                if request.clicked {
+                    if let Some((view_label, pick)) = self.hovered_pick.as_ref() {
+                        self.send_pick_event(view_label, PickTrigger::Click, pick);
+                    }
+                }
+                self.picked_pixel = Some((request.view_label, request.pixel));
+            }
+        }
     }
 
     fn show_plot(ui: &mut Ui, view: &mut PlotView, adjusted_size: ViewportSize, plot_name: String) {
@@ -545,25 +1130,18 @@ impl ViewerBase {
         );
     }
 
-    pub(crate) fn process_simple_packets(
+    pub(crate) fn process_packets(
         views: &mut LinkedHashMap<String, View>,
         state: &RenderContext,
-        message_recv: &Receiver<Vec<Packet>>,
+        packets: Vec<Packet>,
     ) {
-        loop {
-            let maybe_stream = message_recv.try_recv();
-            if maybe_stream.is_err() {
-                break;
-            }
-            let stream = maybe_stream.unwrap();
-            for packet in stream {
-                match packet {
-                    Packet::Scene(packet) => SceneView::update(views, packet, state),
-                    Packet::Image(packet) => ImageView::update(views, packet, state),
-                    Packet::Plot(packets) => {
-                        for packet in packets {
-                            PlotView::update(views, packet)
-                        }
+        for packet in packets {
+            match packet {
+                Packet::Scene(packet) => SceneView::update(views, packet, state),
+                Packet::Image(packet) => ImageView::update(views, packet, state),
+                Packet::Plot(packets) => {
+                    for packet in packets {
+                        PlotView::update(views, packet)
                     }
                 }
             }
@@ -16,6 +16,7 @@ use alloc::string::ToString;
 use alloc::vec::Vec;
 use dog_tv_renderer::aspect_ratio::HasAspectRatio;
 use dog_tv_renderer::camera::properties::RenderCameraProperties;
+use dog_tv_renderer::offscreen_renderer::OffscreenRenderer;
 use linked_hash_map::LinkedHashMap;
 use sophus::image::ImageSize;
 
@@ -82,6 +83,14 @@ impl View {
         }
     }
 
+    pub(crate) fn renderer_mut(&mut self) -> Option<&mut OffscreenRenderer> {
+        match self {
+            View::Scene(view) => Some(&mut view.renderer),
+            View::Image(view) => Some(&mut view.renderer),
+            View::Plot(_) => None,
+        }
+    }
+
     pub(crate) fn camera_propterties(&self) -> RenderCameraProperties {
         match self {
             View::Scene(view) => view.renderer.camera_properties(),
@@ -8,6 +8,7 @@ use dog_tv_renderer::camera::intrinsics::RenderIntrinsics;
 use dog_tv_renderer::offscreen_renderer::OffscreenRenderer;
 use dog_tv_renderer::RenderContext;
 use linked_hash_map::LinkedHashMap;
+use log::warn;
 
 pub(crate) struct ImageView {
     pub(crate) renderer: OffscreenRenderer,
@@ -47,7 +48,13 @@ impl ImageView {
         state: &RenderContext,
     ) {
         Self::create_if_new(views, &packet, state);
-        let view = views.get_mut(&packet.view_label).unwrap();
+        let view = match views.get_mut(&packet.view_label) {
+            Some(view) => view,
+            None => {
+                warn!("View not found: {}", packet.view_label);
+                return;
+            }
+        };
 
         let view = match view {
             View::Image(view) => view,
@@ -72,6 +79,15 @@ impl ImageView {
             _ => panic!("View type mismatch"),
         };
 
+        if packet.clear_all {
+            view.renderer.clear_pixels();
+            view.renderer.clear_scene();
+        }
+        view.renderer
+            .delete_pixels(&packet.pixel_renderables_to_delete);
+        view.renderer
+            .delete_scene(&packet.scene_renderables_to_delete);
+
         view.renderer.update_pixels(packet.pixel_renderables);
         view.renderer.update_scene(packet.scene_renderables);
     }
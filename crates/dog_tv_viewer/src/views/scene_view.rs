@@ -72,6 +72,39 @@ impl SceneView {
                     warn!("View not found: {}", packet.view_label);
                 }
             }
+            SceneViewPacketContent::Delete(names) => {
+                if let Some(view) = views.get_mut(&packet.view_label) {
+                    if let View::Scene(scene_view) = view {
+                        scene_view.renderer.delete_scene(names);
+                    } else {
+                        warn!("Is not a scene-view: {}", packet.view_label);
+                    }
+                } else {
+                    warn!("View not found: {}", packet.view_label);
+                }
+            }
+            SceneViewPacketContent::ClearAll => {
+                if let Some(view) = views.get_mut(&packet.view_label) {
+                    if let View::Scene(scene_view) = view {
+                        scene_view.renderer.clear_scene();
+                    } else {
+                        warn!("Is not a scene-view: {}", packet.view_label);
+                    }
+                } else {
+                    warn!("View not found: {}", packet.view_label);
+                }
+            }
+            SceneViewPacketContent::Lighting(lighting) => {
+                if let Some(view) = views.get_mut(&packet.view_label) {
+                    if let View::Scene(scene_view) = view {
+                        scene_view.renderer.scene.lighting = *lighting;
+                    } else {
+                        warn!("Is not a scene-view: {}", packet.view_label);
+                    }
+                } else {
+                    warn!("View not found: {}", packet.view_label);
+                }
+            }
         }
     }
 